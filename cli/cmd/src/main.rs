@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
 use crossterm::{
     event::{self, Event, KeyCode, KeyModifiers},
     execute,
@@ -21,19 +22,24 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
-    io::{self, Write as IoWrite},
+    io::{self, BufRead, IsTerminal, Write as IoWrite},
     path::PathBuf,
-    process::{Command, Stdio},
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
 };
 
 #[derive(Parser)]
-#[command(name = "cmd", about = "Fuzzy search CLI commands and options")]
+#[command(
+    name = "cmd",
+    about = "Fuzzy search CLI commands and options",
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
     #[command(subcommand)]
     command: Option<Commands>,
 
     /// The CLI command to search (e.g., bun, cargo, git)
-    #[arg(conflicts_with = "command")]
     cli: Option<String>,
 
     /// Force rescan even if cache exists
@@ -47,6 +53,93 @@ struct Args {
     /// List all entries without interactive UI
     #[arg(short, long)]
     list: bool,
+
+    /// Rescan and print how each --help line was classified (no cache, no UI)
+    #[arg(long)]
+    debug_parse: bool,
+
+    /// Pick a previously run command and run it again
+    #[arg(long, conflicts_with_all = ["cli", "refresh", "list", "debug_parse"])]
+    history: bool,
+
+    /// Search every cached CLI at once, optionally starting from QUERY
+    /// (e.g. `cmd --all watch`); only CLIs scanned before are included
+    #[arg(
+        long,
+        value_name = "QUERY",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with_all = ["cli", "refresh", "debug_parse", "history", "ssh"]
+    )]
+    all: Option<String>,
+
+    /// Use a plain numbered prompt instead of the full-screen UI
+    #[arg(long, global = true)]
+    no_tui: bool,
+
+    /// Ask zsh (`whence`) what the command is, following aliases and
+    /// shell functions to the binary they run
+    #[arg(long, global = true)]
+    shell_resolve: bool,
+
+    /// Scan the CLI on a remote host (e.g. user@host); picked commands run
+    /// there too
+    #[arg(long, global = true, value_name = "HOST", conflicts_with = "shell_resolve")]
+    ssh: Option<String>,
+
+    /// When --help lists only a few flags, also parse the man page's options
+    /// (heuristic; applies when scanning, so add --refresh for cached CLIs)
+    #[arg(long, global = true)]
+    man: bool,
+
+    /// Run the picked command through `<shell> -ic`, so quoting, pipes,
+    /// aliases and functions work (default: split on spaces and exec)
+    #[arg(long, global = true, value_enum, value_name = "SHELL")]
+    exec_in: Option<ExecShell>,
+
+    /// Give up on a `--help` (or `--version`, man page) call that runs longer
+    /// than this and scan on without it (e.g. 5s, 500ms, 1m)
+    #[arg(
+        long,
+        global = true,
+        value_name = "DURATION",
+        default_value = "5s",
+        value_parser = parse_duration
+    )]
+    help_timeout: Duration,
+}
+
+/// Parse `500ms`, `5s`, `2m` or a bare number of seconds.
+fn parse_duration(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 5s, 500ms, 1m)", raw))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        _ => Err(format!("unknown unit '{}' in '{}' (use ms, s or m)", unit, raw)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum ExecShell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl ExecShell {
+    fn program(self) -> &'static str {
+        match self {
+            ExecShell::Zsh => "zsh",
+            ExecShell::Bash => "bash",
+            ExecShell::Fish => "fish",
+        }
+    }
 }
 
 #[derive(clap::Subcommand)]
@@ -72,10 +165,38 @@ enum Commands {
         #[arg(long, default_value = "1234")]
         port: u16,
     },
+    /// Parse captured --help text from a file and print the entries as JSON
+    ///
+    /// Runs the same parser as a scan without invoking the CLI, e.g.
+    /// `git remote --help > remote.txt; cmd parse-help remote.txt --command "git remote"`
+    ParseHelp {
+        /// File with the --help output ("-" for stdin)
+        file: PathBuf,
+
+        /// Command the help belongs to, with any subcommands (default: the file name)
+        #[arg(long, value_name = "NAME")]
+        command: Option<String>,
+
+        /// Print how each line was classified to stderr
+        #[arg(long)]
+        debug: bool,
+    },
+    /// Generate shell completion script for cmd
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate completions for (bash, zsh, fish, ...)
+        shell: Shell,
+    },
 }
 
+/// Bump when `CommandInfo`/`Entry` change shape so stale caches get rescanned.
+const CACHE_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CommandInfo {
+    /// Cache format version; 0 for caches (and --help-full output) without one
+    #[serde(default)]
+    schema_version: u32,
     version: String,
     entries: Vec<Entry>,
 }
@@ -92,10 +213,20 @@ struct Entry {
     description: String,
     /// Type: "subcommand" or "flag"
     entry_type: String,
+    /// CLI the entry came from, shown as a prefix when `--all` mixes several
+    #[serde(skip)]
+    tool: Option<String>,
 }
 
 impl Entry {
     fn display_text(&self) -> String {
+        match &self.tool {
+            Some(tool) => format!("[{}] {}", tool, self.entry_text()),
+            None => self.entry_text(),
+        }
+    }
+
+    fn entry_text(&self) -> String {
         // Use just the subcommand part for cleaner display
         let cmd_display = self.command.split_whitespace().collect::<Vec<_>>().join(" ");
 
@@ -110,13 +241,15 @@ impl Entry {
                 };
                 format!("{} {} - {}", cmd_display, flag_part, self.description)
             }
+            "history" => format!("{}  ({})", cmd_display, self.description),
             _ => cmd_display,
         }
     }
 
     fn search_text(&self) -> String {
         format!(
-            "{} {} {} {} {}",
+            "{} {} {} {} {} {}",
+            self.tool.as_deref().unwrap_or(""),
             self.command,
             self.short.as_deref().unwrap_or(""),
             self.long.as_deref().unwrap_or(""),
@@ -135,14 +268,408 @@ fn get_cache_dir() -> Result<PathBuf> {
 }
 
 fn get_cache_path(command: &str) -> Result<PathBuf> {
-    let safe_name = command.replace(['/', '\\'], "_");
-    Ok(get_cache_dir()?.join(format!("{}.json", safe_name)))
+    Ok(get_cache_dir()?.join(format!("{}.json", cache_file_stem(command))))
+}
+
+/// `command` made safe for a file name: resolved paths keep their slashes
+/// as `%2F`, so the CLI name can be read back (see `cache_file_name`).
+fn cache_file_stem(command: &str) -> String {
+    command
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('\\', "%5C")
+}
+
+/// The CLI a cache file stem belongs to: `%2Fhome%2Fme%2Fbin%2Ffix_perms`
+/// is `fix_perms`.
+fn cache_file_name(stem: &str) -> String {
+    let name = stem.rsplit("%2F").next().unwrap_or(stem);
+    let name = name.rsplit("%5C").next().unwrap_or(name);
+    name.replace("%25", "%")
+}
+
+/// Fail instead of prompting for passwords or host keys in the middle of a
+/// scan, and share one connection across the many `--help` calls.
+const SSH_OPTIONS: &[&str] = &[
+    "-o",
+    "BatchMode=yes",
+    "-o",
+    "ConnectTimeout=10",
+    "-o",
+    "ControlMaster=auto",
+    "-o",
+    "ControlPath=/tmp/cmd-ssh-%C",
+    "-o",
+    "ControlPersist=60",
+];
+
+/// The CLI being scanned and where it runs: locally, or on `host` over ssh.
+/// Only scanning goes through here; the UI always runs locally.
+#[derive(Debug, Clone)]
+struct Target {
+    command: String,
+    host: Option<String>,
+    /// How long one `--help`-style invocation may run before it's killed
+    help_timeout: Duration,
+}
+
+/// `--help-timeout` when a target is built without the CLI flags.
+const DEFAULT_HELP_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Target {
+    fn local(command: String) -> Self {
+        Self {
+            command,
+            host: None,
+            help_timeout: DEFAULT_HELP_TIMEOUT,
+        }
+    }
+
+    /// `command args...`, wrapped in ssh for a remote target.
+    fn process(&self, args: &[&str]) -> Command {
+        let Some(host) = &self.host else {
+            let mut cmd = Command::new(&self.command);
+            cmd.args(args);
+            return cmd;
+        };
+
+        let remote: Vec<String> = std::iter::once(self.command.as_str())
+            .chain(args.iter().copied())
+            .map(shell_quote)
+            .collect();
+        let mut cmd = Command::new("ssh");
+        cmd.args(SSH_OPTIONS)
+            .arg(host)
+            .arg(remote.join(" "))
+            .stdin(Stdio::null());
+        cmd
+    }
+
+    /// Run `command args...` for its output, killing it after `help_timeout`.
+    /// `Ok(None)` means it timed out.
+    fn output(&self, args: &[&str]) -> io::Result<Option<Output>> {
+        run_with_timeout(self.process(args), self.help_timeout)
+    }
+
+    /// Qualify `name` with the host, so remote scans get their own cache
+    /// entries (e.g. `user@host:git`).
+    fn key(&self, name: &str) -> String {
+        match &self.host {
+            Some(host) => format!("{}:{}", host, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The command line that runs a picked entry.
+    fn run_line(&self, cmd_str: &str) -> String {
+        match &self.host {
+            Some(host) => format!("ssh -t {} {}", host, cmd_str),
+            None => cmd_str.to_string(),
+        }
+    }
+}
+
+/// Run `cmd` with stdin closed and output captured, or kill it once
+/// `timeout` passes (a pager or prompt waiting on a terminal, say) and
+/// return `Ok(None)`.
+fn run_with_timeout(mut cmd: Command, timeout: Duration) -> io::Result<Option<Output>> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes while waiting, so a chatty child can't fill one and block
+    fn drain<R: io::Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut buf).ok();
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            // Grandchildren may still hold the pipes open, so the readers
+            // are left to finish on their own
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+/// Render a timeout the way `--help-timeout` takes it.
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// Resolve `command` locally, or check that `host` is reachable and has it,
+/// so auth problems and typos aren't mistaken for a CLI without help.
+fn resolve_target(
+    command: &str,
+    ssh: Option<&str>,
+    shell_resolve: bool,
+    help_timeout: Duration,
+) -> Result<Target> {
+    let Some(host) = ssh else {
+        return Ok(Target {
+            help_timeout,
+            ..Target::local(resolve_command(command, shell_resolve)?)
+        });
+    };
+
+    let probe = Target {
+        command: "command".to_string(),
+        host: Some(host.to_string()),
+        help_timeout,
+    };
+    let output = probe
+        .process(&["-v", command])
+        .output()
+        .context("Failed to run ssh")?;
+    match output.status.code() {
+        Some(0) => Ok(Target {
+            command: command.to_string(),
+            host: Some(host.to_string()),
+            help_timeout,
+        }),
+        // ssh's own failures (auth, DNS, timeouts) exit with 255
+        Some(255) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let hint = if stderr.contains("Permission denied") {
+                " (ssh runs in batch mode, so the key must be in ssh-agent or ~/.ssh/config)"
+            } else {
+                ""
+            };
+            anyhow::bail!("ssh to {} failed: {}{}", host, stderr.trim(), hint)
+        }
+        _ => anyhow::bail!("Command not found on {}: {}", host, command),
+    }
+}
+
+/// Keep at most this many runs in history.jsonl.
+const HISTORY_LIMIT: usize = 1000;
+
+/// One executed command, as a line of `history.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct HistoryEntry {
+    command: String,
+    /// Unix seconds
+    timestamp: u64,
+    cwd: Option<PathBuf>,
+}
+
+fn get_history_path() -> Result<PathBuf> {
+    Ok(get_cache_dir()?.join("history.jsonl"))
+}
+
+/// Read history oldest first, skipping lines that don't parse.
+fn load_history(path: &std::path::Path) -> Vec<HistoryEntry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Append `entry` unless it repeats the last command, trimming the file to
+/// the newest `limit` entries.
+fn append_history(path: &std::path::Path, entry: HistoryEntry, limit: usize) -> Result<()> {
+    let mut history = load_history(path);
+    if history.last().is_some_and(|last| last.command == entry.command) {
+        return Ok(());
+    }
+
+    if history.len() < limit {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        return Ok(());
+    }
+
+    history.push(entry);
+    let keep = history.split_off(history.len() - limit);
+    let mut data = String::new();
+    for entry in &keep {
+        data.push_str(&serde_json::to_string(entry)?);
+        data.push('\n');
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+fn record_history(command: &str) {
+    let entry = HistoryEntry {
+        command: command.to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        cwd: std::env::current_dir().ok(),
+    };
+    let result = get_history_path().and_then(|path| append_history(&path, entry, HISTORY_LIMIT));
+    if let Err(err) = result {
+        eprintln!("Could not record history: {}", err);
+    }
+}
+
+/// Newest first, as picker entries whose description says when and where.
+fn history_entries(history: Vec<HistoryEntry>) -> Vec<Entry> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let home = dirs::home_dir();
+
+    history
+        .into_iter()
+        .rev()
+        .map(|item| {
+            let ago = format_ago(now.saturating_sub(item.timestamp));
+            let description = match item.cwd {
+                Some(cwd) => {
+                    let cwd = match home.as_deref().and_then(|home| cwd.strip_prefix(home).ok()) {
+                        Some(rest) => PathBuf::from("~").join(rest),
+                        None => cwd,
+                    };
+                    format!("{}, {}", ago, cwd.display())
+                }
+                None => ago,
+            };
+            Entry {
+                command: item.command,
+                short: None,
+                long: None,
+                description,
+                entry_type: "history".to_string(),
+                tool: None,
+            }
+        })
+        .collect()
+}
+
+fn format_ago(secs: u64) -> String {
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// List CLI names that have a cached scan, for completing the `<cli>` argument.
+fn cached_command_names() -> Vec<String> {
+    let cache_dir = match get_cache_dir() {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = cache_files(&cache_dir)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    names.dedup();
+    names
+}
+
+/// Local scan caches in `cache_dir` as (CLI name, path), sorted by name.
+fn cache_files(cache_dir: &std::path::Path) -> Vec<(String, PathBuf)> {
+    let mut files: Vec<(String, PathBuf)> = fs::read_dir(cache_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let path = e.path();
+                    if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                        return None;
+                    }
+                    let stem = path.file_stem()?.to_str()?;
+                    // Remote scans (host:cli) can't be searched without --ssh.
+                    // Paths cached as `_home_me_bin_foo` predate `%2F` and
+                    // can't be told apart from an underscore in the name.
+                    if stem.contains(':') || stem.starts_with('_') {
+                        return None;
+                    }
+                    let name = cache_file_name(stem);
+                    (!name.is_empty()).then(|| (name, path.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    files.sort();
+    files
 }
 
-fn get_version(command: &str) -> Result<String> {
+/// Entries of every cached scan, each tagged with its CLI, for `--all`.
+/// Also returns the CLIs whose cache is outdated or unreadable; unlike a
+/// single-CLI search those are skipped rather than rescanned.
+fn load_all_cached(cache_dir: &std::path::Path) -> (Vec<Entry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut outdated = Vec::new();
+    for (name, path) in cache_files(cache_dir) {
+        let info = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<CommandInfo>(&data).ok())
+            .filter(|info| info.schema_version == CACHE_SCHEMA_VERSION);
+        let Some(info) = info else {
+            outdated.push(name);
+            continue;
+        };
+        entries.extend(info.entries.into_iter().map(|entry| Entry {
+            tool: Some(name.clone()),
+            ..entry
+        }));
+    }
+    (entries, outdated)
+}
+
+fn print_completions(shell: Shell) {
+    let names = cached_command_names();
+    let mut cmd = Args::command();
+    if !names.is_empty() {
+        cmd = cmd.mut_arg("cli", |arg| {
+            arg.value_parser(clap::builder::PossibleValuesParser::new(names))
+        });
+    }
+    clap_complete::generate(shell, &mut cmd, "cmd", &mut io::stdout());
+}
+
+fn get_version(target: &Target) -> Result<String> {
     // Try --version first, then -V, then -v
     for flag in ["--version", "-V", "-v"] {
-        if let Ok(output) = Command::new(command).arg(flag).output() {
+        if let Ok(Some(output)) = target.output(&[flag]) {
             if output.status.success() {
                 let version = String::from_utf8_lossy(&output.stdout);
                 let version = version.trim();
@@ -155,14 +682,14 @@ fn get_version(command: &str) -> Result<String> {
     Ok("unknown".to_string())
 }
 
-fn get_help(command: &str, subcommands: &[&str]) -> Result<String> {
-    let mut cmd = Command::new(command);
-    for sub in subcommands {
-        cmd.arg(sub);
-    }
-    cmd.arg("--help");
+fn get_help(target: &Target, subcommands: &[&str]) -> Result<String> {
+    let mut args = subcommands.to_vec();
+    args.push("--help");
 
-    let output = cmd.output().context("Failed to run command")?;
+    let output = target
+        .output(&args)
+        .context("Failed to run command")?
+        .with_context(|| format!("timed out after {}", format_duration(target.help_timeout)))?;
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -174,7 +701,9 @@ fn get_help(command: &str, subcommands: &[&str]) -> Result<String> {
     }
 }
 
-fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry> {
+/// Parse `--help` output into entries. With `debug`, every line is echoed to
+/// stderr along with how it was classified (section/subcommand/flag/ignored).
+fn parse_help(command: &str, subcommands: &[&str], help_text: &str, debug: bool) -> Vec<Entry> {
     let mut entries = Vec::new();
     let base_cmd = if subcommands.is_empty() {
         command.to_string()
@@ -211,6 +740,12 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
         "arguments:",
     ];
 
+    let trace = |kind: &str, line: &str| {
+        if debug {
+            eprintln!("  {:<10} | {}", kind, line);
+        }
+    };
+
     for line in help_text.lines() {
         let trimmed = line.trim().to_lowercase();
 
@@ -218,11 +753,13 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
         if cmd_headers.iter().any(|h| trimmed.starts_with(h)) {
             in_commands_section = true;
             in_flags_section = false;
+            trace("commands:", line);
             continue;
         }
         if flag_headers.iter().any(|h| trimmed.starts_with(h)) {
             in_commands_section = false;
             in_flags_section = true;
+            trace("flags:", line);
             continue;
         }
 
@@ -243,15 +780,21 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
             {
                 in_commands_section = true;
                 in_flags_section = false;
+                trace("commands:", line);
+            } else {
+                trace("heading", line);
             }
             continue;
         }
 
         // Skip empty lines but don't reset section
         if trimmed.is_empty() {
+            trace("", line);
             continue;
         }
 
+        let found_before = entries.len();
+
         // Parse subcommands
         if in_commands_section {
             // Try to parse line as: spaces + command + spaces + [example] + spaces + description
@@ -290,6 +833,7 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
                         long: None,
                         description: desc.to_string(),
                         entry_type: "subcommand".to_string(),
+                        tool: None,
                     });
                 }
             }
@@ -317,6 +861,7 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
                         long,
                         description: desc.to_string(),
                         entry_type: "flag".to_string(),
+                        tool: None,
                     });
                     matched = true;
                 }
@@ -333,6 +878,7 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
                         long,
                         description: desc.to_string(),
                         entry_type: "flag".to_string(),
+                        tool: None,
                     });
                     matched = true;
                 }
@@ -349,15 +895,30 @@ fn parse_help(command: &str, subcommands: &[&str], help_text: &str) -> Vec<Entry
                         long: None,
                         description: desc.to_string(),
                         entry_type: "flag".to_string(),
+                        tool: None,
                     });
                 }
             }
         }
+
+        match entries[found_before..].last() {
+            Some(entry) => trace(&entry.entry_type, line),
+            None => trace("ignored", line),
+        }
     }
 
     entries
 }
 
+/// Entries for captured help text, deduped like a scan. `command` may include
+/// subcommands ("git remote"), which end up in the entries' command paths.
+fn parse_help_text(command: &str, help_text: &str, debug: bool) -> Vec<Entry> {
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or(command);
+    let subcommands: Vec<&str> = words.collect();
+    dedupe_entries(parse_help(name, &subcommands, help_text, debug))
+}
+
 fn extract_subcommand_names(entries: &[Entry]) -> Vec<String> {
     entries
         .iter()
@@ -366,21 +927,194 @@ fn extract_subcommand_names(entries: &[Entry]) -> Vec<String> {
         .collect()
 }
 
-fn scan_command(command: &str, max_depth: usize) -> Result<Vec<Entry>> {
+/// Drop duplicate entries (same command, flags and type) that show up when a
+/// flag is listed in several option sections or a subcommand is picked up by
+/// more than one heuristic. Keeps the entry with the longest description.
+fn dedupe_entries(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut seen: std::collections::HashMap<(String, Option<String>, Option<String>, String), usize> =
+        std::collections::HashMap::new();
+    let mut deduped: Vec<Entry> = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let key = (
+            entry.command.clone(),
+            entry.short.clone(),
+            entry.long.clone(),
+            entry.entry_type.clone(),
+        );
+        match seen.get(&key) {
+            Some(&idx) => {
+                if entry.description.len() > deduped[idx].description.len() {
+                    deduped[idx] = entry;
+                }
+            }
+            None => {
+                seen.insert(key, deduped.len());
+                deduped.push(entry);
+            }
+        }
+    }
+
+    deduped
+}
+
+/// With `--man`, the man page is consulted when top-level `--help` yields
+/// fewer flags than this.
+const MAN_FALLBACK_MIN_FLAGS: usize = 5;
+
+/// `man <name> | col -bx` for the target's binary, run on the target's host.
+fn get_man_page(target: &Target) -> Option<String> {
+    let command = target.command.as_str();
+    let name = command.rsplit('/').next().unwrap_or(command);
+    let shell = Target {
+        command: "sh".to_string(),
+        host: target.host.clone(),
+        help_timeout: target.help_timeout,
+    };
+    // Wide pages keep long flag lines from wrapping
+    let output = shell
+        .output(&["-c", "MANWIDTH=200 man \"$1\" 2>/dev/null | col -bx", "sh", name])
+        .ok()??;
+    let text = String::from_utf8_lossy(&output.stdout).into_owned();
+    (output.status.success() && !text.trim().is_empty()).then_some(text)
+}
+
+/// Parse flags from a rendered man page. Looks in sections whose heading
+/// mentions OPTIONS, plus DESCRIPTION (where BSD pages list them). Handles
+/// both `-a, --all` followed by an indented description (GNU) and
+/// `-a      Description` on one line (mdoc). Flag lines must share the
+/// indent of the section's first one, so flags quoted in prose are skipped.
+fn parse_man_options(command: &str, man_text: &str) -> Vec<Entry> {
+    let lines: Vec<&str> = man_text.lines().collect();
+    let mut entries = Vec::new();
+    let mut in_options = false;
+    let mut flag_indent: Option<usize> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+
+        // Section headings are flush left and upper case
+        if indent == 0 && !trimmed.is_empty() {
+            let heading = trimmed.to_uppercase() == trimmed;
+            if heading {
+                in_options = trimmed.contains("OPTIONS") || trimmed == "DESCRIPTION";
+                flag_indent = None;
+            }
+            continue;
+        }
+        if !in_options || !trimmed.starts_with('-') || trimmed == "-" {
+            continue;
+        }
+        if flag_indent.is_some_and(|expected| expected != indent) {
+            continue;
+        }
+
+        // "-a, --all   Description" or "-I pattern" (description below)
+        let (spec, inline_desc) = match trimmed.split_once("  ") {
+            Some((spec, desc)) => (spec.trim(), desc.trim()),
+            None => (trimmed, ""),
+        };
+        let mut short = None;
+        let mut long = None;
+        for token in spec.split([',', ' ']).filter(|t| !t.is_empty()) {
+            if let Some(name) = token.strip_prefix("--") {
+                // "--color[=WHEN]" and "--block-size=SIZE" are run as the bare flag
+                let flag_name = name.split(['[', '=']).next().unwrap_or("");
+                let valid = !flag_name.is_empty()
+                    && flag_name.chars().all(|c| c.is_alphanumeric() || c == '-');
+                if long.is_none() && valid {
+                    long = Some(format!("--{}", flag_name));
+                }
+            } else if token.len() == 2 && token.starts_with('-') && short.is_none() {
+                let c = token.chars().nth(1).unwrap_or(' ');
+                if c.is_ascii_alphanumeric() || "@#?".contains(c) {
+                    short = Some(token.to_string());
+                }
+            }
+        }
+        if short.is_none() && long.is_none() {
+            continue;
+        }
+        flag_indent = Some(indent);
+
+        // Otherwise the description is the indented paragraph that follows
+        let description = if inline_desc.is_empty() {
+            lines[i + 1..]
+                .iter()
+                .take_while(|l| {
+                    let l_indent = l.len() - l.trim_start().len();
+                    !l.trim().is_empty() && l_indent > indent
+                })
+                .map(|l| l.trim())
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            inline_desc.to_string()
+        };
+        // First sentence is enough for the picker
+        let description = match description.find(". ") {
+            Some(end) => description[..=end].to_string(),
+            None => description,
+        };
+
+        entries.push(Entry {
+            command: command.to_string(),
+            short,
+            long,
+            description,
+            entry_type: "flag".to_string(),
+            tool: None,
+        });
+    }
+
+    entries
+}
+
+/// Flag name without an `=VALUE` part, for matching man and help entries.
+fn flag_name(flag: &str) -> &str {
+    flag.split('=').next().unwrap_or(flag)
+}
+
+/// Add man page flags that `command --help` didn't already list.
+fn merge_man_entries(entries: &mut Vec<Entry>, command: &str, man_entries: Vec<Entry>) -> usize {
+    let mut known: std::collections::HashSet<String> = entries
+        .iter()
+        .filter(|e| e.entry_type == "flag" && e.command == command)
+        .flat_map(|e| [e.short.as_deref(), e.long.as_deref().map(flag_name)])
+        .flatten()
+        .map(String::from)
+        .collect();
+
+    let before = entries.len();
+    for entry in man_entries {
+        let names = [entry.short.as_deref(), entry.long.as_deref().map(flag_name)];
+        if names.iter().flatten().any(|name| known.contains(*name)) {
+            continue;
+        }
+        known.extend(names.iter().flatten().map(|name| name.to_string()));
+        entries.push(entry);
+    }
+    entries.len() - before
+}
+
+fn scan_command(target: &Target, max_depth: usize, debug: bool, man: bool) -> Result<Vec<Entry>> {
     let mut all_entries = Vec::new();
     let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
 
     fn scan_recursive(
-        command: &str,
+        target: &Target,
         subcommands: &[&str],
         depth: usize,
         max_depth: usize,
+        debug: bool,
         all_entries: &mut Vec<Entry>,
         visited: &mut std::collections::HashSet<String>,
     ) -> Result<()> {
         if depth > max_depth {
             return Ok(());
         }
+        let command = target.command.as_str();
 
         let key = format!("{} {}", command, subcommands.join(" "));
         if visited.contains(&key) {
@@ -388,15 +1122,27 @@ fn scan_command(command: &str, max_depth: usize) -> Result<Vec<Entry>> {
         }
         visited.insert(key);
 
-        eprint!("\rScanning: {} {}...", command, subcommands.join(" "));
-        io::stderr().flush().ok();
+        if debug {
+            eprintln!("\n=== {} {} --help ===", command, subcommands.join(" "));
+        } else {
+            eprint!("\rScanning: {} {}...", command, subcommands.join(" "));
+            io::stderr().flush().ok();
+        }
 
-        let help_text = match get_help(command, subcommands) {
+        let help_text = match get_help(target, subcommands) {
             Ok(text) => text,
-            Err(_) => return Ok(()), // Skip if help fails
+            Err(err) => {
+                if debug {
+                    eprintln!("  help failed: {}", err);
+                } else if err.to_string().starts_with("timed out") {
+                    // Hangs are worth knowing about; other failures are usual
+                    eprintln!("\rSkipped {} {}: {}", command, subcommands.join(" "), err);
+                }
+                return Ok(()); // Skip if help fails
+            }
         };
 
-        let entries = parse_help(command, subcommands, &help_text);
+        let entries = parse_help(command, subcommands, &help_text, debug);
         let sub_names = extract_subcommand_names(&entries);
 
         all_entries.extend(entries);
@@ -411,25 +1157,61 @@ fn scan_command(command: &str, max_depth: usize) -> Result<Vec<Entry>> {
             let owned_subs: Vec<String> = new_subs.iter().map(|s| s.to_string()).collect();
             let refs: Vec<&str> = owned_subs.iter().map(|s| s.as_str()).collect();
 
-            scan_recursive(command, &refs, depth + 1, max_depth, all_entries, visited)?;
+            scan_recursive(
+                target,
+                &refs,
+                depth + 1,
+                max_depth,
+                debug,
+                all_entries,
+                visited,
+            )?;
         }
 
         Ok(())
     }
 
-    scan_recursive(command, &[], 0, max_depth, &mut all_entries, &mut visited)?;
+    scan_recursive(
+        target,
+        &[],
+        0,
+        max_depth,
+        debug,
+        &mut all_entries,
+        &mut visited,
+    )?;
+
+    // Terse top-level --help: fill in from the man page
+    let command = target.command.as_str();
+    let top_flags = all_entries
+        .iter()
+        .filter(|e| e.entry_type == "flag" && e.command == command)
+        .count();
+    if man && top_flags < MAN_FALLBACK_MIN_FLAGS {
+        match get_man_page(target) {
+            Some(page) => {
+                let added = merge_man_entries(&mut all_entries, command, parse_man_options(command, &page));
+                if debug {
+                    eprintln!("\n=== man {} ===\n  merged {} flags", command, added);
+                }
+            }
+            None if debug => eprintln!("\n=== man {} ===\n  no man page", command),
+            None => {}
+        }
+    }
+    let all_entries = dedupe_entries(all_entries);
     eprintln!("\rScanned {} entries.                    ", all_entries.len());
 
     Ok(all_entries)
 }
 
 /// Collect deep help output for a command and all subcommands
-fn collect_deep_help(command: &str, max_depth: usize) -> Result<String> {
+fn collect_deep_help(target: &Target, max_depth: usize) -> Result<String> {
     let mut output = String::new();
     let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
 
     fn collect_recursive(
-        command: &str,
+        target: &Target,
         subcommands: &[String],
         depth: usize,
         max_depth: usize,
@@ -439,6 +1221,7 @@ fn collect_deep_help(command: &str, max_depth: usize) -> Result<String> {
         if depth > max_depth {
             return Ok(());
         }
+        let command = target.command.as_str();
 
         let key = if subcommands.is_empty() {
             command.to_string()
@@ -456,7 +1239,7 @@ fn collect_deep_help(command: &str, max_depth: usize) -> Result<String> {
 
         // Build the command with subcommands
         let refs: Vec<&str> = subcommands.iter().map(|s| s.as_str()).collect();
-        let help_text = match get_help(command, &refs) {
+        let help_text = match get_help(target, &refs) {
             Ok(text) => text,
             Err(_) => return Ok(()), // Skip if help fails
         };
@@ -473,20 +1256,20 @@ fn collect_deep_help(command: &str, max_depth: usize) -> Result<String> {
         output.push_str("\n");
 
         // Parse to find subcommands
-        let entries = parse_help(command, &refs, &help_text);
+        let entries = parse_help(command, &refs, &help_text, false);
         let sub_names = extract_subcommand_names(&entries);
 
         // Recursively collect subcommands
         for sub_name in sub_names {
             let mut new_subs = subcommands.to_vec();
             new_subs.push(sub_name);
-            collect_recursive(command, &new_subs, depth + 1, max_depth, output, visited)?;
+            collect_recursive(target, &new_subs, depth + 1, max_depth, output, visited)?;
         }
 
         Ok(())
     }
 
-    collect_recursive(command, &[], 0, max_depth, &mut output, &mut visited)?;
+    collect_recursive(target, &[], 0, max_depth, &mut output, &mut visited)?;
     eprintln!("\rCollected help from {} commands.        ", visited.len());
 
     Ok(output)
@@ -513,8 +1296,9 @@ fn query_lm_studio(
         .collect();
 
     let commands_context = commands_list.join("\n");
+    let prompt_config = load_prompt_config(command)?.unwrap_or_default();
 
-    let system_prompt = format!(
+    let default_prompt = format!(
         r#"You are a CLI command assistant. Given a natural language query, output ONLY the exact command to run.
 
 Available commands for `{command}`:
@@ -527,13 +1311,24 @@ Rules:
 4. Use the most appropriate command from the list
 5. Do not explain, just output the command"#
     );
+    let system_prompt = match &prompt_config.system {
+        Some(custom) => custom
+            .replace("{command}", command)
+            .replace("{commands}", &commands_context),
+        None => default_prompt,
+    };
+
+    // Few-shot examples go in as prior turns of the conversation
+    let mut messages = vec![serde_json::json!({"role": "system", "content": system_prompt})];
+    for example in &prompt_config.examples {
+        messages.push(serde_json::json!({"role": "user", "content": example.query}));
+        messages.push(serde_json::json!({"role": "assistant", "content": example.command}));
+    }
+    messages.push(serde_json::json!({"role": "user", "content": query}));
 
     let payload = serde_json::json!({
         "model": "qwen3-8b",
-        "messages": [
-            {"role": "system", "content": system_prompt},
-            {"role": "user", "content": query}
-        ],
+        "messages": messages,
         "temperature": 0.1,
         "max_tokens": 200,
         "stream": false
@@ -576,6 +1371,167 @@ Rules:
     Ok(content.trim().to_string())
 }
 
+/// Per-CLI AI prompt overrides from `~/.config/cmd/<cli>.prompt.toml`:
+///
+/// ```toml
+/// # Optional; {command} and {commands} expand to the CLI and its subcommands
+/// system = "..."
+///
+/// [[example]]
+/// query = "deploy to staging"
+/// command = "flow deploy --env staging"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct PromptConfig {
+    system: Option<String>,
+    #[serde(default, rename = "example")]
+    examples: Vec<PromptExample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptExample {
+    query: String,
+    command: String,
+}
+
+fn load_prompt_config(command: &str) -> Result<Option<PromptConfig>> {
+    let Some(home) = dirs::home_dir() else {
+        return Ok(None);
+    };
+    // `command` may be a resolved path like ~/bin/tool
+    let name = std::path::Path::new(command)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| command.to_string());
+    let path = home
+        .join(".config")
+        .join("cmd")
+        .join(format!("{}.prompt.toml", name));
+
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read {}", path.display()))
+        }
+    };
+    let config = toml::from_str(&text)
+        .with_context(|| format!("Invalid prompt config {}", path.display()))?;
+    Ok(Some(config))
+}
+
+/// Whether colors should be disabled (https://no-color.org).
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+fn fg_style(color: Color) -> Style {
+    if no_color() {
+        Style::default()
+    } else {
+        Style::default().fg(color)
+    }
+}
+
+fn highlight_style() -> Style {
+    if no_color() {
+        Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    } else {
+        Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+    }
+}
+
+/// The full-screen UI needs a terminal on both ends; fall back to a plain
+/// prompt in CI, over pipes, or when asked to.
+fn use_tui(no_tui: bool) -> bool {
+    !no_tui && io::stdout().is_terminal() && io::stdin().is_terminal()
+}
+
+fn read_prompt(prompt: &str) -> Result<Option<String>> {
+    eprint!("{}", prompt);
+    io::stderr().flush().ok();
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None); // EOF
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Plain line-based fallback for `run_unified_ui`: type a query to filter,
+/// then a number to pick. Prompts go to stderr so stdout stays clean.
+fn run_plain_ui(
+    command: &str,
+    entries: Vec<Entry>,
+    port: u16,
+    ai_mode: bool,
+    query: &str,
+) -> Result<Option<UiResult>> {
+    const MAX_SHOWN: usize = 20;
+
+    if ai_mode {
+        let Some(query) = read_prompt("AI query: ")? else {
+            return Ok(Some(UiResult::Cancelled));
+        };
+        if query.is_empty() {
+            return Ok(Some(UiResult::Cancelled));
+        }
+        let cmd = query_lm_studio(&query, command, &entries, port)?;
+        eprintln!("Suggested: {}", cmd);
+        return match read_prompt("Run it? [y/N] ")? {
+            Some(answer) if answer.eq_ignore_ascii_case("y") => Ok(Some(UiResult::Command(cmd))),
+            _ => Ok(Some(UiResult::Cancelled)),
+        };
+    }
+
+    let mut app = App::new(entries);
+    let mut query = Some(query.to_string()).filter(|q| !q.is_empty());
+
+    loop {
+        let input = match query.take() {
+            Some(query) => query,
+            None => match read_prompt("Search (empty to cancel): ")? {
+                Some(input) => input,
+                None => return Ok(Some(UiResult::Cancelled)),
+            },
+        };
+        if input.is_empty() {
+            return Ok(Some(UiResult::Cancelled));
+        }
+
+        app.input = input;
+        app.update_filter();
+
+        if app.filtered.is_empty() {
+            eprintln!("No matches.");
+            continue;
+        }
+
+        for (i, (_, entry)) in app.filtered.iter().take(MAX_SHOWN).enumerate() {
+            eprintln!("{:>3}) {}", i + 1, entry.display_text());
+        }
+        if app.filtered.len() > MAX_SHOWN {
+            eprintln!("  ... {} more", app.filtered.len() - MAX_SHOWN);
+        }
+
+        let Some(choice) = read_prompt("Select # (or new search): ")? else {
+            return Ok(Some(UiResult::Cancelled));
+        };
+        match choice.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= app.filtered.len().min(MAX_SHOWN) => {
+                return Ok(Some(UiResult::Entry(app.filtered[n - 1].1.clone())));
+            }
+            Ok(_) => eprintln!("Out of range."),
+            Err(_) if choice.is_empty() => return Ok(Some(UiResult::Cancelled)),
+            Err(_) => {
+                // Treat anything else as a new query
+                app.input = choice;
+                app.update_filter();
+            }
+        }
+    }
+}
+
 /// UI Mode - Search (fuzzy filter) or AI (natural language)
 #[derive(PartialEq, Clone, Copy)]
 enum UiMode {
@@ -597,6 +1553,7 @@ fn run_unified_ui(
     entries: Vec<Entry>,
     port: u16,
     start_in_ai_mode: bool,
+    query: &str,
 ) -> Result<Option<UiResult>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -612,6 +1569,11 @@ fn run_unified_ui(
 
     // Search mode state
     let mut app = App::new(entries.clone());
+    if !query.is_empty() {
+        app.input = query.to_string();
+        cursor_pos = app.input.len();
+        app.update_filter();
+    }
 
     // AI mode state
     let mut ai_suggested_cmd = String::new();
@@ -633,7 +1595,7 @@ fn run_unified_ui(
 
                     // Input box
                     let input_widget = Paragraph::new(app.input.as_str())
-                        .style(Style::default().fg(Color::Yellow))
+                        .style(fg_style(Color::Yellow))
                         .block(
                             Block::default()
                                 .borders(Borders::ALL)
@@ -648,8 +1610,8 @@ fn run_unified_ui(
                         .iter()
                         .map(|(_, entry)| {
                             let style = match entry.entry_type.as_str() {
-                                "subcommand" => Style::default().fg(Color::Cyan),
-                                _ => Style::default().fg(Color::White),
+                                "subcommand" => fg_style(Color::Cyan),
+                                _ => fg_style(Color::White),
                             };
                             let text = entry.display_text();
                             let max_len = chunks[1].width.saturating_sub(4) as usize;
@@ -668,11 +1630,7 @@ fn run_unified_ui(
                                 .borders(Borders::ALL)
                                 .title(" Results (Enter=run, Ctrl+O=copy, Esc=cancel) "),
                         )
-                        .highlight_style(
-                            Style::default()
-                                .bg(Color::DarkGray)
-                                .add_modifier(Modifier::BOLD),
-                        )
+                        .highlight_style(highlight_style())
                         .highlight_symbol("> ");
                     f.render_stateful_widget(list, chunks[1], &mut app.list_state);
                 }
@@ -710,18 +1668,18 @@ fn run_unified_ui(
                         )
                     };
                     let input_widget = Paragraph::new(input_display.as_str())
-                        .style(Style::default().fg(input_color))
+                        .style(fg_style(input_color))
                         .block(Block::default().borders(Borders::ALL).title(input_title));
                     f.render_widget(input_widget, chunks[0]);
                     f.set_cursor_position((chunks[0].x + input_cursor as u16 + 1, chunks[0].y + 1));
 
                     // Suggested command
                     let (cmd_style, cmd_display) = if ai_loading {
-                        (Style::default().fg(Color::Yellow), "Loading...".to_string())
+                        (fg_style(Color::Yellow), "Loading...".to_string())
                     } else if ai_suggested_cmd.is_empty() {
-                        (Style::default().fg(Color::DarkGray), "(waiting for query...)".to_string())
+                        (fg_style(Color::DarkGray), "(waiting for query...)".to_string())
                     } else {
-                        (Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD), ai_suggested_cmd.clone())
+                        (fg_style(Color::Cyan).add_modifier(Modifier::BOLD), ai_suggested_cmd.clone())
                     };
                     let cmd_widget = Paragraph::new(cmd_display)
                         .style(cmd_style)
@@ -730,7 +1688,7 @@ fn run_unified_ui(
 
                     // Status
                     let status_widget = Paragraph::new(ai_status.as_str())
-                        .style(Style::default().fg(Color::White))
+                        .style(fg_style(Color::White))
                         .block(Block::default().borders(Borders::ALL).title(" Status "));
                     f.render_widget(status_widget, chunks[2]);
                 }
@@ -934,11 +1892,8 @@ fn run_unified_ui(
 }
 
 /// Try to get command info via --help-full (instant, no scanning needed).
-fn try_help_full(command: &str) -> Option<CommandInfo> {
-    let output = Command::new(command)
-        .arg("--help-full")
-        .output()
-        .ok()?;
+fn try_help_full(target: &Target) -> Option<CommandInfo> {
+    let output = target.output(&["--help-full"]).ok()??;
 
     if !output.status.success() {
         return None;
@@ -953,9 +1908,16 @@ fn get_help_full_cache_path() -> Result<PathBuf> {
     Ok(get_cache_dir()?.join("help-full-commands.txt"))
 }
 
+/// Name recorded in the help-full cache: the binary's file name, qualified
+/// with the host for remote targets.
+fn help_full_name(target: &Target) -> String {
+    let command = target.command.as_str();
+    target.key(command.rsplit('/').next().unwrap_or(command))
+}
+
 /// Check if command is known to support --help-full (from cache).
-fn supports_help_full(command: &str) -> bool {
-    let base = command.rsplit('/').next().unwrap_or(command);
+fn supports_help_full(target: &Target) -> bool {
+    let base = help_full_name(target);
 
     let cache_path = match get_help_full_cache_path() {
         Ok(p) => p,
@@ -972,8 +1934,8 @@ fn supports_help_full(command: &str) -> bool {
 }
 
 /// Mark a command as supporting --help-full.
-fn mark_supports_help_full(command: &str) {
-    let base = command.rsplit('/').next().unwrap_or(command);
+fn mark_supports_help_full(target: &Target) {
+    let base = help_full_name(target);
 
     let cache_path = match get_help_full_cache_path() {
         Ok(p) => p,
@@ -988,28 +1950,49 @@ fn mark_supports_help_full(command: &str) {
         .map(|c| c.lines().map(|s| s.to_string()).collect())
         .unwrap_or_default();
 
-    if !commands.iter().any(|c| c == base) {
-        commands.push(base.to_string());
+    if !commands.contains(&base) {
+        commands.push(base);
         let _ = fs::write(&cache_path, commands.join("\n"));
     }
 }
 
-fn load_or_scan(command: &str, refresh: bool) -> Result<CommandInfo> {
+/// Read a cache file, discarding it if it can't be parsed or was written with
+/// a different schema. Returns None when the command needs a rescan.
+fn load_cached(path: &std::path::Path) -> Option<CommandInfo> {
+    let data = fs::read_to_string(path).ok()?;
+    let reason = match serde_json::from_str::<CommandInfo>(&data) {
+        Ok(info) if info.schema_version == CACHE_SCHEMA_VERSION => return Some(info),
+        Ok(info) => format!(
+            "cache format v{} is outdated (now v{})",
+            info.schema_version, CACHE_SCHEMA_VERSION
+        ),
+        Err(err) => format!("cache is unreadable ({})", err),
+    };
+
+    eprintln!("{}, rescanning...", reason);
+    let _ = fs::remove_file(path);
+    None
+}
+
+fn load_or_scan(target: &Target, refresh: bool, man: bool) -> Result<CommandInfo> {
     // Check if command is known to support --help-full
-    if supports_help_full(command) {
-        if let Some(info) = try_help_full(command) {
+    if supports_help_full(target) {
+        if let Some(info) = try_help_full(target) {
             return Ok(info);
         }
     }
 
-    let cache_path = get_cache_path(command)?;
+    let command = target.key(&target.command);
+    let cache_path = get_cache_path(&command)?;
 
     // Check cache first
-    if !refresh && cache_path.exists() {
-        let data = fs::read_to_string(&cache_path)?;
-        let cached: CommandInfo = serde_json::from_str(&data)?;
-
-        let current_version = get_version(command)?;
+    let cached = if !refresh && cache_path.exists() {
+        load_cached(&cache_path)
+    } else {
+        None
+    };
+    if let Some(cached) = cached {
+        let current_version = get_version(target)?;
         if cached.version == current_version {
             eprintln!("Using cached data for {} ({})", command, current_version);
             return Ok(cached);
@@ -1021,8 +2004,9 @@ fn load_or_scan(command: &str, refresh: bool) -> Result<CommandInfo> {
     }
 
     // Before scanning, try --help-full once (discover new commands that support it)
-    if let Some(info) = try_help_full(command) {
-        mark_supports_help_full(command);
+    if let Some(mut info) = try_help_full(target) {
+        mark_supports_help_full(target);
+        info.schema_version = CACHE_SCHEMA_VERSION;
         let data = serde_json::to_string_pretty(&info)?;
         fs::write(&cache_path, data)?;
         return Ok(info);
@@ -1030,10 +2014,11 @@ fn load_or_scan(command: &str, refresh: bool) -> Result<CommandInfo> {
 
     // Fall back to scanning
     eprintln!("Scanning {}...", command);
-    let current_version = get_version(command)?;
-    let entries = scan_command(command, 3)?;
+    let current_version = get_version(target)?;
+    let entries = scan_command(target, 3, false, man)?;
 
     let info = CommandInfo {
+        schema_version: CACHE_SCHEMA_VERSION,
         version: current_version,
         entries,
     };
@@ -1138,7 +2123,8 @@ fn build_command_string(entry: &Entry) -> String {
 
 /// Resolve command to an executable path.
 /// Falls back to ~/bin/<cmd> if `which` fails (e.g., for shell functions).
-fn resolve_command(command: &str) -> Result<String> {
+/// With `shell_resolve`, asks zsh instead so aliases and functions work.
+fn resolve_command(command: &str, shell_resolve: bool) -> Result<String> {
     // If it's already a path, use it directly
     if command.contains('/') {
         if PathBuf::from(command).exists() {
@@ -1147,6 +2133,10 @@ fn resolve_command(command: &str) -> Result<String> {
         anyhow::bail!("Command not found: {}", command);
     }
 
+    if shell_resolve {
+        return shell_resolve_command(command);
+    }
+
     // Try `which` first
     let which = Command::new("which")
         .arg(command)
@@ -1169,13 +2159,272 @@ fn resolve_command(command: &str) -> Result<String> {
         }
     }
 
-    anyhow::bail!("Command not found: {} (not in PATH or ~/bin/)", command)
+    anyhow::bail!(
+        "Command not found: {} (not in PATH or ~/bin/; try --shell-resolve for aliases and functions)",
+        command
+    )
+}
+
+/// What `whence -v` says a name is.
+#[derive(Debug, PartialEq)]
+enum WhenceKind {
+    Path(PathBuf),
+    Alias(String),
+    Function,
+    Builtin,
+    NotFound,
+}
+
+/// Parse the last line of `whence -v <name>` (an interactive shell may print
+/// other noise first).
+fn parse_whence(name: &str, output: &str) -> WhenceKind {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty())
+        .unwrap_or("");
+
+    let Some(rest) = line.strip_prefix(&format!("{} is ", name)) else {
+        return WhenceKind::NotFound;
+    };
+    if let Some(alias) = rest.strip_prefix("an alias for ") {
+        WhenceKind::Alias(alias.to_string())
+    } else if rest.starts_with("a shell function") {
+        WhenceKind::Function
+    } else if rest.contains("builtin") || rest.contains("reserved word") {
+        WhenceKind::Builtin
+    } else if rest.starts_with('/') {
+        WhenceKind::Path(PathBuf::from(rest))
+    } else {
+        WhenceKind::NotFound
+    }
+}
+
+/// Run `whence <flag> <name>` in an interactive zsh so aliases and functions
+/// from the user's zshrc are visible. The name is passed as `$1`, never
+/// spliced into the script.
+fn zsh_whence(flag: &str, name: &str) -> Result<String> {
+    let output = Command::new("zsh")
+        .args(["-ic", &format!("whence {} -- \"$1\"", flag), "zsh", name])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run zsh")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Words that can start a line of a function body without being the
+/// program it wraps.
+const SHELL_SKIP_WORDS: &[&str] = &[
+    "!", "{", "}", "[", "[[", ".", "builtin", "case", "cd", "command", "declare", "do", "done",
+    "echo", "elif", "else", "emulate", "esac", "eval", "exec", "export", "false", "fi", "for",
+    "if", "local", "nocorrect", "noglob", "print", "printf", "readonly", "return", "set",
+    "setopt", "shift", "source", "test", "then", "time", "true", "typeset", "unsetopt", "until",
+    "while",
+];
+
+/// Program names a function body invokes, in order of appearance.
+fn function_candidates(body: &str) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::new();
+
+    // First line is the `name () {` header
+    for line in body.lines().skip(1) {
+        for segment in line.split(['|', ';', '&']) {
+            // The first word that isn't a keyword or assignment is the program;
+            // if it's a variable, test operand, comment etc. there's none
+            let word = segment
+                .split_whitespace()
+                .map(|w| w.trim_matches(|c| c == '"' || c == '\''))
+                .find(|w| !w.is_empty() && !SHELL_SKIP_WORDS.contains(w) && !is_assignment(w))
+                .filter(|w| w.starts_with(|c: char| c.is_alphanumeric() || "/~._".contains(c)));
+            if let Some(word) = word {
+                if !candidates.iter().any(|c| c == word) {
+                    candidates.push(word.to_string());
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Follow aliases and shell functions through zsh to a binary we can scan.
+fn shell_resolve_command(command: &str) -> Result<String> {
+    let mut name = command.to_string();
+
+    // Aliases can point at other aliases; give up on long chains
+    for _ in 0..5 {
+        match parse_whence(&name, &zsh_whence("-v", &name)?) {
+            WhenceKind::Path(path) => {
+                if name != command {
+                    eprintln!("{} resolves to {}", command, path.display());
+                }
+                return Ok(path.to_string_lossy().to_string());
+            }
+            WhenceKind::Alias(expansion) => {
+                let Some(next) = expansion.split_whitespace().find(|w| !is_assignment(w)) else {
+                    anyhow::bail!("{} is an empty alias", name);
+                };
+                name = next.to_string();
+            }
+            WhenceKind::Function => {
+                let body = zsh_whence("-f", &name)?;
+                for candidate in function_candidates(&body) {
+                    if candidate == name {
+                        continue;
+                    }
+                    if let WhenceKind::Path(path) =
+                        parse_whence(&candidate, &zsh_whence("-v", &candidate)?)
+                    {
+                        eprintln!(
+                            "{} is a shell function; scanning {} ({})",
+                            name,
+                            candidate,
+                            path.display()
+                        );
+                        return Ok(path.to_string_lossy().to_string());
+                    }
+                }
+                anyhow::bail!(
+                    "{} is a shell function that doesn't call a binary cmd can scan",
+                    name
+                );
+            }
+            WhenceKind::Builtin => {
+                anyhow::bail!("{} is a zsh builtin; it has no --help to scan", name);
+            }
+            WhenceKind::NotFound => {
+                anyhow::bail!("Command not found: {} (zsh doesn't know it either)", name);
+            }
+        }
+    }
+
+    anyhow::bail!("{} is an alias chain that's too deep to follow", command)
+}
+
+/// Run `cmd_str`, record it in history, and exit with its status. Without
+/// `shell` it's split on whitespace and exec'd directly; with one, the whole
+/// line goes to an interactive `<shell> -ic`.
+fn exec_command(cmd_str: &str, shell: Option<ExecShell>) -> Result<()> {
+    let parts: Vec<&str> = cmd_str.split_whitespace().collect();
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    record_history(cmd_str);
+    let mut cmd = match shell {
+        Some(shell) => {
+            let mut cmd = Command::new(shell.program());
+            cmd.arg("-ic").arg(cmd_str);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new(parts[0]);
+            cmd.args(&parts[1..]);
+            cmd
+        }
+    };
+    let status = cmd
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Fuzzy pick over past runs; the chosen one runs again.
+fn run_history(print_only: bool, no_tui: bool, shell: Option<ExecShell>) -> Result<()> {
+    let entries = history_entries(load_history(&get_history_path()?));
+    if entries.is_empty() {
+        eprintln!("No history yet");
+        return Ok(());
+    }
+
+    // Default LM Studio port
+    let port = 1234;
+
+    let result = if use_tui(no_tui) {
+        run_unified_ui("history", entries, port, false, "")?
+    } else {
+        run_plain_ui("history", entries, port, false, "")?
+    };
+
+    let cmd_str = match result {
+        Some(UiResult::Entry(entry)) => build_command_string(&entry),
+        Some(UiResult::Command(cmd_str)) => cmd_str,
+        Some(UiResult::Copied) | Some(UiResult::Cancelled) | None => return Ok(()),
+    };
+    println!("{}", cmd_str);
+    if !print_only {
+        exec_command(&cmd_str, shell)?;
+    }
+    Ok(())
 }
 
-fn run_search(command: &str, refresh: bool, print_only: bool, list: bool) -> Result<()> {
-    let resolved = resolve_command(command)?;
+/// Fuzzy pick across the cached entries of every CLI; the chosen command
+/// runs as-is, since each entry already names its CLI.
+fn run_all(query: &str, args: &Args) -> Result<()> {
+    let (entries, outdated) = load_all_cached(&get_cache_dir()?);
+    if !outdated.is_empty() {
+        eprintln!(
+            "Skipped outdated caches for {} (run `cmd <CLI>` to rescan)",
+            outdated.join(", ")
+        );
+    }
+    if entries.is_empty() {
+        eprintln!("No cached CLIs yet; run `cmd <CLI>` to scan one first");
+        return Ok(());
+    }
 
-    let info = load_or_scan(&resolved, refresh)?;
+    // List mode - print the matches instead of opening a picker
+    if args.list {
+        let mut app = App::new(entries);
+        app.input = query.to_string();
+        app.update_filter();
+        for (_, entry) in &app.filtered {
+            println!("{}", entry.display_text());
+        }
+        return Ok(());
+    }
+
+    // Default LM Studio port
+    let port = 1234;
+
+    let result = if use_tui(args.no_tui) {
+        run_unified_ui("all", entries, port, false, query)?
+    } else {
+        run_plain_ui("all", entries, port, false, query)?
+    };
+
+    let cmd_str = match result {
+        Some(UiResult::Entry(entry)) => build_command_string(&entry),
+        Some(UiResult::Command(cmd_str)) => cmd_str,
+        Some(UiResult::Copied) | Some(UiResult::Cancelled) | None => return Ok(()),
+    };
+    println!("{}", cmd_str);
+    if !args.print_only {
+        exec_command(&cmd_str, args.exec_in)?;
+    }
+    Ok(())
+}
+
+fn run_search(command: &str, args: &Args) -> Result<()> {
+    let target = resolve_target(
+        command,
+        args.ssh.as_deref(),
+        args.shell_resolve,
+        args.help_timeout,
+    )?;
+    let resolved = target.command.as_str();
+
+    let info = load_or_scan(&target, args.refresh, args.man)?;
 
     if info.entries.is_empty() {
         eprintln!("No commands or flags found for {}", command);
@@ -1183,7 +2432,7 @@ fn run_search(command: &str, refresh: bool, print_only: bool, list: bool) -> Res
     }
 
     // List mode - just print all entries
-    if list {
+    if args.list {
         for entry in &info.entries {
             println!("{}", entry.display_text());
         }
@@ -1193,42 +2442,27 @@ fn run_search(command: &str, refresh: bool, print_only: bool, list: bool) -> Res
     // Default LM Studio port
     let port = 1234;
 
-    let result = run_unified_ui(&resolved, info.entries, port, false)?;
+    let result = if use_tui(args.no_tui) {
+        run_unified_ui(resolved, info.entries, port, false, "")?
+    } else {
+        run_plain_ui(resolved, info.entries, port, false, "")?
+    };
 
     match result {
         Some(UiResult::Entry(entry)) => {
-            let cmd_str = build_command_string(&entry);
+            let cmd_str = target.run_line(&build_command_string(&entry));
             println!("{}", cmd_str);
 
-            if !print_only {
-                let parts: Vec<&str> = cmd_str.split_whitespace().collect();
-                if !parts.is_empty() {
-                    let status = Command::new(parts[0])
-                        .args(&parts[1..])
-                        .stdin(Stdio::inherit())
-                        .stdout(Stdio::inherit())
-                        .stderr(Stdio::inherit())
-                        .status()?;
-
-                    std::process::exit(status.code().unwrap_or(1));
-                }
+            if !args.print_only {
+                exec_command(&cmd_str, args.exec_in)?;
             }
         }
         Some(UiResult::Command(cmd_str)) => {
+            let cmd_str = target.run_line(&cmd_str);
             println!("{}", cmd_str);
 
-            if !print_only {
-                let parts: Vec<&str> = cmd_str.split_whitespace().collect();
-                if !parts.is_empty() {
-                    let status = Command::new(parts[0])
-                        .args(&parts[1..])
-                        .stdin(Stdio::inherit())
-                        .stdout(Stdio::inherit())
-                        .stderr(Stdio::inherit())
-                        .status()?;
-
-                    std::process::exit(status.code().unwrap_or(1));
-                }
+            if !args.print_only {
+                exec_command(&cmd_str, args.exec_in)?;
             }
         }
         Some(UiResult::Copied) | Some(UiResult::Cancelled) | None => {}
@@ -1248,10 +2482,15 @@ fn main() -> Result<()> {
                 path,
                 depth,
             } => {
-                let resolved = resolve_command(&command)?;
+                let target = resolve_target(
+                    &command,
+                    args.ssh.as_deref(),
+                    args.shell_resolve,
+                    args.help_timeout,
+                )?;
 
-                eprintln!("Collecting deep help for '{}'...", resolved);
-                let help_output = collect_deep_help(&resolved, depth)?;
+                eprintln!("Collecting deep help for '{}'...", target.key(&target.command));
+                let help_output = collect_deep_help(&target, depth)?;
 
                 if let Some(path) = path {
                     fs::write(&path, &help_output)
@@ -1274,60 +2513,437 @@ fn main() -> Result<()> {
                 }
             }
             Commands::Ai { command, port } => {
-                let resolved = resolve_command(&command)?;
-                let info = load_or_scan(&resolved, false)?;
+                let target = resolve_target(
+                    &command,
+                    args.ssh.as_deref(),
+                    args.shell_resolve,
+                    args.help_timeout,
+                )?;
+                let resolved = target.command.as_str();
+                let info = load_or_scan(&target, false, args.man)?;
 
                 if info.entries.is_empty() {
                     anyhow::bail!("No commands found for {}", command);
                 }
 
-                let result = run_unified_ui(&resolved, info.entries, port, true)?;
+                let result = if use_tui(args.no_tui) {
+                    run_unified_ui(resolved, info.entries, port, true, "")?
+                } else {
+                    run_plain_ui(resolved, info.entries, port, true, "")?
+                };
 
                 match result {
                     Some(UiResult::Entry(entry)) => {
-                        let cmd_str = build_command_string(&entry);
+                        let cmd_str = target.run_line(&build_command_string(&entry));
                         println!("{}", cmd_str);
 
-                        let parts: Vec<&str> = cmd_str.split_whitespace().collect();
-                        if !parts.is_empty() {
-                            let status = Command::new(parts[0])
-                                .args(&parts[1..])
-                                .stdin(Stdio::inherit())
-                                .stdout(Stdio::inherit())
-                                .stderr(Stdio::inherit())
-                                .status()?;
-
-                            std::process::exit(status.code().unwrap_or(1));
-                        }
+                        exec_command(&cmd_str, args.exec_in)?;
                     }
                     Some(UiResult::Command(cmd_str)) => {
+                        let cmd_str = target.run_line(&cmd_str);
                         println!("{}", cmd_str);
 
-                        let parts: Vec<&str> = cmd_str.split_whitespace().collect();
-                        if !parts.is_empty() {
-                            let status = Command::new(parts[0])
-                                .args(&parts[1..])
-                                .stdin(Stdio::inherit())
-                                .stdout(Stdio::inherit())
-                                .stderr(Stdio::inherit())
-                                .status()?;
-
-                            std::process::exit(status.code().unwrap_or(1));
-                        }
+                        exec_command(&cmd_str, args.exec_in)?;
                     }
                     Some(UiResult::Copied) | Some(UiResult::Cancelled) | None => {}
                 }
             }
+            Commands::ParseHelp {
+                file,
+                command,
+                debug,
+            } => {
+                let help_text = if file.as_os_str() == "-" {
+                    io::read_to_string(io::stdin()).context("Failed to read stdin")?
+                } else {
+                    fs::read_to_string(&file)
+                        .with_context(|| format!("Failed to read {}", file.display()))?
+                };
+                let command = command
+                    .or_else(|| file.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                    .filter(|c| !c.is_empty() && c != "-")
+                    .context("--command is required when reading from stdin")?;
+
+                let entries = parse_help_text(&command, &help_text, debug);
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            }
+            Commands::Completions { shell } => {
+                print_completions(shell);
+            }
         }
         return Ok(());
     }
 
+    // Debug mode: show how help output is classified, without touching the cache
+    if args.debug_parse {
+        let Some(cli) = args.cli else {
+            anyhow::bail!("Usage: cmd <CLI> --debug-parse");
+        };
+        let target = resolve_target(
+            &cli,
+            args.ssh.as_deref(),
+            args.shell_resolve,
+            args.help_timeout,
+        )?;
+        scan_command(&target, 3, true, args.man)?;
+        return Ok(());
+    }
+
+    if args.history {
+        return run_history(args.print_only, args.no_tui, args.exec_in);
+    }
+
+    if let Some(query) = &args.all {
+        return run_all(query, &args);
+    }
+
     // Default: search mode
-    if let Some(cli) = args.cli {
-        run_search(&cli, args.refresh, args.print_only, args.list)?;
+    if let Some(cli) = &args.cli {
+        run_search(cli, &args)?;
     } else {
-        anyhow::bail!("Usage: cmd <CLI> or cmd copy <CLI> [PATH]");
+        anyhow::bail!("Usage: cmd <CLI>, cmd --all, cmd --history or cmd copy <CLI> [PATH]");
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outdated_cache_is_discarded() {
+        let dir = std::env::temp_dir().join(format!("cmd-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Pre-versioning shape: no schema_version, different entry fields
+        let old = dir.join("old.json");
+        fs::write(
+            &old,
+            r#"{"version":"1.0","entries":[{"cmd":"tool run","kind":"subcommand"}]}"#,
+        )
+        .unwrap();
+        assert!(load_cached(&old).is_none());
+        assert!(!old.exists());
+
+        // Valid shape but unversioned
+        let unversioned = dir.join("unversioned.json");
+        fs::write(&unversioned, r#"{"version":"1.0","entries":[]}"#).unwrap();
+        assert!(load_cached(&unversioned).is_none());
+
+        let current = dir.join("current.json");
+        let info = CommandInfo {
+            schema_version: CACHE_SCHEMA_VERSION,
+            version: "1.0".to_string(),
+            entries: vec![],
+        };
+        fs::write(&current, serde_json::to_string(&info).unwrap()).unwrap();
+        assert_eq!(load_cached(&current).unwrap().version, "1.0");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Each tests/fixtures/help/<name>.txt is real --help output for <name>;
+    /// <name>.json is what it parsed to. Regenerate a snapshot after an
+    /// intended parser change with `cmd parse-help <name>.txt > <name>.json`.
+    #[test]
+    fn help_fixtures_match_snapshots() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/help");
+        let mut checked = 0;
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "txt") {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let help = fs::read_to_string(&path).unwrap();
+            let snapshot = fs::read_to_string(path.with_extension("json")).unwrap();
+
+            let parsed = serde_json::to_value(parse_help_text(&name, &help, false)).unwrap();
+            let expected: serde_json::Value = serde_json::from_str(&snapshot).unwrap();
+            assert_eq!(parsed, expected, "{} no longer parses to {}.json", path.display(), name);
+            checked += 1;
+        }
+        assert!(checked > 0, "no fixtures in {}", dir.display());
+    }
+
+    #[test]
+    fn dedupe_removes_repeated_flags() {
+        let help = "\
+Usage: tool [OPTIONS]
+
+Options:
+  -v, --verbose    Verbose
+  -q, --quiet      Less output
+
+Global options:
+  -v, --verbose    Print more output while running
+";
+        let entries = dedupe_entries(parse_help("tool", &[], help, false));
+        let verbose: Vec<&Entry> = entries
+            .iter()
+            .filter(|e| e.long.as_deref() == Some("--verbose"))
+            .collect();
+
+        assert_eq!(verbose.len(), 1);
+        assert_eq!(verbose[0].description, "Print more output while running");
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn man_options_are_parsed_and_merged() {
+        // `man ls | col -bx` (GNU), trimmed
+        let gnu = "\
+LS(1)                            User Commands                           LS(1)
+
+NAME
+       ls - list directory contents
+
+DESCRIPTION
+       List  information  about  the FILEs (the current directory by default).
+
+       Mandatory arguments to long options are mandatory for short options too.
+
+       -a, --all
+              do not ignore entries starting with .
+
+       --block-size=SIZE
+              with -l, scale sizes by SIZE when printing sizes; e.g.,
+              '--block-size=M'. See SIZE format below
+
+       -C     list entries by columns
+
+              -x is described below, not here
+
+       -1     list one file per line
+
+SEE ALSO
+       -z, --zero
+              not an option section
+";
+        let entries = parse_man_options("ls", gnu);
+        let flags: Vec<(Option<&str>, Option<&str>, &str)> = entries
+            .iter()
+            .map(|e| (e.short.as_deref(), e.long.as_deref(), e.description.as_str()))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                (Some("-a"), Some("--all"), "do not ignore entries starting with ."),
+                (
+                    None,
+                    Some("--block-size"),
+                    "with -l, scale sizes by SIZE when printing sizes; e.g., '--block-size=M'."
+                ),
+                (Some("-C"), None, "list entries by columns"),
+                (Some("-1"), None, "list one file per line"),
+            ]
+        );
+
+        // mdoc (BSD/macOS) style, with an OPTIONS heading
+        let bsd = "\
+OPTIONS
+     -I pattern
+             Ignore files matching pattern.
+     -v      Print each file as it is copied.
+";
+        let entries = parse_man_options("tool", bsd);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].short.as_deref(), Some("-I"));
+        assert_eq!(entries[1].description, "Print each file as it is copied.");
+
+        let help = "Usage: ls [OPTION]...\n\nOptions:\n  -a, --all    Show all\n      --block-size=<SIZE>    Scale\n";
+        let mut merged = parse_help("ls", &[], help, false);
+        let added = merge_man_entries(&mut merged, "ls", parse_man_options("ls", gnu));
+        assert_eq!(added, 2);
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged[0].description, "Show all");
+    }
+
+    #[test]
+    fn man_options_keep_only_the_flag_name() {
+        // From GNU coreutils ls(1)
+        let ls = "\
+DESCRIPTION
+       --block-size=SIZE
+              with -l, scale sizes by SIZE when printing sizes; e.g., '--block-size=M';
+              see SIZE format below
+
+       --color[=WHEN]
+              color the output WHEN; more info below
+
+       -I, --ignore=PATTERN
+              do not list implied entries matching shell PATTERN
+
+       --time-style=TIME_STYLE
+              time/date format with -l; see TIME_STYLE below
+";
+        let flags: Vec<_> = parse_man_options("ls", ls)
+            .into_iter()
+            .map(|e| (e.short, e.long))
+            .collect();
+        let flag =
+            |short: Option<&str>, long: &str| (short.map(String::from), Some(long.to_string()));
+        assert_eq!(
+            flags,
+            vec![
+                flag(None, "--block-size"),
+                flag(None, "--color"),
+                flag(Some("-I"), "--ignore"),
+                flag(None, "--time-style"),
+            ]
+        );
+    }
+
+    #[test]
+    fn whence_output_is_classified() {
+        assert_eq!(
+            parse_whence("git", "welcome!\ngit is /usr/bin/git\n"),
+            WhenceKind::Path(PathBuf::from("/usr/bin/git"))
+        );
+        assert_eq!(
+            parse_whence("g", "g is an alias for git --no-pager"),
+            WhenceKind::Alias("git --no-pager".to_string())
+        );
+        assert_eq!(
+            parse_whence("f", "f is a shell function from /Users/me/.zshrc"),
+            WhenceKind::Function
+        );
+        assert_eq!(parse_whence("cd", "cd is a shell builtin"), WhenceKind::Builtin);
+        assert_eq!(parse_whence("nope", "nope not found"), WhenceKind::NotFound);
+    }
+
+    #[test]
+    fn function_candidates_skip_shell_syntax() {
+        let body = "\
+f () {
+\tlocal dir=$1
+\tif [[ -n $dir ]]; then
+\t\tRUST_LOG=debug command cargo run -- \"$@\" | less
+\tfi
+}";
+        assert_eq!(function_candidates(body), vec!["cargo", "less"]);
+    }
+
+    #[test]
+    fn history_dedupes_and_caps() {
+        let dir = std::env::temp_dir().join(format!("cmd-history-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let run = |command: &str, timestamp: u64| HistoryEntry {
+            command: command.to_string(),
+            timestamp,
+            cwd: None,
+        };
+        append_history(&path, run("git status", 1), 3).unwrap();
+        append_history(&path, run("git status", 2), 3).unwrap();
+        append_history(&path, run("git log", 3), 3).unwrap();
+        append_history(&path, run("git status", 4), 3).unwrap();
+        assert_eq!(load_history(&path).len(), 3);
+
+        append_history(&path, run("git diff", 5), 3).unwrap();
+        let history = load_history(&path);
+        let commands: Vec<&str> = history.iter().map(|h| h.command.as_str()).collect();
+        assert_eq!(commands, vec!["git log", "git status", "git diff"]);
+
+        let entries = history_entries(history);
+        assert_eq!(entries[0].command, "git diff");
+        assert_eq!(build_command_string(&entries[0]), "git diff");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn all_merges_every_local_cache() {
+        let dir = std::env::temp_dir().join(format!("cmd-all-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let write = |file: &str, schema_version: u32, entries: Vec<Entry>| {
+            let info = CommandInfo {
+                schema_version,
+                version: "1.0".to_string(),
+                entries,
+            };
+            fs::write(dir.join(file), serde_json::to_string(&info).unwrap()).unwrap();
+        };
+        let help = "Usage: tool [OPTIONS]\n\nOptions:\n  -w, --watch    Rerun on changes\n";
+        write("cargo.json", CACHE_SCHEMA_VERSION, parse_help("cargo", &[], help, false));
+        write(
+            &format!("{}.json", cache_file_stem("/home/me/bin/fix_perms")),
+            CACHE_SCHEMA_VERSION,
+            parse_help("/home/me/bin/fix_perms", &[], help, false),
+        );
+        write("_home_me_bin_old.json", CACHE_SCHEMA_VERSION, Vec::new());
+        write("box:git.json", CACHE_SCHEMA_VERSION, parse_help("git", &[], help, false));
+        write("old.json", 0, Vec::new());
+        fs::write(dir.join("history.jsonl"), "").unwrap();
+
+        let (entries, outdated) = load_all_cached(&dir);
+        assert_eq!(outdated, vec!["old"]);
+        let shown: Vec<String> = entries.iter().map(|e| e.display_text()).collect();
+        assert_eq!(
+            shown,
+            vec![
+                "[cargo] cargo -w, --watch - Rerun on changes",
+                "[fix_perms] /home/me/bin/fix_perms -w, --watch - Rerun on changes",
+            ]
+        );
+        assert_eq!(build_command_string(&entries[1]), "/home/me/bin/fix_perms --watch");
+
+        // Merged entries filter like a single CLI's
+        let mut app = App::new(entries);
+        app.input = "fix_perms watch".to_string();
+        app.update_filter();
+        assert_eq!(app.filtered[0].1.tool.as_deref(), Some("fix_perms"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn remote_target_runs_through_ssh() {
+        let target = Target {
+            command: "git".to_string(),
+            host: Some("me@box".to_string()),
+            help_timeout: DEFAULT_HELP_TIMEOUT,
+        };
+        let cmd = target.process(&["log", "--format=%h %s", "it's"]);
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(cmd.get_program(), "ssh");
+        assert_eq!(args[args.len() - 2], "me@box");
+        assert_eq!(args[args.len() - 1], "git log '--format=%h %s' 'it'\\''s'");
+
+        assert_eq!(target.key("git"), "me@box:git");
+        assert_eq!(target.run_line("git log"), "ssh -t me@box git log");
+        assert_eq!(Target::local("git".to_string()).run_line("git log"), "git log");
+    }
+
+    #[test]
+    fn help_timeout_kills_hanging_commands() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("s").is_err());
+        assert_eq!(format_duration(Duration::from_millis(250)), "250ms");
+
+        let target = Target {
+            help_timeout: Duration::from_millis(200),
+            ..Target::local("sh".to_string())
+        };
+        // `sh -c 'sleep 10' --help`: the trailing --help only sets $0
+        let started = Instant::now();
+        assert!(target.output(&["-c", "sleep 10"]).unwrap().is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let err = get_help(&target, &["-c", "sleep 10"]).unwrap_err();
+        assert_eq!(err.to_string(), "timed out after 200ms");
+
+        let echo = Target::local("echo".to_string());
+        let output = echo.output(&["hi"]).unwrap().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hi\n");
+    }
+}
@@ -0,0 +1,3 @@
+export function lerp(a, b, t) {
+  return a + (b - a) * t;
+}
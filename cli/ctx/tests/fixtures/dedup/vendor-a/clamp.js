@@ -0,0 +1,4 @@
+// Vendored copy; kept identical in every package that uses it.
+export function clamp(value, min, max) {
+  return Math.min(Math.max(value, min), max);
+}
@@ -1,15 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::{Context as _, Result};
-use chrono::{Local, Utc};
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
 use clap::Parser;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
 
 fn main() {
     if let Err(err) = run() {
@@ -20,16 +23,24 @@ fn main() {
 
 fn run() -> Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.log_file.as_deref())?;
 
     match cli.command {
         Some(Commands::Daemon) => run_daemon(),
         Some(Commands::List) => list_intents(),
         Some(Commands::Trigger { name }) => trigger_intent(&name),
         Some(Commands::Propose { title, action, context }) => {
-            propose_to_lin(&title, &action, context.as_deref())
+            propose_to_lin(&title, &action, context.as_deref(), None)
         }
+        Some(Commands::Snooze {
+            name,
+            for_secs,
+            clear,
+        }) => snooze_intent(&name, for_secs, clear),
         Some(Commands::Context) => show_context(),
+        Some(Commands::Test { name, all: _ }) => test_intents(name.as_deref()),
         Some(Commands::Watch) => watch_context(),
+        Some(Commands::Status) => show_status(),
         None => run_daemon(),
     }
 }
@@ -39,6 +50,10 @@ fn run() -> Result<()> {
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Also append log lines to this file (filter with RUST_LOG, default "info")
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
 }
 
 #[derive(clap::Subcommand)]
@@ -59,10 +74,83 @@ enum Commands {
         #[arg(long)]
         context: Option<String>,
     },
+    /// Keep an intent from firing for a while (same as dismissing it in Lin)
+    Snooze {
+        /// Intent to snooze
+        name: String,
+        /// Seconds to snooze for (default: the intent's `snooze` setting)
+        #[arg(long = "for", value_name = "SECS")]
+        for_secs: Option<u64>,
+        /// Lift an existing snooze
+        #[arg(long, conflicts_with = "for_secs")]
+        clear: bool,
+    },
     /// Show current context
     Context,
+    /// Check whether an intent matches the current context (without running it)
+    Test {
+        /// Intent to check
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+        /// Check every configured intent
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
     /// Watch context changes in real-time
     Watch,
+    /// Show whether the daemon is running, its config and last trigger
+    #[command(alias = "ps")]
+    Status,
+}
+
+// ── Logging ───────────────────────────────────────────────────────────────────
+
+/// Local wall-clock time, matching the daemon's old "[HH:MM:SS]" prefixes.
+struct LocalTime;
+
+impl tracing_subscriber::fmt::time::FormatTime for LocalTime {
+    fn format_time(
+        &self,
+        w: &mut tracing_subscriber::fmt::format::Writer<'_>,
+    ) -> std::fmt::Result {
+        write!(w, "{}", Local::now().format("%H:%M:%S"))
+    }
+}
+
+fn init_logging(log_file: Option<&std::path::Path>) -> Result<()> {
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, EnvFilter};
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let stderr_layer = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .with_timer(LocalTime);
+
+    let file_layer = match log_file {
+        Some(path) => {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("failed to open log file {}", path.display()))?;
+            Some(
+                fmt::layer()
+                    .with_writer(std::sync::Mutex::new(file))
+                    .with_ansi(false)
+                    .with_target(false),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr_layer)
+        .with(file_layer)
+        .init();
+    Ok(())
 }
 
 // ── Config ────────────────────────────────────────────────────────────────────
@@ -75,19 +163,33 @@ struct Config {
     intent: Vec<Intent>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct ContextConfig {
-    /// Context source: "native" (AppleScript), "file" (JSON file)
+    /// Context source: "native" (AppleScript), "file" (JSON file), "http" (POST /context)
     #[serde(default = "default_source")]
     source: String,
     /// Path to context file (when source = "file")
     #[serde(default = "default_context_file")]
     context_file: String,
+    /// Port the localhost context server listens on (when source = "http")
+    #[serde(default = "default_http_port")]
+    http_port: u16,
     /// Poll interval in milliseconds
     #[serde(default = "default_poll_interval")]
     poll_interval_ms: u64,
 }
 
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            source: default_source(),
+            context_file: default_context_file(),
+            http_port: default_http_port(),
+            poll_interval_ms: default_poll_interval(),
+        }
+    }
+}
+
 fn default_source() -> String {
     "native".to_string()
 }
@@ -100,6 +202,10 @@ fn default_poll_interval() -> u64 {
     1000
 }
 
+fn default_http_port() -> u16 {
+    7331
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Intent {
     name: String,
@@ -109,13 +215,22 @@ struct Intent {
     /// Window title pattern (regex)
     #[serde(default)]
     window: Option<String>,
-    /// Trigger type: "enter", "exit", "change"
+    /// Trigger type: "enter", "exit", "change", "idle", "schedule",
+    /// "app_launch", "app_quit" (the last two match `app` against apps
+    /// starting/quitting, regardless of focus; `window` is ignored)
     #[serde(default = "default_trigger")]
     trigger: String,
-    /// Action type: "run" (execute immediately) or "propose" (send to Lin)
+    /// Seconds without keyboard/mouse input before an "idle" trigger fires
+    #[serde(default)]
+    idle_secs: Option<u64>,
+    /// Cron-style "min hour day month weekday" for "schedule" triggers
+    #[serde(default)]
+    schedule: Option<String>,
+    /// Action type: "run" (execute immediately), "open" (open `action` as a
+    /// URL or app deep link) or "propose" (send to Lin)
     #[serde(default = "default_action_type")]
     action_type: String,
-    /// Shell command or proposal
+    /// Shell command, URL (supports {project}) or proposal
     action: String,
     /// Title for proposals (used when action_type = "propose")
     #[serde(default)]
@@ -123,6 +238,16 @@ struct Intent {
     /// Cooldown in seconds
     #[serde(default = "default_cooldown")]
     cooldown: u64,
+    /// Seconds a proposal dismissed in Lin keeps this intent quiet
+    #[serde(default = "default_snooze")]
+    snooze: u64,
+    /// Working directory for "run" actions; supports {project} and ~.
+    /// Relative paths are taken from $HOME.
+    #[serde(default)]
+    cwd: Option<String>,
+    /// Extra environment for "run" actions; values support {project}
+    #[serde(default)]
+    env: BTreeMap<String, String>,
 }
 
 fn default_trigger() -> String {
@@ -137,6 +262,10 @@ fn default_cooldown() -> u64 {
     30
 }
 
+fn default_snooze() -> u64 {
+    3600
+}
+
 // ── Lin Proposal ──────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,6 +276,9 @@ struct LinProposal {
     action: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     context: Option<String>,
+    /// Intent that proposed this, so Lin can snooze it when dismissed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    intent: Option<String>,
     expires_at: i64,
 }
 
@@ -156,7 +288,12 @@ fn lin_proposals_path() -> PathBuf {
         .join("Library/Application Support/Lin/proposals.json")
 }
 
-fn propose_to_lin(title: &str, action: &str, context: Option<&str>) -> Result<()> {
+fn propose_to_lin(
+    title: &str,
+    action: &str,
+    context: Option<&str>,
+    intent: Option<&str>,
+) -> Result<()> {
     let path = lin_proposals_path();
 
     // Ensure directory exists
@@ -183,14 +320,15 @@ fn propose_to_lin(title: &str, action: &str, context: Option<&str>) -> Result<()
         title: title.to_string(),
         action: action.to_string(),
         context: context.map(String::from),
+        intent: intent.map(String::from),
         expires_at: now + 300, // 5 minute expiry
     };
 
-    eprintln!(
-        "[{}] propose: {} -> {}",
-        Local::now().format("%H:%M:%S"),
-        proposal.title,
-        proposal.action
+    info!(
+        title = %proposal.title,
+        action = %proposal.action,
+        context = proposal.context.as_deref().unwrap_or(""),
+        "propose"
     );
     proposals.push(proposal);
 
@@ -209,6 +347,72 @@ fn rand_id() -> u64 {
         .unwrap_or(0)
 }
 
+// ── Snooze ────────────────────────────────────────────────────────────────────
+
+/// Snoozed intents, written by Lin when a proposal is dismissed (and by
+/// `intent snooze`). A JSON object keyed by the proposal's `intent` field:
+///
+/// ```json
+/// {
+///   "deploy-on-exit": { "dismissed_at": 1718000000 },
+///   "standup-notes": { "until": 1718003600 }
+/// }
+/// ```
+///
+/// Times are unix seconds. `until` wins when present; otherwise the intent
+/// stays quiet for its configured `snooze` seconds after `dismissed_at`.
+/// Expired entries and unknown names are ignored, so writers needn't prune.
+fn snoozed_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Library/Application Support/Lin/snoozed.json")
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Snooze {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dismissed_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    until: Option<i64>,
+}
+
+impl Snooze {
+    /// Unix seconds the snooze ends, given the intent's `snooze` setting.
+    fn ends_at(&self, snooze_secs: u64) -> Option<i64> {
+        self.until
+            .or_else(|| Some(self.dismissed_at? + snooze_secs as i64))
+    }
+}
+
+fn load_snoozes(path: &Path) -> BTreeMap<String, Snooze> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// When `name`'s snooze ends, if it is still snoozed at `now` (unix seconds).
+fn snoozed_until(
+    snoozes: &BTreeMap<String, Snooze>,
+    name: &str,
+    snooze_secs: u64,
+    now: i64,
+) -> Option<i64> {
+    snoozes
+        .get(name)?
+        .ends_at(snooze_secs)
+        .filter(|&end| end > now)
+}
+
+fn save_snoozes(path: &Path, snoozes: &BTreeMap<String, Snooze>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(snoozes)?)?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
 // ── Context ───────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -284,6 +488,20 @@ fn config_path() -> PathBuf {
         .join("intent.toml")
 }
 
+/// Last-fired times per intent, so cooldowns survive daemon restarts.
+fn cooldowns_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".local/state/intent/cooldowns.json")
+}
+
+/// Pidfile the daemon keeps while it runs, for `intent status`.
+fn pidfile_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".local/state/intent/daemon.json")
+}
+
 fn expand_path(path: &str) -> String {
     if path.starts_with("~/") {
         if let Ok(home) = std::env::var("HOME") {
@@ -303,7 +521,15 @@ fn load_config() -> Result<Config> {
     }
     let content = fs::read_to_string(&path)
         .with_context(|| format!("failed to read {}", path.display()))?;
-    toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    parse_config(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn parse_config(content: &str) -> Result<Config> {
+    let config: Config = toml::from_str(content)?;
+    if config.context.poll_interval_ms == 0 {
+        anyhow::bail!("context.poll_interval_ms must be greater than 0");
+    }
+    Ok(config)
 }
 
 fn load_context_from_file(path: &str) -> Result<SystemContext> {
@@ -343,19 +569,97 @@ fn load_context_native() -> SystemContext {
                 app_id: lines.first().unwrap_or(&"").to_string(),
                 app_name: lines.get(1).unwrap_or(&"").to_string(),
                 window_title: lines.get(2).unwrap_or(&"").to_string(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_millis() as u64)
-                    .unwrap_or(0),
+                timestamp: now_millis(),
             }
         }
         _ => SystemContext::default(),
     }
 }
 
+/// Latest context POSTed to the HTTP source; None until the first push.
+static HTTP_CONTEXT: Mutex<Option<SystemContext>> = Mutex::new(None);
+static HTTP_SERVER: Once = Once::new();
+
+/// Serve `POST /context` on 127.0.0.1:`port` so other programs (a browser
+/// extension, another machine via ssh -L, ...) can push context. The body is
+/// a `SystemContext`; every field is optional and `timestamp` (ms since the
+/// epoch) defaults to the time of the request:
+///
+/// ```text
+/// curl -X POST localhost:7331/context \
+///   -d '{"app_id": "com.google.Chrome", "app_name": "Chrome", "window_title": "PR #12"}'
+/// ```
+///
+/// `GET /context` returns the latest pushed context.
+fn start_context_server(port: u16) {
+    let server = match tiny_http::Server::http(("127.0.0.1", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            error!(port, error = %e, "failed to start context server");
+            return;
+        }
+    };
+    info!(port, "listening for POST /context on 127.0.0.1");
+
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let response = match (request.method(), request.url()) {
+                (tiny_http::Method::Post, "/context") => {
+                    let mut body = String::new();
+                    match std::io::Read::read_to_string(request.as_reader(), &mut body)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|_| Ok(serde_json::from_str::<SystemContext>(&body)?))
+                    {
+                        Ok(mut ctx) => {
+                            if ctx.timestamp == 0 {
+                                ctx.timestamp = now_millis();
+                            }
+                            if let Ok(mut latest) = HTTP_CONTEXT.lock() {
+                                *latest = Some(ctx);
+                            }
+                            tiny_http::Response::from_string("").with_status_code(204)
+                        }
+                        Err(e) => tiny_http::Response::from_string(format!("invalid context: {e}\n"))
+                            .with_status_code(400),
+                    }
+                }
+                (tiny_http::Method::Get, "/context") => {
+                    let latest = HTTP_CONTEXT
+                        .lock()
+                        .ok()
+                        .and_then(|latest| latest.clone())
+                        .unwrap_or_default();
+                    tiny_http::Response::from_string(
+                        serde_json::to_string(&latest).unwrap_or_default(),
+                    )
+                }
+                _ => tiny_http::Response::from_string("not found\n").with_status_code(404),
+            };
+            let _ = request.respond(response);
+        }
+    });
+}
+
+fn load_context_http(port: u16) -> SystemContext {
+    HTTP_SERVER.call_once(|| start_context_server(port));
+    HTTP_CONTEXT
+        .lock()
+        .ok()
+        .and_then(|latest| latest.clone())
+        .unwrap_or_default()
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 fn get_context(config: &ContextConfig) -> SystemContext {
     match config.source.as_str() {
         "file" => load_context_from_file(&config.context_file).unwrap_or_default(),
+        "http" => load_context_http(config.http_port),
         "native" | _ => load_context_native(),
     }
 }
@@ -365,6 +669,8 @@ fn get_context(config: &ContextConfig) -> SystemContext {
 struct IntentMatcher {
     app_regex: Option<Regex>,
     window_regex: Option<Regex>,
+    idle: Option<Duration>,
+    schedule: Option<Schedule>,
 }
 
 impl IntentMatcher {
@@ -381,75 +687,506 @@ impl IntentMatcher {
             .map(|p| Regex::new(p))
             .transpose()
             .context("invalid window pattern")?;
+
+        let idle = match (intent.trigger.as_str(), intent.idle_secs) {
+            ("idle", Some(secs)) => Some(Duration::from_secs(secs)),
+            ("idle", None) => anyhow::bail!("idle trigger requires idle_secs"),
+            _ => None,
+        };
+        let schedule = match (intent.trigger.as_str(), intent.schedule.as_deref()) {
+            ("schedule", Some(spec)) => Some(Schedule::parse(spec)?),
+            ("schedule", None) => anyhow::bail!("schedule trigger requires schedule"),
+            _ => None,
+        };
+        if matches!(intent.trigger.as_str(), "app_launch" | "app_quit") && app_regex.is_none() {
+            anyhow::bail!("{} trigger requires app", intent.trigger);
+        }
+
         Ok(Self {
             app_regex,
             window_regex,
+            idle,
+            schedule,
         })
     }
 
     fn matches(&self, ctx: &SystemContext) -> bool {
-        let app_match = self.app_regex.as_ref().map_or(true, |r| {
-            r.is_match(&ctx.app_id) || r.is_match(&ctx.app_name)
-        });
+        let (app_match, window_match) = self.match_parts(ctx);
+        app_match.unwrap_or(true) && window_match.unwrap_or(true)
+    }
+
+    /// App and window pattern results; `None` when the intent has no such pattern.
+    fn match_parts(&self, ctx: &SystemContext) -> (Option<bool>, Option<bool>) {
+        let app_match = self
+            .app_regex
+            .as_ref()
+            .map(|r| r.is_match(&ctx.app_id) || r.is_match(&ctx.app_name));
         let window_match = self
             .window_regex
             .as_ref()
-            .map_or(true, |r| r.is_match(&ctx.window_title));
-        app_match && window_match
+            .map(|r| r.is_match(&ctx.window_title));
+        (app_match, window_match)
+    }
+
+    /// Whether a launched/quit app matches the app pattern (window is ignored).
+    fn matches_app(&self, app: &RunningApp) -> bool {
+        self.app_regex
+            .as_ref()
+            .is_some_and(|r| r.is_match(&app.app_id) || r.is_match(&app.app_name))
+    }
+}
+
+// ── Idle & Schedule ───────────────────────────────────────────────────────────
+
+/// Time since the last keyboard/mouse event, from `ioreg -c IOHIDSystem`.
+fn system_idle_time() -> Option<Duration> {
+    let output = Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
     }
+    parse_hid_idle_time(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Extract `"HIDIdleTime" = <nanoseconds>` from ioreg output.
+fn parse_hid_idle_time(ioreg: &str) -> Option<Duration> {
+    ioreg.lines().find_map(|line| {
+        let (_, value) = line.split_once("\"HIDIdleTime\"")?;
+        let nanos = value.trim_start().strip_prefix('=')?.trim().parse::<u64>().ok()?;
+        Some(Duration::from_nanos(nanos))
+    })
+}
+
+/// A five-field cron expression ("min hour day month weekday"). Fields accept
+/// `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/10`, `8-18/2`).
+#[derive(Debug, Clone, PartialEq)]
+struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_any: bool,
+    weekdays_any: bool,
+}
+
+impl Schedule {
+    fn parse(spec: &str) -> Result<Self> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!("schedule '{}' must have 5 fields (min hour day month weekday)", spec);
+        }
+
+        let field = |i: usize, min: u32, max: u32| {
+            parse_cron_field(fields[i], min, max)
+                .with_context(|| format!("invalid schedule field '{}' in '{}'", fields[i], spec))
+        };
+
+        let mut weekdays = field(4, 0, 7)?;
+        // Both 0 and 7 mean Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+
+        Ok(Self {
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            weekdays,
+            days_any: fields[2] == "*",
+            weekdays_any: fields[4] == "*",
+        })
+    }
+
+    fn matches(&self, t: &DateTime<Local>) -> bool {
+        let bit = |mask: u64, v: u32| mask & (1 << v) != 0;
+
+        let day = bit(self.days, t.day());
+        let weekday = bit(self.weekdays, t.weekday().num_days_from_sunday());
+        // Like cron: when both day fields are restricted, either may match
+        let day_match = match (self.days_any, self.weekdays_any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+
+        bit(self.minutes, t.minute())
+            && bit(self.hours, t.hour())
+            && bit(self.months, t.month())
+            && day_match
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().context("invalid step")?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            anyhow::bail!("step must be positive");
+        }
+
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (lo.parse::<u32>()?, hi.parse::<u32>()?)
+        } else {
+            let v = range.parse::<u32>()?;
+            // "5/10" means "from 5 every 10"
+            if part.contains('/') {
+                (v, max)
+            } else {
+                (v, v)
+            }
+        };
+        if lo < min || hi > max || lo > hi {
+            anyhow::bail!("{}-{} out of range {}-{}", lo, hi, min, max);
+        }
+
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+
+    Ok(mask)
+}
+
+// ── App Launch & Quit ─────────────────────────────────────────────────────────
+//
+// There's no NSWorkspace observer here: each daemon tick lists the running
+// foreground apps (System Events, "background only" processes excluded) and
+// diffs against the previous tick. Launches and quits are therefore seen up
+// to one poll_interval_ms (+ ~100-300ms for osascript) late, and an app that
+// starts and quits within a single interval is missed. Unlike enter/exit,
+// this doesn't depend on which app has focus.
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RunningApp {
+    app_id: String,
+    app_name: String,
+}
+
+impl RunningApp {
+    /// Context handed to actions for an app event (no window title).
+    fn context(&self) -> SystemContext {
+        SystemContext {
+            app_id: self.app_id.clone(),
+            app_name: self.app_name.clone(),
+            window_title: String::new(),
+            timestamp: now_millis(),
+        }
+    }
+}
+
+fn trigger_watches_apps(trigger: &str) -> bool {
+    matches!(trigger, "app_launch" | "app_quit")
+}
+
+/// Foreground apps currently running. `None` if System Events couldn't be
+/// asked, so a failed poll isn't mistaken for every app quitting.
+fn running_apps() -> Option<HashSet<RunningApp>> {
+    let script = r#"
+        set out to ""
+        tell application "System Events"
+            repeat with p in (every application process whose background only is false)
+                set appId to ""
+                try
+                    set appId to bundle identifier of p
+                    if appId is missing value then set appId to ""
+                end try
+                set out to out & appId & tab & (name of p) & linefeed
+            end repeat
+        end tell
+        return out
+    "#;
+
+    let output = Command::new("osascript").args(["-e", script]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_running_apps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse "bundle id<TAB>name" lines from `running_apps`.
+fn parse_running_apps(text: &str) -> HashSet<RunningApp> {
+    text.lines()
+        .filter_map(|line| {
+            let (app_id, app_name) = line.split_once('\t')?;
+            Some(RunningApp {
+                app_id: app_id.trim().to_string(),
+                app_name: app_name.trim().to_string(),
+            })
+        })
+        .filter(|app| !app.app_id.is_empty() || !app.app_name.is_empty())
+        .collect()
 }
 
 // ── Daemon ────────────────────────────────────────────────────────────────────
 
 struct IntentState {
     matched_since: Option<Instant>,
-    last_triggered: Option<Instant>,
+    /// Unix millis, wall-clock so it can be persisted across restarts
+    last_triggered: Option<u64>,
     last_context: Option<SystemContext>,
+    /// Minute (unix time / 60) a schedule last fired, so it fires once per minute
+    last_schedule_minute: Option<i64>,
 }
 
-fn run_daemon() -> Result<()> {
-    eprintln!("intent: starting daemon");
-    eprintln!("config: {}", config_path().display());
+/// Whether an intent that last fired at `last` (unix millis) is still cooling
+/// down. A time in the future (clock moved back) doesn't hold it off.
+fn in_cooldown(last: Option<u64>, cooldown_secs: u64, now_ms: u64) -> bool {
+    last.and_then(|last| now_ms.checked_sub(last))
+        .is_some_and(|elapsed| elapsed < cooldown_secs * 1000)
+}
 
-    let config = load_config()?;
-    let poll_interval = Duration::from_millis(config.context.poll_interval_ms);
+fn load_cooldowns(path: &Path) -> HashMap<String, u64> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write last-fired times via a temp file + rename, so a crash mid-write
+/// can't leave a truncated file behind.
+fn save_cooldowns(path: &Path, states: &HashMap<String, IntentState>) -> Result<()> {
+    let fired: BTreeMap<&str, u64> = states
+        .iter()
+        .filter_map(|(name, state)| Some((name.as_str(), state.last_triggered?)))
+        .collect();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(&fired)?)?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
 
-    eprintln!("source: {}", config.context.source);
-    eprintln!("intents: {}", config.intent.len());
-    eprintln!("proposals: {}", lin_proposals_path().display());
+/// Contents of the pidfile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DaemonInfo {
+    pid: u32,
+    started_at: DateTime<Utc>,
+}
 
-    if config.intent.is_empty() {
-        eprintln!("no intents configured, watching context only");
+#[derive(Debug, PartialEq)]
+enum DaemonStatus {
+    Running(DaemonInfo),
+    /// A pidfile whose process is gone (killed, or the machine rebooted)
+    Stale(DaemonInfo),
+    Stopped,
+}
+
+fn daemon_status(pidfile: &Path) -> DaemonStatus {
+    let info: Option<DaemonInfo> = fs::read_to_string(pidfile)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    match info {
+        Some(info) if pid_alive(info.pid) => DaemonStatus::Running(info),
+        Some(info) => DaemonStatus::Stale(info),
+        None => DaemonStatus::Stopped,
     }
+}
 
-    // Compile matchers
-    let matchers: Vec<_> = config
+fn pid_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks; EPERM means it exists but belongs to someone else
+    let res = unsafe { libc::kill(pid, 0) };
+    res == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+fn write_pidfile(path: &Path, info: &DaemonInfo) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(info)?)?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+/// Remove the pidfile if it's still ours (a newer daemon may have replaced it).
+fn remove_pidfile(path: &Path) {
+    let ours = match daemon_status(path) {
+        DaemonStatus::Running(info) | DaemonStatus::Stale(info) => info.pid == std::process::id(),
+        DaemonStatus::Stopped => false,
+    };
+    if ours {
+        if let Err(err) = fs::remove_file(path) {
+            warn!(path = %path.display(), error = %err, "failed to remove pidfile");
+        }
+    }
+}
+
+fn config_modified() -> Option<std::time::SystemTime> {
+    fs::metadata(config_path()).and_then(|m| m.modified()).ok()
+}
+
+fn compile_matchers(config: &Config) -> Result<Vec<IntentMatcher>> {
+    config
         .intent
         .iter()
-        .map(|i| IntentMatcher::new(i))
-        .collect::<Result<Vec<_>>>()?;
+        .map(|i| IntentMatcher::new(i).with_context(|| format!("intent '{}'", i.name)))
+        .collect()
+}
 
-    // State tracking per intent
-    let mut states: HashMap<String, IntentState> = config
+/// Build per-intent state, carrying over cooldowns from `previous` for
+/// intents that still exist by name.
+fn build_states(
+    config: &Config,
+    mut previous: HashMap<String, IntentState>,
+) -> HashMap<String, IntentState> {
+    config
         .intent
         .iter()
         .map(|i| {
+            let prev = previous.remove(&i.name);
             (
                 i.name.clone(),
                 IntentState {
                     matched_since: None,
-                    last_triggered: None,
+                    last_triggered: prev.as_ref().and_then(|s| s.last_triggered),
                     last_context: None,
+                    last_schedule_minute: prev.and_then(|s| s.last_schedule_minute),
                 },
             )
         })
-        .collect();
+        .collect()
+}
+
+fn run_daemon() -> Result<()> {
+    info!(config = %config_path().display(), "starting daemon");
+
+    let mut config = load_config()?;
+    let mut config_mtime = config_modified();
+    let mut poll_interval = Duration::from_millis(config.context.poll_interval_ms);
+
+    info!(
+        source = %config.context.source,
+        intents = config.intent.len(),
+        proposals = %lin_proposals_path().display(),
+        snoozed = %snoozed_path().display(),
+        "loaded config"
+    );
+
+    if config.intent.is_empty() {
+        warn!("no intents configured, watching context only");
+    }
+
+    // Compile matchers
+    let mut matchers = compile_matchers(&config)?;
+
+    let pidfile = pidfile_path();
+    match daemon_status(&pidfile) {
+        DaemonStatus::Running(other) if other.pid != std::process::id() => {
+            anyhow::bail!("daemon already running (pid {})", other.pid)
+        }
+        DaemonStatus::Stale(old) => info!(pid = old.pid, "replacing stale pidfile"),
+        _ => {}
+    }
+    let daemon = DaemonInfo {
+        pid: std::process::id(),
+        started_at: Utc::now(),
+    };
+    write_pidfile(&pidfile, &daemon)?;
+
+    // Finish the current poll and clean up on the first signal; a second one
+    // exits immediately
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [
+        signal_hook::consts::SIGINT,
+        signal_hook::consts::SIGTERM,
+        signal_hook::consts::SIGHUP,
+    ] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&shutdown))?;
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))?;
+    }
+
+    // State tracking per intent, with cooldowns from before the last restart
+    let cooldowns = cooldowns_path();
+    let mut states = build_states(&config, HashMap::new());
+    for (name, last) in load_cooldowns(&cooldowns) {
+        if let Some(state) = states.get_mut(&name) {
+            state.last_triggered = Some(last);
+        }
+    }
 
     let mut prev_context = SystemContext::default();
+    let mut prev_idle = Duration::ZERO;
+    // None until the first successful poll, so startup doesn't count as launches
+    let mut prev_apps: Option<HashSet<RunningApp>> = None;
+
+    while !shutdown.load(Ordering::Relaxed) {
+        // Hot-reload config when intent.toml changes on disk
+        let mtime = config_modified();
+        if mtime != config_mtime {
+            config_mtime = mtime;
+            // Editors that delete and rewrite the file leave a moment with
+            // none; reloading then would drop every intent and its cooldown
+            let reloaded = if mtime.is_none() {
+                Err(anyhow::anyhow!("{} is missing", config_path().display()))
+            } else {
+                load_config().and_then(|c| compile_matchers(&c).map(|m| (c, m)))
+            };
+            match reloaded {
+                Ok((new_config, new_matchers)) => {
+                    states = build_states(&new_config, states);
+                    matchers = new_matchers;
+                    config = new_config;
+                    poll_interval = Duration::from_millis(config.context.poll_interval_ms);
+                    info!(intents = config.intent.len(), "config reloaded");
+                }
+                Err(err) => {
+                    error!(
+                        error = %format!("{:#}", err),
+                        "config reload failed, keeping previous config"
+                    );
+                }
+            }
+        }
 
-    loop {
         let ctx = get_context(&config.context);
 
+        // Only ask ioreg when some intent cares about idle time
+        let idle = if matchers.iter().any(|m| m.idle.is_some()) {
+            system_idle_time().unwrap_or(Duration::ZERO)
+        } else {
+            Duration::ZERO
+        };
+        // Likewise, only list running apps when an intent has an app trigger
+        let (launched, quit) = if config.intent.iter().any(|i| trigger_watches_apps(&i.trigger)) {
+            match (running_apps(), &prev_apps) {
+                (Some(apps), Some(prev)) => {
+                    let launched: Vec<RunningApp> = apps.difference(prev).cloned().collect();
+                    let quit: Vec<RunningApp> = prev.difference(&apps).cloned().collect();
+                    prev_apps = Some(apps);
+                    (launched, quit)
+                }
+                (Some(apps), None) => {
+                    prev_apps = Some(apps);
+                    (Vec::new(), Vec::new())
+                }
+                (None, _) => (Vec::new(), Vec::new()),
+            }
+        } else {
+            prev_apps = None;
+            (Vec::new(), Vec::new())
+        };
+        for app in &launched {
+            info!(app = %app.app_name, id = %app.app_id, "app launched");
+        }
+        for app in &quit {
+            info!(app = %app.app_name, id = %app.app_id, "app quit");
+        }
+        let now = Local::now();
+        let minute = now.timestamp() / 60;
+        let mut fired = false;
+
         // Check each intent
         for (i, intent) in config.intent.iter().enumerate() {
             let matcher = &matchers[i];
@@ -462,19 +1199,45 @@ fn run_daemon() -> Result<()> {
             if now_matches {
                 if state.matched_since.is_none() {
                     state.matched_since = Some(Instant::now());
-                    eprintln!("[{}] match: {}", Local::now().format("%H:%M:%S"), intent.name);
+                    info!(
+                        intent = %intent.name,
+                        app = %ctx.app_name,
+                        window = %ctx.window_title,
+                        "match"
+                    );
                 }
                 state.last_context = Some(ctx.clone());
             } else if !now_matches && prev_matches {
                 // Just exited - keep last_context for proposal
-                eprintln!("[{}] exit: {}", Local::now().format("%H:%M:%S"), intent.name);
+                info!(intent = %intent.name, app = %ctx.app_name, "exit");
             }
 
             // Check trigger conditions
+            let mut app_event: Option<SystemContext> = None;
             let should_trigger = match intent.trigger.as_str() {
                 "enter" => now_matches && !prev_matches,
                 "exit" => !now_matches && prev_matches,
                 "change" => now_matches != prev_matches,
+                "idle" => {
+                    let threshold = matcher.idle.unwrap_or(Duration::MAX);
+                    now_matches && idle >= threshold && prev_idle < threshold
+                }
+                "schedule" => {
+                    let due = matcher.schedule.as_ref().is_some_and(|s| s.matches(&now))
+                        && state.last_schedule_minute != Some(minute);
+                    if due {
+                        state.last_schedule_minute = Some(minute);
+                    }
+                    due && now_matches
+                }
+                "app_launch" | "app_quit" => {
+                    let apps = if intent.trigger == "app_launch" { &launched } else { &quit };
+                    app_event = apps
+                        .iter()
+                        .find(|app| matcher.matches_app(app))
+                        .map(RunningApp::context);
+                    app_event.is_some()
+                }
                 _ => false,
             };
 
@@ -486,15 +1249,26 @@ fn run_daemon() -> Result<()> {
             }
 
             // Check cooldown
-            if let Some(last) = state.last_triggered {
-                if last.elapsed() < Duration::from_secs(intent.cooldown) {
-                    state.matched_since = None;
-                    continue;
-                }
+            if in_cooldown(state.last_triggered, intent.cooldown, now_millis()) {
+                state.matched_since = None;
+                continue;
             }
 
-            // Get context for this trigger (use last matched context for exit triggers)
-            let trigger_ctx = if intent.trigger == "exit" {
+            // Check snooze (dismissed in Lin or `intent snooze`); read fresh
+            // each time since Lin writes the file behind our back
+            let snoozes = load_snoozes(&snoozed_path());
+            let now_secs = Utc::now().timestamp();
+            if let Some(until) = snoozed_until(&snoozes, &intent.name, intent.snooze, now_secs) {
+                info!(intent = %intent.name, until, "snoozed");
+                state.matched_since = None;
+                continue;
+            }
+
+            // Get context for this trigger (use last matched context for exit
+            // triggers, and the launched/quit app for app triggers)
+            let trigger_ctx = if let Some(event) = &app_event {
+                event
+            } else if intent.trigger == "exit" {
                 state.last_context.as_ref().unwrap_or(&prev_context)
             } else {
                 &ctx
@@ -503,25 +1277,65 @@ fn run_daemon() -> Result<()> {
             // Execute based on action_type
             match intent.action_type.as_str() {
                 "run" => {
-                    eprintln!("run: {} -> {}", intent.name, intent.action);
-                    execute_action(&intent.action);
+                    info!(
+                        intent = %intent.name,
+                        trigger = %intent.trigger,
+                        action = %intent.action,
+                        "run"
+                    );
+                    if let Err(err) = run_intent_action(intent, trigger_ctx) {
+                        warn!(intent = %intent.name, error = %err, "skipping run");
+                    }
+                }
+                "open" => {
+                    info!(
+                        intent = %intent.name,
+                        trigger = %intent.trigger,
+                        action = %intent.action,
+                        "open"
+                    );
+                    if let Err(err) = open_intent_url(intent, trigger_ctx) {
+                        warn!(intent = %intent.name, error = %err, "skipping open");
+                    }
                 }
                 "propose" | _ => {
                     let title = intent.title.as_deref().unwrap_or(&intent.name);
                     let action = resolve_action(&intent.action, trigger_ctx);
                     let context = trigger_ctx.infer_project();
-                    let _ = propose_to_lin(title, &action, context.as_deref());
+                    info!(
+                        intent = %intent.name,
+                        trigger = %intent.trigger,
+                        app = %trigger_ctx.app_name,
+                        "trigger"
+                    );
+                    if let Err(err) =
+                        propose_to_lin(title, &action, context.as_deref(), Some(&intent.name))
+                    {
+                        error!(intent = %intent.name, error = %err, "propose failed");
+                    }
                 }
             }
 
-            state.last_triggered = Some(Instant::now());
+            state.last_triggered = Some(now_millis());
             state.matched_since = None;
             state.last_context = None;
+            fired = true;
+        }
+
+        if fired {
+            if let Err(err) = save_cooldowns(&cooldowns, &states) {
+                warn!(path = %cooldowns.display(), error = %err, "failed to save cooldowns");
+            }
         }
 
         prev_context = ctx;
+        prev_idle = idle;
         thread::sleep(poll_interval);
     }
+
+    info!("shutting down");
+    remove_pidfile(&pidfile);
+    Ok(())
 }
 
 /// Resolve action template with context variables
@@ -541,17 +1355,120 @@ fn resolve_action(action: &str, ctx: &SystemContext) -> String {
     result
 }
 
-fn execute_action(action: &str) {
-    let result = Command::new("sh").args(["-c", action]).status();
+/// Resolve an intent's cwd against the trigger context. `Err` when it
+/// needs {project} and none could be inferred.
+fn resolve_cwd(intent: &Intent, ctx: &SystemContext) -> Result<Option<PathBuf>> {
+    let Some(cwd) = &intent.cwd else {
+        return Ok(None);
+    };
+    let resolved = resolve_action(cwd, ctx);
+    if resolved.contains("{project}") {
+        anyhow::bail!("cwd {:?} needs a project, none inferred from the window title", cwd);
+    }
+
+    let path = PathBuf::from(expand_path(&resolved));
+    if path.is_absolute() {
+        return Ok(Some(path));
+    }
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    Ok(Some(home.join(path)))
+}
+
+/// Run a "run" intent's action in its resolved cwd with its extra env.
+fn run_intent_action(intent: &Intent, ctx: &SystemContext) -> Result<()> {
+    let cwd = resolve_cwd(intent, ctx)?;
+    let env: Vec<(String, String)> = intent
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), expand_path(&resolve_action(value, ctx))))
+        .collect();
+
+    execute_action(&intent.action, cwd.as_deref(), &env);
+    Ok(())
+}
+
+/// Open an "open" intent's URL, resolved against the trigger context.
+fn open_intent_url(intent: &Intent, ctx: &SystemContext) -> Result<()> {
+    let url = resolve_url(&intent.action, ctx);
+    validate_url(&url)?;
+
+    let status = Command::new("open")
+        .arg(&url)
+        .status()
+        .context("failed to run open")?;
+    if !status.success() {
+        warn!(url, %status, "open failed");
+    }
+    Ok(())
+}
+
+/// Resolve an "open" URL template. {project} becomes an absolute path,
+/// since apps don't expand ~ in their deep links.
+fn resolve_url(url: &str, ctx: &SystemContext) -> String {
+    let url = match ctx.infer_project() {
+        Some(project) => url.replace("{project}", &expand_path(&project)),
+        None => url.to_string(),
+    };
+    resolve_action(&url, ctx)
+}
+
+/// Check that `url` is something `open` should hand to a browser or app:
+/// an http(s) URL with a host, or a deep link with its own scheme
+/// (`raycast://...`, `obsidian://...`, `mailto:...`). Schemes that would
+/// open or run local content, like file: and javascript:, are refused.
+fn validate_url(url: &str) -> Result<()> {
+    if url.contains("{project}") || url.contains("{deploy}") {
+        anyhow::bail!("{:?} needs a project, none inferred from the window title", url);
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        anyhow::bail!("{:?} contains whitespace; percent-encode it (%20)", url);
+    }
+
+    let Some((scheme, rest)) = url.split_once(':') else {
+        anyhow::bail!("{:?} has no scheme (expected e.g. https:// or an app's scheme://)", url);
+    };
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid_scheme {
+        anyhow::bail!("{:?} has an invalid scheme {:?}", url, scheme);
+    }
+
+    match scheme.to_ascii_lowercase().as_str() {
+        "http" | "https" => {
+            let host = rest
+                .strip_prefix("//")
+                .map(|r| r.split(['/', '?', '#']).next().unwrap_or(""));
+            if host.is_none_or(str::is_empty) {
+                anyhow::bail!("{:?} is missing a host", url);
+            }
+        }
+        "file" | "javascript" | "data" | "vbscript" => {
+            anyhow::bail!("{}: URLs aren't allowed in open actions; use a run action", scheme);
+        }
+        _ if rest.is_empty() => anyhow::bail!("{:?} is empty after the scheme", url),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn execute_action(action: &str, cwd: Option<&Path>, env: &[(String, String)]) {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", action]).envs(env.iter().map(|(k, v)| (k, v)));
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    let result = cmd.status();
 
     match result {
         Ok(status) => {
             if !status.success() {
-                eprintln!("action failed: exit {}", status);
+                warn!(action, %status, "action failed");
             }
         }
         Err(e) => {
-            eprintln!("action error: {}", e);
+            error!(action, error = %e, "action error");
         }
     }
 }
@@ -567,9 +1484,20 @@ fn list_intents() -> Result<()> {
         return Ok(());
     }
 
+    let snoozes = load_snoozes(&snoozed_path());
+    let now = Utc::now().timestamp();
     for intent in &config.intent {
+        let snoozed = snoozed_until(&snoozes, &intent.name, intent.snooze, now)
+            .and_then(|until| DateTime::from_timestamp(until, 0))
+            .map(|until| {
+                format!(
+                    " (snoozed until {})",
+                    until.with_timezone(&Local).format("%H:%M")
+                )
+            })
+            .unwrap_or_default();
         println!(
-            "{}: {} [{}:{}] -> {}",
+            "{}: {} [{}:{}] -> {}{}",
             intent.name,
             intent
                 .app
@@ -578,13 +1506,73 @@ fn list_intents() -> Result<()> {
                 .unwrap_or("*"),
             intent.action_type,
             intent.trigger,
-            intent.action
+            intent.action,
+            snoozed
         );
     }
 
     Ok(())
 }
 
+fn show_status() -> Result<()> {
+    let now = Utc::now();
+    match daemon_status(&pidfile_path()) {
+        DaemonStatus::Running(info) => println!(
+            "daemon: running (pid {}), up {} since {}",
+            info.pid,
+            format_elapsed((now - info.started_at).num_seconds()),
+            info.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        ),
+        DaemonStatus::Stale(info) => {
+            println!("daemon: not running (stale pidfile from pid {})", info.pid)
+        }
+        DaemonStatus::Stopped => println!("daemon: not running"),
+    }
+
+    let path = config_path();
+    let intents = if !path.exists() {
+        "not found".to_string()
+    } else {
+        match load_config() {
+            Ok(config) => format!("{} intents", config.intent.len()),
+            Err(err) => format!("invalid: {:#}", err),
+        }
+    };
+    println!("config: {} ({})", path.display(), intents);
+
+    // Cooldowns hold each intent's last firing, persisted by the daemon
+    let last = load_cooldowns(&cooldowns_path())
+        .into_iter()
+        .max_by_key(|(_, ms)| *ms)
+        .and_then(|(name, ms)| Some((name, DateTime::from_timestamp_millis(ms as i64)?)));
+    match last {
+        Some((name, at)) => println!(
+            "last trigger: {} at {} ({} ago)",
+            name,
+            at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            format_elapsed((now - at).num_seconds())
+        ),
+        None => println!("last trigger: none"),
+    }
+
+    Ok(())
+}
+
+/// Coarse duration for status lines: "45s", "12m", "2h 5m", "3d 4h".
+fn format_elapsed(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m", mins)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 fn trigger_intent(name: &str) -> Result<()> {
     let config = load_config()?;
 
@@ -594,19 +1582,75 @@ fn trigger_intent(name: &str) -> Result<()> {
         .find(|i| i.name == name)
         .ok_or_else(|| anyhow::anyhow!("intent not found: {}", name))?;
 
-    eprintln!("triggering: {} -> {}", intent.name, intent.action);
+    info!(intent = %intent.name, action = %intent.action, "triggering");
 
     match intent.action_type.as_str() {
-        "run" => execute_action(&intent.action),
+        "run" => {
+            // Only look at the frontmost window if the cwd depends on it
+            let ctx = if intent.cwd.as_deref().is_some_and(|c| c.contains("{project}"))
+                || intent.env.values().any(|v| v.contains("{project}"))
+            {
+                get_context(&config.context)
+            } else {
+                SystemContext::default()
+            };
+            run_intent_action(intent, &ctx)?;
+        }
+        "open" => {
+            let ctx = if intent.action.contains("{project}") || intent.action.contains("{deploy}") {
+                get_context(&config.context)
+            } else {
+                SystemContext::default()
+            };
+            open_intent_url(intent, &ctx)?;
+        }
         "propose" | _ => {
             let title = intent.title.as_deref().unwrap_or(&intent.name);
-            propose_to_lin(title, &intent.action, None)?;
+            propose_to_lin(title, &intent.action, None, Some(&intent.name))?;
         }
     }
 
     Ok(())
 }
 
+fn snooze_intent(name: &str, for_secs: Option<u64>, clear: bool) -> Result<()> {
+    let config = load_config()?;
+    let intent = config
+        .intent
+        .iter()
+        .find(|i| i.name == name)
+        .ok_or_else(|| anyhow::anyhow!("intent not found: {}", name))?;
+
+    let path = snoozed_path();
+    let mut snoozes = load_snoozes(&path);
+    if clear {
+        if snoozes.remove(name).is_none() {
+            println!("{} is not snoozed", name);
+            return Ok(());
+        }
+        save_snoozes(&path, &snoozes)?;
+        println!("{} unsnoozed", name);
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+    let until = now + for_secs.unwrap_or(intent.snooze) as i64;
+    snoozes.insert(
+        name.to_string(),
+        Snooze {
+            dismissed_at: Some(now),
+            until: Some(until),
+        },
+    );
+    save_snoozes(&path, &snoozes)?;
+
+    let until_local = DateTime::from_timestamp(until, 0)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    println!("{} snoozed until {}", name, until_local);
+    Ok(())
+}
+
 fn show_context() -> Result<()> {
     let config = load_config()?;
     let ctx = get_context(&config.context);
@@ -625,6 +1669,104 @@ fn show_context() -> Result<()> {
     Ok(())
 }
 
+fn test_intents(name: Option<&str>) -> Result<()> {
+    let config = load_config()?;
+
+    let intents: Vec<&Intent> = match name {
+        Some(name) => vec![config
+            .intent
+            .iter()
+            .find(|i| i.name == name)
+            .ok_or_else(|| anyhow::anyhow!("intent not found: {}", name))?],
+        None => config.intent.iter().collect(),
+    };
+    if intents.is_empty() {
+        println!("no intents configured");
+        println!("config: {}", config_path().display());
+        return Ok(());
+    }
+
+    let ctx = get_context(&config.context);
+    println!("app: {} ({})", ctx.app_name, ctx.app_id);
+    println!("window: {}", ctx.window_title);
+
+    let mark = |result: Option<bool>| match result {
+        Some(true) => "matched",
+        Some(false) => "failed",
+        None => "any",
+    };
+
+    for intent in intents {
+        println!();
+        let matcher = match IntentMatcher::new(intent) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                println!("{}: INVALID ({:#})", intent.name, e);
+                continue;
+            }
+        };
+
+        let (app_match, window_match) = matcher.match_parts(&ctx);
+        let verdict = if matcher.matches(&ctx) { "MATCH" } else { "NO MATCH" };
+        println!("{}: {}", intent.name, verdict);
+        println!(
+            "  app:     {:<8} {}",
+            mark(app_match),
+            intent.app.as_deref().unwrap_or("*")
+        );
+        println!(
+            "  window:  {:<8} {}",
+            mark(window_match),
+            intent.window.as_deref().unwrap_or("*")
+        );
+
+        let mut trigger = intent.trigger.clone();
+        if let Some(idle) = matcher.idle {
+            let current = system_idle_time().map_or("unknown".to_string(), |d| {
+                format!("{}s", d.as_secs())
+            });
+            trigger.push_str(&format!(" (idle {} of {}s)", current, idle.as_secs()));
+        }
+        if let Some(schedule) = &matcher.schedule {
+            let now = if schedule.matches(&Local::now()) {
+                "due this minute"
+            } else {
+                "not due now"
+            };
+            trigger.push_str(&format!(" ({})", now));
+        }
+        if trigger_watches_apps(&intent.trigger) {
+            let running = running_apps().map_or("unknown", |apps| {
+                if apps.iter().any(|app| matcher.matches_app(app)) {
+                    "running now"
+                } else {
+                    "not running"
+                }
+            });
+            trigger.push_str(&format!(" ({})", running));
+        }
+        println!("  trigger: {}", trigger);
+        if intent.action_type == "open" {
+            let url = resolve_url(&intent.action, &ctx);
+            println!("  action:  open {}", url);
+            if let Err(e) = validate_url(&url) {
+                println!("  url:     invalid ({})", e);
+            }
+        } else {
+            println!("  action:  {} {}", intent.action_type, resolve_action(&intent.action, &ctx));
+        }
+        if intent.cwd.is_some() {
+            match resolve_cwd(intent, &ctx) {
+                Ok(Some(cwd)) => println!("  cwd:     {}", cwd.display()),
+                Ok(None) => {}
+                Err(e) => println!("  cwd:     unresolved ({})", e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn watch_context() -> Result<()> {
     let config = load_config()?;
     let poll_interval = Duration::from_millis(config.context.poll_interval_ms);
@@ -651,3 +1793,192 @@ fn watch_context() -> Result<()> {
         thread::sleep(poll_interval);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_hid_idle_time_from_ioreg() {
+        let ioreg = r#"
+    | |   "HIDIdleTimeDelta" = 0
+    | |   "HIDIdleTime" = 12500000000
+    | |   "HIDParameters" = {}
+"#;
+        assert_eq!(parse_hid_idle_time(ioreg), Some(Duration::from_millis(12_500)));
+        assert_eq!(parse_hid_idle_time("\"HIDIdleTime\" = nope"), None);
+        assert_eq!(parse_hid_idle_time(""), None);
+    }
+
+    #[test]
+    fn config_defaults_and_rejects_a_zero_poll_interval() {
+        let config = parse_config("[[intent]]\nname = \"x\"\naction = \"true\"\n").unwrap();
+        assert_eq!(config.context.poll_interval_ms, 1000);
+        assert_eq!(config.context.source, "native");
+
+        let err = parse_config("[context]\npoll_interval_ms = 0\n").unwrap_err();
+        assert_eq!(err.to_string(), "context.poll_interval_ms must be greater than 0");
+    }
+
+    #[test]
+    fn schedule_matches_cron_fields() {
+        let weekday_mornings = Schedule::parse("*/15 9 * * 1-5").unwrap();
+        // 2024-06-03 is a Monday
+        let monday = Local.with_ymd_and_hms(2024, 6, 3, 9, 30, 0).unwrap();
+        let sunday = Local.with_ymd_and_hms(2024, 6, 2, 9, 30, 0).unwrap();
+        assert!(weekday_mornings.matches(&monday));
+        assert!(!weekday_mornings.matches(&sunday));
+        assert!(!weekday_mornings.matches(&(monday + chrono::Duration::minutes(1))));
+
+        let sundays = Schedule::parse("0 0 * * 7").unwrap();
+        assert!(sundays.matches(&Local.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap()));
+
+        assert!(Schedule::parse("0 9 * *").is_err());
+        assert!(Schedule::parse("60 9 * * *").is_err());
+    }
+
+    #[test]
+    fn app_triggers_diff_running_apps() {
+        let before =
+            parse_running_apps("com.apple.Safari\tSafari\ncom.tinyspeck.slackmacgap\tSlack\n");
+        let after = parse_running_apps("com.apple.Safari\tSafari\n\tSome Helper\n\t\n");
+        assert_eq!(after.len(), 2);
+
+        let quit: Vec<&RunningApp> = before.difference(&after).collect();
+        assert_eq!(quit.len(), 1);
+        assert_eq!(quit[0].app_name, "Slack");
+
+        let config: Config = toml::from_str(
+            "[[intent]]\nname = \"slack-quit\"\napp = \"(?i)slack\"\n\
+             trigger = \"app_quit\"\naction = \"x\"\n",
+        )
+        .unwrap();
+        let matcher = IntentMatcher::new(&config.intent[0]).unwrap();
+        assert!(matcher.matches_app(quit[0]));
+        assert!(!after.iter().any(|app| matcher.matches_app(app)));
+
+        let no_app: Config =
+            toml::from_str("[[intent]]\nname = \"x\"\ntrigger = \"app_launch\"\naction = \"x\"\n")
+                .unwrap();
+        assert!(IntentMatcher::new(&no_app.intent[0]).is_err());
+    }
+
+    #[test]
+    fn open_actions_validate_urls() {
+        for url in [
+            "https://github.com/nikivdev/rust/pulls",
+            "http://localhost:3000/?q=1",
+            "raycast://extensions/raycast/github/my-pull-requests",
+            "obsidian://open?vault=notes&file=today",
+            "mailto:me@example.com",
+            "zed://file/Users/me/src/app",
+        ] {
+            assert!(validate_url(url).is_ok(), "{url}");
+        }
+        for url in [
+            "github.com/nikivdev",
+            "https:/github.com",
+            "https://",
+            "https://github.com/{project}",
+            "https://example.com/a b",
+            "file:///Applications/Calculator.app",
+            "JavaScript:alert(1)",
+            "1password://open",
+            "slack:",
+        ] {
+            assert!(validate_url(url).is_err(), "{url}");
+        }
+
+        let ctx = SystemContext {
+            app_id: "com.microsoft.VSCode".into(),
+            window_title: "main.rs — ~/src/app".into(),
+            ..Default::default()
+        };
+        let url = resolve_url("vscode://file{project}", &ctx);
+        assert!(url.starts_with("vscode://file/"), "{url}");
+        assert!(url.ends_with("/src/app"), "{url}");
+        assert!(validate_url(&url).is_ok());
+    }
+
+    #[test]
+    fn cooldowns_survive_a_restart() {
+        let dir = std::env::temp_dir().join(format!("intent-cooldowns-{}", std::process::id()));
+        let path = dir.join("cooldowns.json");
+        let config: Config = toml::from_str(
+            "[[intent]]\nname = \"a\"\naction = \"x\"\n[[intent]]\nname = \"b\"\naction = \"y\"\n",
+        )
+        .unwrap();
+
+        let mut states = build_states(&config, HashMap::new());
+        states.get_mut("a").unwrap().last_triggered = Some(1_000_000);
+        save_cooldowns(&path, &states).unwrap();
+
+        let loaded = load_cooldowns(&path);
+        assert_eq!(loaded.get("a"), Some(&1_000_000));
+        assert!(!loaded.contains_key("b"));
+        assert!(load_cooldowns(&dir.join("missing.json")).is_empty());
+
+        assert!(in_cooldown(loaded.get("a").copied(), 60, 1_000_000 + 59_999));
+        assert!(!in_cooldown(loaded.get("a").copied(), 60, 1_000_000 + 60_000));
+        assert!(!in_cooldown(loaded.get("a").copied(), 60, 999_000));
+        assert!(!in_cooldown(None, 60, 1_000_000));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pidfile_status_detects_stale_daemons() {
+        let dir = std::env::temp_dir().join(format!("intent-pidfile-{}", std::process::id()));
+        let path = dir.join("daemon.json");
+        assert_eq!(daemon_status(&path), DaemonStatus::Stopped);
+
+        let ours = DaemonInfo {
+            pid: std::process::id(),
+            started_at: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+        };
+        write_pidfile(&path, &ours).unwrap();
+        assert_eq!(daemon_status(&path), DaemonStatus::Running(ours.clone()));
+
+        let mut child = Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        let gone = DaemonInfo {
+            pid: child.id(),
+            ..ours.clone()
+        };
+        write_pidfile(&path, &gone).unwrap();
+        assert_eq!(daemon_status(&path), DaemonStatus::Stale(gone));
+        // Someone else's pidfile is left alone on shutdown
+        remove_pidfile(&path);
+        assert!(path.exists());
+
+        write_pidfile(&path, &ours).unwrap();
+        remove_pidfile(&path);
+        assert!(!path.exists());
+
+        assert_eq!(format_elapsed(45), "45s");
+        assert_eq!(format_elapsed(12 * 60 + 5), "12m");
+        assert_eq!(format_elapsed(2 * 3600 + 5 * 60), "2h 5m");
+        assert_eq!(format_elapsed(3 * 86_400 + 4 * 3600 + 59), "3d 4h");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn snooze_uses_until_or_the_intent_default() {
+        let snoozes: BTreeMap<String, Snooze> = serde_json::from_str(
+            r#"{
+                "dismissed": { "dismissed_at": 1000 },
+                "pinned": { "dismissed_at": 1000, "until": 5000 },
+                "garbage": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(snoozed_until(&snoozes, "dismissed", 600, 1599), Some(1600));
+        assert_eq!(snoozed_until(&snoozes, "dismissed", 600, 1600), None);
+        assert_eq!(snoozed_until(&snoozes, "pinned", 600, 4999), Some(5000));
+        assert_eq!(snoozed_until(&snoozes, "garbage", 600, 0), None);
+        assert_eq!(snoozed_until(&snoozes, "unknown", 600, 0), None);
+    }
+}
@@ -12,12 +12,41 @@ fn main() {
 
 fn try_main() -> Result<()> {
     let cli = Cli::parse();
+    preflight(&cli.command)?;
 
     match cli.command {
-        Commands::CreateOpen { name, app, path, goku } => create_open_macro(&name, &app, &path, goku.as_deref()),
-        Commands::List => list_macros(),
-        Commands::Run { name } => run_macro(&name),
+        Commands::CreateOpen {
+            name,
+            app,
+            path,
+            goku,
+            hotkey,
+            replace,
+            dry_run,
+        } => create_open_macro(
+            &name,
+            &app,
+            &path,
+            goku.as_deref(),
+            hotkey.as_deref(),
+            replace,
+            dry_run,
+        ),
+        Commands::CreateFrom { file, name, dry_run } => {
+            create_from_file(&file, name.as_deref(), dry_run)
+        }
+        Commands::List {
+            pattern,
+            group,
+            json,
+        } => list_macros(pattern.as_deref(), group.as_deref(), json),
+        Commands::Run { name, param } => run_macro(&name, param.as_deref()),
         Commands::Inspect { name } => inspect_macro(&name),
+        Commands::Toggle { name, on, off } => toggle_enabled(KmObject::Macro, &name, on, off),
+        Commands::ToggleGroup { name, on, off } => {
+            toggle_enabled(KmObject::MacroGroup, &name, on, off)
+        }
+        Commands::CheckGoku => check_goku(),
     }
 }
 
@@ -35,6 +64,9 @@ enum Commands {
     /// Examples:
     ///   km create-open "zed: focus" Zed "~/org/1f/focus"
     ///   km create-open "zed: focus" Zed "~/org/1f/focus" --goku v.o
+    ///   km create-open "zed: focus" Zed "~/org/1f/focus" --hotkey "Cmd+Opt+O"
+    ///   km create-open "zed: focus" Zed "~/org/1f/focus" --dry-run
+    ///   km create-open "zed: focus" Zed "~/org/1f/other" --goku v.o --replace
     CreateOpen {
         /// Macro name (e.g., "zed: focus").
         name: String,
@@ -45,27 +77,140 @@ enum Commands {
         /// Goku binding in "layer.key" format (e.g., "v.o" for v-mode + o key).
         #[arg(long)]
         goku: Option<String>,
+        /// Native Keyboard Maestro hot key, e.g. "Cmd+Opt+O" or "Ctrl+Shift+F5".
+        #[arg(long)]
+        hotkey: Option<String>,
+        /// Overwrite a macro with the same name. The old one is only deleted
+        /// once the new one has imported; a --goku binding already pointing
+        /// at this macro is kept.
+        #[arg(long)]
+        replace: bool,
+        /// Print the generated plist instead of importing it (no goku changes).
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create a macro from a JSON list of actions and import it.
+    ///
+    /// Accepts `{"name": ..., "actions": [...]}` or a bare action array (e.g.
+    /// `km inspect` output, with --name). Actions are either raw Keyboard
+    /// Maestro dicts (with "MacroActionType") or a "type" plus fields, where
+    /// "type" is IfThenElse, ManipulateWindow or ExecuteShellScript and
+    /// missing fields get the same defaults as create-open.
+    ///
+    /// Examples:
+    ///   km inspect "zed: focus" > focus.json && km create-from focus.json --name "zed: focus 2"
+    ///   km create-from actions.json --dry-run
+    CreateFrom {
+        /// JSON file describing the actions.
+        file: std::path::PathBuf,
+        /// Macro name (overrides "name" in the file).
+        #[arg(long)]
+        name: Option<String>,
+        /// Print the generated plist instead of importing it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List macros, optionally filtered by name or group.
+    ///
+    /// Examples:
+    ///   km list zed
+    ///   km list --group Global --json
+    List {
+        /// Case-insensitive filter on macro (or group) names; letters may be
+        /// spread out, e.g. "zfoc" finds "zed: focus".
+        pattern: Option<String>,
+        /// Only macros in this group.
+        #[arg(long)]
+        group: Option<String>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
     },
-    /// List all macros.
-    List,
     /// Run a macro by name.
+    ///
+    /// Examples:
+    ///   km run "zed: focus"
+    ///   km run "open url" --param "https://example.com"
     Run {
         /// Macro name to run.
         name: String,
+        /// Parameter passed to the macro (available as %TriggerValue%).
+        #[arg(long)]
+        param: Option<String>,
     },
     /// Inspect a macro's actions as JSON.
     Inspect {
         /// Macro name to inspect.
         name: String,
     },
+    /// Enable or disable a macro (flips it unless --on/--off is given).
+    ///
+    /// Examples:
+    ///   km toggle "zed: focus"
+    ///   km toggle "zed: focus" --off
+    Toggle {
+        /// Macro name.
+        name: String,
+        /// Enable the macro.
+        #[arg(long, conflicts_with = "off")]
+        on: bool,
+        /// Disable the macro.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Enable or disable a macro group (flips it unless --on/--off is given).
+    ///
+    /// Examples:
+    ///   km toggle-group Zed --off
+    ToggleGroup {
+        /// Macro group name.
+        name: String,
+        /// Enable the group.
+        #[arg(long, conflicts_with = "off")]
+        on: bool,
+        /// Disable the group.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Report keys bound more than once in a goku layer and bindings to missing macros.
+    CheckGoku,
 }
 
 const KARABINER_CONFIG: &str = "/Users/nikiv/config/i/karabiner/karabiner.edn";
 
-fn create_open_macro(name: &str, app: &str, path: &str, goku: Option<&str>) -> Result<()> {
+fn create_open_macro(
+    name: &str,
+    app: &str,
+    path: &str,
+    goku: Option<&str>,
+    hotkey: Option<&str>,
+    replace: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let hotkey = hotkey.map(Hotkey::parse).transpose()?;
+
+    // Get folder name from path for matching
+    let folder_name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+
+    let plist = generate_open_macro_plist(name, app, path, folder_name, hotkey.as_ref());
+
+    // Dry runs stop before anything touches Keyboard Maestro or karabiner.edn
+    if dry_run {
+        println!("{}", plist);
+        return Ok(());
+    }
+
     // Check if macro already exists
-    if macro_exists(name)? {
-        bail!("macro '{}' already exists in Keyboard Maestro", name);
+    let exists = macro_exists(name)?;
+    if exists && !replace {
+        bail!(
+            "macro '{}' already exists in Keyboard Maestro (use --replace to overwrite it)",
+            name
+        );
     }
 
     // Parse and validate goku binding if provided
@@ -77,50 +222,149 @@ fn create_open_macro(name: &str, app: &str, path: &str, goku: Option<&str>) -> R
         let layer = parts[0];
         let key = parts[1];
 
-        // Check if key already bound in layer
-        if goku_key_exists(layer, key)? {
-            bail!("key '{}' already bound in layer '{}'. Use 'karabiner comment {} {}' first.", key, layer, layer, key);
+        // Check if key already bound in layer (fine when replacing, if it's
+        // bound to this very macro)
+        let content = std::fs::read_to_string(KARABINER_CONFIG)
+            .context("failed to read karabiner.edn")?;
+        match goku_rule(&content, layer, key)? {
+            Some(rule) if replace && rule.km_macro.as_deref() == Some(name) => None,
+            Some(_) => bail!(
+                "key '{}' already bound in layer '{}'. Use 'karabiner comment {} {}' first.",
+                key,
+                layer,
+                layer,
+                key
+            ),
+            None => Some((layer.to_string(), key.to_string())),
         }
-
-        Some((layer.to_string(), key.to_string()))
     } else {
         None
     };
 
-    // Get folder name from path for matching
-    let folder_name = path
-        .trim_end_matches('/')
-        .rsplit('/')
-        .next()
-        .unwrap_or(path);
+    // Import via Keyboard Maestro
+    if exists {
+        replace_macro(name, &plist)?;
+        println!("replaced macro: {}", name);
+    } else {
+        import_macro_plist(&plist)?;
+        println!("created macro: {}", name);
+    }
+    if let Some(hotkey) = &hotkey {
+        println!("bound hot key: {} -> {}", hotkey, name);
+    }
 
-    let plist = generate_open_macro_plist(name, app, path, folder_name);
+    // Add goku binding if provided
+    match (goku_binding, goku) {
+        (Some((layer, key)), _) => {
+            add_goku_rule(&layer, &key, name)?;
+            println!("added goku binding: {}.{} -> {}", layer, key, name);
+        }
+        (None, Some(binding)) => println!("kept goku binding: {} -> {}", binding, name),
+        (None, None) => {}
+    }
 
-    // Import via Keyboard Maestro
-    import_macro_plist(&plist)?;
+    Ok(())
+}
 
-    println!("created macro: {}", name);
+/// Swap in a new version of an existing macro. The old one is renamed out
+/// of the way and only deleted once the new one shows up, so a failed import
+/// puts it back instead of leaving no macro at all.
+fn replace_macro(name: &str, plist: &str) -> Result<()> {
+    let parked = format!("{} (replaced by km)", name);
+    rename_macro(name, &parked)?;
 
-    // Add goku binding if provided
-    if let Some((layer, key)) = goku_binding {
-        add_goku_rule(&layer, &key, name)?;
-        println!("added goku binding: {}.{} -> {}", layer, key, name);
+    let imported = import_macro_plist(plist).and_then(|()| {
+        if !macro_exists(name)? {
+            bail!("Keyboard Maestro didn't import the new macro");
+        }
+        Ok(())
+    });
+    if let Err(err) = imported {
+        rename_macro(&parked, name)
+            .with_context(|| format!("{:#}; the old macro is left as '{}'", err, parked))?;
+        return Err(err.context("kept the existing macro"));
     }
 
+    delete_macro(&parked).with_context(|| {
+        format!("created the new macro, but the old one is left as '{}'", parked)
+    })
+}
+
+fn rename_macro(from: &str, to: &str) -> Result<()> {
+    let from_escaped = from.replace('\\', "\\\\").replace('"', "\\\"");
+    let to_escaped = to.replace('\\', "\\\\").replace('"', "\\\"");
+    run_km_script(
+        &format!(
+            r#"tell application "Keyboard Maestro"
+    set name of (first macro whose name is "{}") to "{}"
+end tell"#,
+            from_escaped, to_escaped
+        ),
+        &format!("rename macro '{}'", from),
+    )
+}
+
+fn delete_macro(name: &str) -> Result<()> {
+    let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+    run_km_script(
+        &format!(
+            r#"tell application "Keyboard Maestro"
+    delete (first macro whose name is "{}")
+end tell"#,
+            escaped
+        ),
+        &format!("delete macro '{}'", name),
+    )
+}
+
+/// Run an AppleScript that only has an effect, failing with its stderr.
+fn run_km_script(script: &str, what: &str) -> Result<()> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .context("failed to run osascript")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to {}: {}", what, stderr.trim());
+    }
     Ok(())
 }
 
 fn macro_exists(name: &str) -> Result<bool> {
+    object_exists(KmObject::Macro, name)
+}
+
+/// Things in the Keyboard Maestro editor that can be looked up by name.
+#[derive(Clone, Copy)]
+enum KmObject {
+    Macro,
+    MacroGroup,
+}
+
+impl KmObject {
+    /// AppleScript class name.
+    fn class(self) -> &'static str {
+        match self {
+            KmObject::Macro => "macro",
+            KmObject::MacroGroup => "macro group",
+        }
+    }
+}
+
+fn object_exists(kind: KmObject, name: &str) -> Result<bool> {
     let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
     let script = format!(
         r#"tell application "Keyboard Maestro"
     try
-        set m to first macro whose name is "{}"
+        set m to first {} whose name is "{}"
         return "EXISTS"
     on error
         return "NOT_FOUND"
     end try
 end tell"#,
+        kind.class(),
         escaped
     );
 
@@ -134,10 +378,8 @@ end tell"#,
     Ok(result == "EXISTS")
 }
 
-fn goku_key_exists(layer: &str, key: &str) -> Result<bool> {
-    let content = std::fs::read_to_string(KARABINER_CONFIG)
-        .context("failed to read karabiner.edn")?;
-
+/// The active rule for `key` in `layer`'s section of karabiner.edn, if any.
+fn goku_rule(content: &str, layer: &str, key: &str) -> Result<Option<GokuBinding>> {
     // Find the layer section
     let layer_pattern = if layer == "semicolon" {
         "colonkey".to_string()
@@ -147,57 +389,244 @@ fn goku_key_exists(layer: &str, key: &str) -> Result<bool> {
         format!("{}key", layer)
     };
 
-    // Look for {:des "<layer>key pattern
-    let des_pattern = format!(r#"\{{:des\s+"{}[^"]*""#, regex::escape(&layer_pattern));
-    let des_re = regex::Regex::new(&des_pattern)?;
-
-    let section_start = match des_re.find(&content) {
-        Some(m) => m.start(),
-        None => return Ok(false), // Layer not found
+    // Look for {:des "<layer>key section
+    let section = match goku_sections(content)?
+        .into_iter()
+        .find(|s| s.des.starts_with(&layer_pattern))
+    {
+        Some(s) => s,
+        None => return Ok(None), // Layer not found
     };
 
-    // Find section end
-    let section = &content[section_start..];
-    let mut depth = 0;
+    // Active (non-commented) rule with this key in that section
+    Ok(goku_bindings(content)?
+        .into_iter()
+        .find(|b| b.layer == section.des && b.key == key))
+}
+
+/// A `{:des "..." :rules [...]}` block in karabiner.edn.
+struct GokuSection<'a> {
+    des: String,
+    /// Byte offset of the section in the file
+    start: usize,
+    body: &'a str,
+}
+
+/// Find every `{:des "..."}` section, each cut at its matching close brace.
+fn goku_sections(content: &str) -> Result<Vec<GokuSection<'_>>> {
+    let des_re = regex::Regex::new(r#"\{:des\s+"([^"]*)""#)?;
+
+    Ok(des_re
+        .captures_iter(content)
+        .map(|caps| {
+            let start = caps.get(0).unwrap().start();
+            let section = &content[start..];
+            GokuSection {
+                des: caps[1].to_string(),
+                start,
+                body: &section[..section_end(section)],
+            }
+        })
+        .collect())
+}
+
+/// Byte offset of every bracket or brace in `text` that isn't inside a string.
+fn edn_brackets(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
     let mut in_string = false;
-    let mut section_end = section.len();
+    let mut escaped = false;
 
-    for (i, c) in section.char_indices() {
+    text.char_indices().filter(move |&(_, c)| {
+        if escaped {
+            escaped = false;
+            return false;
+        }
         match c {
+            '\\' if in_string => escaped = true,
             '"' => in_string = !in_string,
-            '{' | '[' if !in_string => depth += 1,
-            '}' | ']' if !in_string => {
-                depth -= 1;
-                if depth == 0 {
-                    section_end = i + 1;
-                    break;
-                }
-            }
+            '{' | '[' | '}' | ']' => return !in_string,
             _ => {}
         }
+        false
+    })
+}
+
+/// Length of the bracketed form at the start of `section`.
+fn section_end(section: &str) -> usize {
+    let mut depth = 0;
+
+    for (i, c) in edn_brackets(section) {
+        if matches!(c, '{' | '[') {
+            depth += 1;
+        } else {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
     }
 
-    let section_content = &section[..section_end];
+    section.len()
+}
 
-    // Check for active (non-commented) rule with this key
-    let key_pattern = format!(r#"(?m)^\s+\[:{}[\s\[]"#, regex::escape(key));
-    let key_re = regex::Regex::new(&key_pattern)?;
+/// Check that every brace and bracket in karabiner.edn closes the one it
+/// should, naming the line of the first mismatch.
+fn check_edn_balanced(content: &str) -> Result<()> {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let mut open: Vec<(usize, char)> = Vec::new();
 
-    Ok(key_re.is_match(section_content))
+    for (i, c) in edn_brackets(content) {
+        match c {
+            '{' | '[' => open.push((i, c)),
+            _ => match open.pop() {
+                Some((_, opener)) if (opener == '{') == (c == '}') => {}
+                Some((j, opener)) => bail!(
+                    "line {}: '{}' closes '{}' opened on line {}",
+                    line_of(i),
+                    c,
+                    opener,
+                    line_of(j)
+                ),
+                None => bail!("line {}: unmatched '{}'", line_of(i), c),
+            },
+        }
+    }
+
+    if let Some((j, opener)) = open.pop() {
+        bail!("line {}: '{}' is never closed", line_of(j), opener);
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct GokuBinding {
+    layer: String,
+    key: String,
+    line: usize,
+    /// Macro name when the rule runs `[:km "..."]`
+    km_macro: Option<String>,
+}
+
+/// Active (non-commented) rules in every section of karabiner.edn.
+fn goku_bindings(content: &str) -> Result<Vec<GokuBinding>> {
+    let rule_re = regex::Regex::new(r#"(?m)^\s+\[:([^\s\[\]]+)[\s\[].*$"#)?;
+    let km_re = regex::Regex::new(r#"\[:km\s+"([^"]+)"\]"#)?;
+
+    let mut bindings = Vec::new();
+    for section in goku_sections(content)? {
+        for caps in rule_re.captures_iter(section.body) {
+            let whole = caps.get(0).unwrap();
+            let offset = section.start + whole.start();
+            bindings.push(GokuBinding {
+                layer: section.des.clone(),
+                key: caps[1].to_string(),
+                line: content[..offset].matches('\n').count() + 1,
+                km_macro: km_re.captures(whole.as_str()).map(|c| c[1].to_string()),
+            });
+        }
+    }
+    Ok(bindings)
 }
 
+fn check_goku() -> Result<()> {
+    let content = std::fs::read_to_string(KARABINER_CONFIG)
+        .context("failed to read karabiner.edn")?;
+    let bindings = goku_bindings(&content)?;
+
+    let mut problems = 0;
+
+    // Same key bound more than once in a layer
+    let mut by_key: std::collections::BTreeMap<(&str, &str), Vec<usize>> =
+        std::collections::BTreeMap::new();
+    for b in &bindings {
+        by_key.entry((&b.layer, &b.key)).or_default().push(b.line);
+    }
+    let duplicates: Vec<_> = by_key.iter().filter(|(_, lines)| lines.len() > 1).collect();
+    if !duplicates.is_empty() {
+        println!("duplicate bindings:");
+        for ((layer, key), lines) in &duplicates {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            println!("  {}: :{} bound {}x (lines {})", layer, key, lines.len(), lines.join(", "));
+        }
+        problems += duplicates.len();
+    }
+
+    // [:km "..."] rules pointing at macros Keyboard Maestro doesn't have
+    match fetch_macro_names() {
+        Ok(macros) => {
+            let names: std::collections::HashSet<&str> =
+                macros.iter().map(|(name, _)| name.as_str()).collect();
+            let missing: Vec<_> = bindings
+                .iter()
+                .filter(|b| b.km_macro.as_deref().is_some_and(|m| !names.contains(m)))
+                .collect();
+            if !missing.is_empty() {
+                println!("missing macros:");
+                for b in &missing {
+                    println!(
+                        "  {}: :{} -> \"{}\" (line {})",
+                        b.layer,
+                        b.key,
+                        b.km_macro.as_deref().unwrap_or_default(),
+                        b.line
+                    );
+                }
+                problems += missing.len();
+            }
+        }
+        Err(err) => eprintln!("skipping macro check: {}", err),
+    }
+
+    if problems > 0 {
+        bail!("found {} problem(s) in {}", problems, KARABINER_CONFIG);
+    }
+    println!("no conflicts in {} bindings", bindings.len());
+    Ok(())
+}
+
+/// Add a rule with the karabiner CLI, which edits karabiner.edn and
+/// regenerates with goku. The file is checked before and after, and put
+/// back from karabiner.edn.bak if the CLI fails or leaves it unbalanced.
 fn add_goku_rule(layer: &str, key: &str, action: &str) -> Result<()> {
+    let original = std::fs::read_to_string(KARABINER_CONFIG)
+        .context("failed to read karabiner.edn")?;
+    check_edn_balanced(&original).with_context(|| {
+        format!("{} doesn't parse; fix it before adding rules", KARABINER_CONFIG)
+    })?;
+
+    let backup = format!("{}.bak", KARABINER_CONFIG);
+    std::fs::write(&backup, &original).with_context(|| format!("failed to write {}", backup))?;
+
     // Call karabiner CLI to add the rule
     let status = Command::new("karabiner")
         .args(["add", layer, key, action])
         .status()
         .context("failed to run karabiner CLI")?;
 
-    if !status.success() {
-        bail!("karabiner add failed");
-    }
+    let failure = if !status.success() {
+        Some(format!("karabiner add failed ({})", status))
+    } else {
+        let edited = std::fs::read_to_string(KARABINER_CONFIG)
+            .context("failed to read karabiner.edn")?;
+        check_edn_balanced(&edited)
+            .err()
+            .map(|err| format!("karabiner add left karabiner.edn unbalanced: {}", err))
+    };
+    let Some(failure) = failure else {
+        return Ok(());
+    };
 
-    Ok(())
+    let current = std::fs::read_to_string(KARABINER_CONFIG).unwrap_or_default();
+    if current == original {
+        bail!("{}; karabiner.edn was not changed", failure);
+    }
+    std::fs::write(KARABINER_CONFIG, &original).with_context(|| {
+        format!("{}; restoring {} from {} also failed", failure, KARABINER_CONFIG, backup)
+    })?;
+    eprintln!(
+        "rolled back {} to before adding {}.{} -> {} (backup kept at {})",
+        KARABINER_CONFIG, layer, key, action, backup
+    );
+    bail!("{}; karabiner.edn restored, run goku to regenerate from it", failure);
 }
 
 fn get_bundle_identifier(app: &str) -> Option<String> {
@@ -215,7 +644,120 @@ fn get_bundle_identifier(app: &str) -> Option<String> {
     None
 }
 
-fn generate_open_macro_plist(name: &str, app: &str, path: &str, folder_name: &str) -> String {
+/// A Keyboard Maestro hot key trigger: a virtual key code plus Carbon
+/// modifier flags (not the NSEvent flags in AppleSymbolicHotKeys).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Hotkey {
+    key_code: u16,
+    modifiers: u32,
+}
+
+const CMD_KEY: u32 = 1 << 8;
+const SHIFT_KEY: u32 = 1 << 9;
+const OPTION_KEY: u32 = 1 << 11;
+const CONTROL_KEY: u32 = 1 << 12;
+
+/// Virtual key codes for the US layout, the same table as the macos crate's
+/// `keycode_to_string`.
+const KEY_CODES: &[(&str, u16)] = &[
+    ("A", 0), ("S", 1), ("D", 2), ("F", 3), ("H", 4), ("G", 5), ("Z", 6), ("X", 7),
+    ("C", 8), ("V", 9), ("B", 11), ("Q", 12), ("W", 13), ("E", 14), ("R", 15),
+    ("Y", 16), ("T", 17), ("1", 18), ("2", 19), ("3", 20), ("4", 21), ("6", 22),
+    ("5", 23), ("=", 24), ("9", 25), ("7", 26), ("-", 27), ("8", 28), ("0", 29),
+    ("]", 30), ("O", 31), ("U", 32), ("[", 33), ("I", 34), ("P", 35), ("Return", 36),
+    ("L", 37), ("J", 38), ("'", 39), ("K", 40), (";", 41), ("\\", 42), (",", 43),
+    ("/", 44), ("N", 45), ("M", 46), (".", 47), ("Tab", 48), ("Space", 49), ("`", 50),
+    ("Delete", 51), ("Escape", 53), ("F5", 96), ("F6", 97), ("F7", 98), ("F3", 99),
+    ("F8", 100), ("F9", 101), ("F11", 103), ("F13", 105), ("F16", 106), ("F14", 107),
+    ("F10", 109), ("F12", 111), ("F15", 113), ("Help", 114), ("Home", 115),
+    ("PageUp", 116), ("ForwardDelete", 117), ("F4", 118), ("End", 119), ("F2", 120),
+    ("PageDown", 121), ("F1", 122), ("Left", 123), ("Right", 124), ("Down", 125),
+    ("Up", 126),
+];
+
+impl Hotkey {
+    /// Parse "Cmd+Opt+O": modifiers in any order, then exactly one key.
+    fn parse(spec: &str) -> Result<Self> {
+        let parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let Some((key, mods)) = parts.split_last().filter(|(key, _)| !key.is_empty()) else {
+            bail!("hot key '{}' has no key (e.g. 'Cmd+Opt+O')", spec);
+        };
+
+        let mut modifiers = 0;
+        for m in mods {
+            modifiers |= match m.to_lowercase().as_str() {
+                "cmd" | "command" | "⌘" => CMD_KEY,
+                "shift" | "⇧" => SHIFT_KEY,
+                "opt" | "option" | "alt" | "⌥" => OPTION_KEY,
+                "ctrl" | "control" | "⌃" => CONTROL_KEY,
+                _ => bail!("unknown modifier '{}' in '{}' (use Cmd, Opt, Ctrl, Shift)", m, spec),
+            };
+        }
+
+        let lower = key.to_lowercase();
+        let name = match lower.as_str() {
+            "enter" => "return",
+            "esc" => "escape",
+            "backspace" => "delete",
+            "del" => "forwarddelete",
+            "pgup" => "pageup",
+            "pgdn" => "pagedown",
+            other => other,
+        };
+        let Some(&(_, key_code)) = KEY_CODES.iter().find(|(k, _)| k.eq_ignore_ascii_case(name))
+        else {
+            bail!("unknown key '{}' in '{}'", key, spec);
+        };
+
+        // A bare letter or digit would swallow normal typing
+        let is_function_key = name.len() > 1
+            && name.starts_with('f')
+            && name[1..].parse::<u8>().is_ok();
+        if modifiers == 0 && !is_function_key {
+            bail!("hot key '{}' needs a modifier (Cmd, Opt, Ctrl or Shift)", spec);
+        }
+
+        Ok(Self { key_code, modifiers })
+    }
+
+    /// The `Triggers` entry Keyboard Maestro stores for a hot key.
+    fn trigger(&self) -> serde_json::Value {
+        serde_json::json!({
+            "FireType": "Pressed",
+            "KeyCode": self.key_code,
+            "MacroTriggerType": "HotKey",
+            "Modifiers": self.modifiers,
+        })
+    }
+}
+
+impl std::fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (flag, label) in [
+            (CONTROL_KEY, "Ctrl+"),
+            (OPTION_KEY, "Opt+"),
+            (SHIFT_KEY, "Shift+"),
+            (CMD_KEY, "Cmd+"),
+        ] {
+            if self.modifiers & flag != 0 {
+                f.write_str(label)?;
+            }
+        }
+        let key = KEY_CODES
+            .iter()
+            .find(|&&(_, code)| code == self.key_code)
+            .map_or("?", |&(k, _)| k);
+        f.write_str(key)
+    }
+}
+
+fn generate_open_macro_plist(
+    name: &str,
+    app: &str,
+    path: &str,
+    folder_name: &str,
+    hotkey: Option<&Hotkey>,
+) -> String {
     let name_escaped = escape_xml(name);
     let app_escaped = escape_xml(app);
     let path_escaped = escape_xml(path);
@@ -223,6 +765,9 @@ fn generate_open_macro_plist(name: &str, app: &str, path: &str, folder_name: &st
     let bundle_id = get_bundle_identifier(app).unwrap_or_default();
     let bundle_id_escaped = escape_xml(&bundle_id);
     let macro_uid = uuid::Uuid::new_v4().to_string().to_uppercase();
+    let mut triggers = String::new();
+    let trigger_list = hotkey.iter().map(|h| h.trigger()).collect();
+    write_plist_value(&serde_json::Value::Array(trigger_list), 4, &mut triggers);
 
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -238,8 +783,7 @@ fn generate_open_macro_plist(name: &str, app: &str, path: &str, folder_name: &st
 				<key>Name</key>
 				<string>{name}</string>
 				<key>Triggers</key>
-				<array/>
-				<key>UID</key>
+{triggers}				<key>UID</key>
 				<string>{macro_uid}</string>
 				<key>Actions</key>
 				<array>
@@ -339,10 +883,200 @@ fn generate_open_macro_plist(name: &str, app: &str, path: &str, folder_name: &st
         app = app_escaped,
         path = path_escaped,
         folder = folder_escaped,
-        bundle_id = bundle_id_escaped
+        bundle_id = bundle_id_escaped,
+        triggers = triggers
     )
 }
 
+fn create_from_file(file: &std::path::Path, name: Option<&str>, dry_run: bool) -> Result<()> {
+    let text = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let spec: serde_json::Value =
+        serde_json::from_str(&text).with_context(|| format!("invalid JSON in {}", file.display()))?;
+
+    let (file_name, actions) = match &spec {
+        serde_json::Value::Array(actions) => (None, actions.clone()),
+        serde_json::Value::Object(obj) => (
+            obj.get("name").and_then(|n| n.as_str()),
+            obj.get("actions")
+                .and_then(|a| a.as_array())
+                .cloned()
+                .context("spec object needs an \"actions\" array")?,
+        ),
+        _ => bail!("spec must be an action array or an object with \"actions\""),
+    };
+    let Some(name) = name.or(file_name) else {
+        bail!("macro name missing: pass --name or set \"name\" in the file");
+    };
+
+    let actions = expand_actions(&actions)?;
+    let plist = generate_macro_plist(name, actions);
+
+    if dry_run {
+        println!("{}", plist);
+        return Ok(());
+    }
+
+    if macro_exists(name)? {
+        bail!("macro '{}' already exists in Keyboard Maestro", name);
+    }
+    import_macro_plist(&plist)?;
+    println!("created macro: {}", name);
+    Ok(())
+}
+
+const SPEC_ACTION_TYPES: &[&str] = &["IfThenElse", "ManipulateWindow", "ExecuteShellScript"];
+
+/// Turn spec actions into Keyboard Maestro action dicts, recursing into the
+/// branches of IfThenElse.
+fn expand_actions(actions: &[serde_json::Value]) -> Result<Vec<serde_json::Value>> {
+    actions.iter().map(expand_action).collect()
+}
+
+fn expand_action(action: &serde_json::Value) -> Result<serde_json::Value> {
+    use serde_json::{json, Value};
+
+    let Some(fields) = action.as_object() else {
+        bail!("action must be an object, got: {}", action);
+    };
+    let mut fields = fields.clone();
+
+    let action_type = match fields.remove("type") {
+        Some(Value::String(t)) => {
+            if !SPEC_ACTION_TYPES.contains(&t.as_str()) {
+                bail!(
+                    "unsupported action type '{}' (supported: {})",
+                    t,
+                    SPEC_ACTION_TYPES.join(", ")
+                );
+            }
+            t
+        }
+        Some(other) => bail!("action \"type\" must be a string, got: {}", other),
+        // Raw Keyboard Maestro action, e.g. from `km inspect`
+        None => match fields.get("MacroActionType").and_then(|t| t.as_str()) {
+            Some(t) => t.to_string(),
+            None => bail!("action needs \"type\" or \"MacroActionType\": {}", action),
+        },
+    };
+
+    let mut expanded = match action_type.as_str() {
+        "IfThenElse" => {
+            if !fields.contains_key("Conditions") {
+                bail!("IfThenElse action needs \"Conditions\"");
+            }
+            json!({
+                "TimeOutAbortsMacro": true,
+                "ThenActions": [],
+                "ElseActions": [],
+            })
+        }
+        "ManipulateWindow" => json!({
+            "Action": "SelectWindow",
+            "Targeting": "WindowNameContaining",
+            "TargetingType": "Specific",
+        }),
+        "ExecuteShellScript" => json!({
+            "DisplayKind": "Window",
+            "HonourFailureSettings": true,
+            "IncludeStdErr": false,
+            "Path": "",
+            "Source": "Nothing",
+            "Text": "",
+            "TimeOutAbortsMacro": true,
+            "TrimResults": true,
+            "TrimResultsNew": true,
+            "UseText": true,
+        }),
+        _ => json!({}),
+    };
+
+    // `"app": "Zed"` is shorthand for the window's target application
+    if let Some(app) = fields.remove("app") {
+        let Some(app) = app.as_str() else {
+            bail!("\"app\" must be a string");
+        };
+        fields.insert("TargetApplication".to_string(), app_dict(app));
+    }
+
+    let map = expanded.as_object_mut().expect("defaults are objects");
+    map.insert("MacroActionType".to_string(), Value::String(action_type));
+    for (key, value) in fields {
+        let value = match (key.as_str(), value) {
+            ("ThenActions" | "ElseActions" | "Actions", Value::Array(nested)) => {
+                Value::Array(expand_actions(&nested)?)
+            }
+            (_, value) => value,
+        };
+        map.insert(key, value);
+    }
+
+    Ok(expanded)
+}
+
+fn app_dict(app: &str) -> serde_json::Value {
+    serde_json::json!({
+        "BundleIdentifier": get_bundle_identifier(app).unwrap_or_default(),
+        "Name": app,
+        "NewFile": format!("/Applications/{}.app", app),
+    })
+}
+
+/// Wrap actions in the macro-group plist that Keyboard Maestro imports.
+fn generate_macro_plist(name: &str, actions: Vec<serde_json::Value>) -> String {
+    let group = serde_json::json!([{
+        "Macros": [{
+            "Activate": "Normal",
+            "Name": name,
+            "Triggers": [],
+            "UID": uuid::Uuid::new_v4().to_string().to_uppercase(),
+            "Actions": actions,
+        }],
+        "Name": "Global Macro Group",
+    }]);
+
+    let mut plist = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#,
+    );
+    write_plist_value(&group, 0, &mut plist);
+    plist.push_str("</plist>");
+    plist
+}
+
+fn write_plist_value(value: &serde_json::Value, depth: usize, out: &mut String) {
+    use serde_json::Value;
+
+    let indent = "\t".repeat(depth);
+    match value {
+        // plist has no null; an empty string is what KM uses for "unset"
+        Value::Null => out.push_str(&format!("{indent}<string></string>\n")),
+        Value::Bool(true) => out.push_str(&format!("{indent}<true/>\n")),
+        Value::Bool(false) => out.push_str(&format!("{indent}<false/>\n")),
+        Value::Number(n) if n.is_f64() => out.push_str(&format!("{indent}<real>{n}</real>\n")),
+        Value::Number(n) => out.push_str(&format!("{indent}<integer>{n}</integer>\n")),
+        Value::String(s) => out.push_str(&format!("{indent}<string>{}</string>\n", escape_xml(s))),
+        Value::Array(items) if items.is_empty() => out.push_str(&format!("{indent}<array/>\n")),
+        Value::Array(items) => {
+            out.push_str(&format!("{indent}<array>\n"));
+            for item in items {
+                write_plist_value(item, depth + 1, out);
+            }
+            out.push_str(&format!("{indent}</array>\n"));
+        }
+        Value::Object(map) => {
+            out.push_str(&format!("{indent}<dict>\n"));
+            for (key, item) in map {
+                out.push_str(&format!("{indent}\t<key>{}</key>\n", escape_xml(key)));
+                write_plist_value(item, depth + 1, out);
+            }
+            out.push_str(&format!("{indent}</dict>\n"));
+        }
+    }
+}
+
 fn import_macro_plist(plist: &str) -> Result<()> {
     let temp_path = "/tmp/km_macro_import.kmmacros";
     std::fs::write(temp_path, plist).context("failed to write temp plist")?;
@@ -372,7 +1106,187 @@ fn escape_xml(s: &str) -> String {
         .replace('\'', "&apos;")
 }
 
-fn list_macros() -> Result<()> {
+fn list_macros(pattern: Option<&str>, group: Option<&str>, json: bool) -> Result<()> {
+    let stdout = fetch_hotkeys_plist()?;
+
+    // Parse plist and print macro names
+    let Some(macros) = parse_macro_names(&stdout) else {
+        if pattern.is_some() || group.is_some() || json {
+            bail!("could not parse macro list from Keyboard Maestro output");
+        }
+        print!("{}", stdout);
+        return Ok(());
+    };
+    let macros = filter_macros(macros, pattern, group);
+
+    if json {
+        let list: Vec<serde_json::Value> = macros
+            .iter()
+            .map(|(name, category)| serde_json::json!({ "name": name, "group": category }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&list)?);
+    } else {
+        for (name, category) in macros {
+            println!("{}\t{}", name, category);
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep macros in `group` whose name or group matches `pattern`. Substring
+/// hits on the name come first, then group hits, then looser subsequence
+/// matches; order is otherwise kept.
+fn filter_macros(
+    macros: Vec<(String, String)>,
+    pattern: Option<&str>,
+    group: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut macros: Vec<(String, String)> = match group {
+        Some(group) => macros
+            .into_iter()
+            .filter(|(_, category)| category.eq_ignore_ascii_case(group))
+            .collect(),
+        None => macros,
+    };
+
+    let Some(pattern) = pattern.map(str::to_lowercase).filter(|p| !p.is_empty()) else {
+        return macros;
+    };
+    let rank = |name: &str, category: &str| {
+        let name = name.to_lowercase();
+        let category = category.to_lowercase();
+        if name.contains(&pattern) {
+            Some(0)
+        } else if category.contains(&pattern) {
+            Some(1)
+        } else if is_subsequence(&pattern, &name) {
+            Some(2)
+        } else {
+            None
+        }
+    };
+
+    macros.retain(|(name, category)| rank(name, category).is_some());
+    macros.sort_by_key(|(name, category)| rank(name, category));
+    macros
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|n| chars.any(|h| h == n))
+}
+
+const ENGINE_ID: &str = "com.stairways.keyboardmaestro.engine";
+
+#[derive(Debug, PartialEq)]
+enum EngineStatus {
+    Running,
+    Stopped,
+    NotInstalled,
+}
+
+fn engine_status() -> Result<EngineStatus> {
+    let script = format!(
+        r#"
+try
+    get application id "{ENGINE_ID}"
+on error
+    return "NOT_INSTALLED"
+end try
+
+if application id "{ENGINE_ID}" is running then
+    return "RUNNING"
+end if
+return "STOPPED"
+"#
+    );
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(&script)
+        .output()
+        .context("failed to run osascript")?;
+
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "RUNNING" => Ok(EngineStatus::Running),
+        "STOPPED" => Ok(EngineStatus::Stopped),
+        "NOT_INSTALLED" => Ok(EngineStatus::NotInstalled),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("could not check for Keyboard Maestro: {}", stderr.trim());
+        }
+    }
+}
+
+/// Make sure Keyboard Maestro is installed and its Engine running before
+/// commands that talk to it, instead of failing with an osascript error.
+/// `run` offers to launch the Engine when asked from a terminal.
+fn preflight(command: &Commands) -> Result<()> {
+    let needs_engine = match command {
+        Commands::CreateOpen { dry_run, .. } | Commands::CreateFrom { dry_run, .. } => !dry_run,
+        // Skips its missing-macro check when the Engine is unreachable
+        Commands::CheckGoku => false,
+        _ => true,
+    };
+    if !needs_engine {
+        return Ok(());
+    }
+
+    match engine_status()? {
+        EngineStatus::Running => Ok(()),
+        EngineStatus::NotInstalled => {
+            bail!("Keyboard Maestro is not installed (https://www.keyboardmaestro.com)")
+        }
+        EngineStatus::Stopped => {
+            if matches!(command, Commands::Run { .. }) && confirm_launch_engine()? {
+                return launch_engine();
+            }
+            bail!("Keyboard Maestro Engine is not running (start it with: open -b {ENGINE_ID})")
+        }
+    }
+}
+
+fn confirm_launch_engine() -> Result<bool> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("Keyboard Maestro Engine is not running. Launch it? [Y/n] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(answer.is_empty() || answer.eq_ignore_ascii_case("y"))
+}
+
+fn launch_engine() -> Result<()> {
+    let status = Command::new("open")
+        .args(["-b", ENGINE_ID])
+        .status()
+        .context("failed to run open")?;
+    if !status.success() {
+        bail!("failed to launch Keyboard Maestro Engine");
+    }
+
+    // The Engine takes a moment before it accepts AppleScript
+    for _ in 0..20 {
+        if engine_status()? == EngineStatus::Running {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+    bail!("Keyboard Maestro Engine did not start within 5 seconds")
+}
+
+/// All macro names (with their group) known to the Keyboard Maestro engine.
+fn fetch_macro_names() -> Result<Vec<(String, String)>> {
+    let stdout = fetch_hotkeys_plist()?;
+    parse_macro_names(&stdout).context("no macros found in Keyboard Maestro output")
+}
+
+fn fetch_hotkeys_plist() -> Result<String> {
     let script = r#"
 try
     get application id "com.stairways.keyboardmaestro.engine"
@@ -396,18 +1310,7 @@ end tell
         bail!("osascript failed: {}", stderr);
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    // Parse plist and print macro names
-    if let Some(macros) = parse_macro_names(&stdout) {
-        for (name, category) in macros {
-            println!("{}\t{}", name, category);
-        }
-    } else {
-        print!("{}", stdout);
-    }
-
-    Ok(())
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 fn parse_macro_names(plist_str: &str) -> Option<Vec<(String, String)>> {
@@ -455,11 +1358,59 @@ fn extract_string_value(line: &str) -> Option<String> {
     Some(line[start..end].to_string())
 }
 
-fn run_macro(name: &str) -> Result<()> {
+fn run_macro(name: &str, param: Option<&str>) -> Result<()> {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = match param {
+        Some(param) => format!(
+            r#"tell application "Keyboard Maestro Engine" to do script "{}" with parameter "{}""#,
+            escape(name),
+            escape(param)
+        ),
+        None => format!(
+            r#"tell application "Keyboard Maestro Engine" to do script "{}""#,
+            escape(name)
+        ),
+    };
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(&script)
+        .output()
+        .context("failed to run osascript")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to run macro: {}", stderr);
+    }
+
+    println!("Ran macro: {}", name);
+    Ok(())
+}
+
+/// Set (or flip, when neither `on` nor `off`) whether a macro or group is
+/// enabled, and report the state it ended up in.
+fn toggle_enabled(kind: KmObject, name: &str, on: bool, off: bool) -> Result<()> {
+    if !object_exists(kind, name)? {
+        bail!("{} '{}' not found in Keyboard Maestro", kind.class(), name);
+    }
+
     let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+    let new_state = if on {
+        "true"
+    } else if off {
+        "false"
+    } else {
+        "not (enabled of m)"
+    };
     let script = format!(
-        r#"tell application "Keyboard Maestro Engine" to do script "{}""#,
-        escaped
+        r#"tell application "Keyboard Maestro"
+    set m to first {} whose name is "{}"
+    set enabled of m to {}
+    return enabled of m
+end tell"#,
+        kind.class(),
+        escaped,
+        new_state
     );
 
     let output = Command::new("osascript")
@@ -470,10 +1421,16 @@ fn run_macro(name: &str) -> Result<()> {
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("failed to run macro: {}", stderr);
+        bail!("failed to toggle {}: {}", kind.class(), stderr.trim());
     }
 
-    println!("Ran macro: {}", name);
+    let enabled = String::from_utf8_lossy(&output.stdout).trim() == "true";
+    println!(
+        "{} {}: {}",
+        if enabled { "enabled" } else { "disabled" },
+        kind.class(),
+        name
+    );
     Ok(())
 }
 
@@ -540,3 +1497,147 @@ fn parse_macro_actions(xml: &str) -> Result<Vec<serde_json::Value>> {
 
     Ok(actions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_filter_ranks_name_then_group_then_fuzzy() {
+        let macros = vec![
+            ("open safari".to_string(), "Global".to_string()),
+            ("zed: focus".to_string(), "Zed".to_string()),
+            ("zed: new file".to_string(), "Zed".to_string()),
+            ("focus mode".to_string(), "Global".to_string()),
+        ];
+
+        let names = |list: Vec<(String, String)>| -> Vec<String> {
+            list.into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(
+            names(filter_macros(macros.clone(), Some("FOCUS"), None)),
+            vec!["zed: focus", "focus mode"]
+        );
+        assert_eq!(
+            names(filter_macros(macros.clone(), Some("zed"), None)),
+            vec!["zed: focus", "zed: new file"]
+        );
+        assert_eq!(
+            names(filter_macros(macros.clone(), Some("zfoc"), None)),
+            vec!["zed: focus"]
+        );
+        assert_eq!(
+            names(filter_macros(macros, Some("o"), Some("global"))),
+            vec!["open safari", "focus mode"]
+        );
+    }
+
+    #[test]
+    fn open_macro_plist_escapes_ampersand() {
+        let plist = generate_open_macro_plist("build & run", "Zed", "~/a&b", "a&b", None);
+
+        assert!(plist.contains("<string>build &amp; run</string>"));
+        assert!(plist.contains("<string>a&amp;b</string>"));
+        assert!(plist.contains("open -a /Applications/Zed.app ~/a&amp;b"));
+        assert!(!plist.contains("build & run"));
+        assert!(plist.contains("<key>Triggers</key>\n\t\t\t\t<array/>\n"));
+    }
+
+    #[test]
+    fn hotkey_parses_to_km_key_code_and_carbon_modifiers() {
+        let hotkey = Hotkey::parse("Cmd+Opt+O").unwrap();
+        assert_eq!(hotkey, Hotkey { key_code: 31, modifiers: 256 | 2048 });
+        assert_eq!(hotkey.to_string(), "Opt+Cmd+O");
+        assert_eq!(Hotkey::parse("ctrl + shift + esc").unwrap().key_code, 53);
+        assert_eq!(Hotkey::parse("F5").unwrap().modifiers, 0);
+
+        assert!(Hotkey::parse("Cmd+Opt+Nope").is_err());
+        assert!(Hotkey::parse("Hyper+O").is_err());
+        assert!(Hotkey::parse("Cmd+").is_err());
+        assert!(Hotkey::parse("O").is_err());
+
+        let plist = generate_open_macro_plist("focus", "Zed", "~/f", "f", Some(&hotkey));
+        assert!(plist.contains("<key>MacroTriggerType</key>\n\t\t\t\t\t\t<string>HotKey</string>"));
+        assert!(plist.contains("<key>KeyCode</key>\n\t\t\t\t\t\t<integer>31</integer>"));
+        assert!(plist.contains("<key>Modifiers</key>\n\t\t\t\t\t\t<integer>2304</integer>"));
+    }
+
+    #[test]
+    fn spec_actions_expand_with_defaults_and_raw_passthrough() {
+        let spec: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {"type": "IfThenElse",
+                 "Conditions": {"ConditionList": [], "ConditionListMatch": "All"},
+                 "ElseActions": [{"type": "ExecuteShellScript", "Text": "echo a&b"}]},
+                {"MacroActionType": "Pause", "Time": "0.5"}
+            ]"#,
+        )
+        .unwrap();
+        let actions = expand_actions(&spec).unwrap();
+
+        assert_eq!(actions[0]["MacroActionType"], "IfThenElse");
+        assert_eq!(actions[0]["ThenActions"], serde_json::json!([]));
+        let shell = &actions[0]["ElseActions"][0];
+        assert_eq!(shell["MacroActionType"], "ExecuteShellScript");
+        assert_eq!(shell["UseText"], true);
+        assert_eq!(actions[1], spec[1]);
+
+        let plist = generate_macro_plist("spec", actions);
+        assert!(plist.contains("<string>echo a&amp;b</string>"));
+        assert!(plist.contains("<key>ThenActions</key>\n\t\t\t\t\t\t<array/>"));
+
+        let bad = serde_json::json!([{"type": "Pause"}]);
+        assert!(expand_actions(bad.as_array().unwrap()).is_err());
+    }
+
+    #[test]
+    fn goku_bindings_skip_comments_and_stay_in_section() {
+        let edn = r#"{:main [
+    {:des "okey (apps)"
+     :rules [:o-mode
+                 [:z [:km "zed: focus"]]
+                 ;[:z [:km "old"]]
+                 [:z :!Ca]
+             ]}
+    {:des "vkey (misc)"
+     :rules [:v-mode
+                 [:z [:km "other"] ["x" 1]]
+             ]}
+]}"#;
+        let bindings = goku_bindings(edn).unwrap();
+
+        let keys: Vec<(&str, &str, usize)> = bindings
+            .iter()
+            .map(|b| (b.layer.as_str(), b.key.as_str(), b.line))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("okey (apps)", "z", 4),
+                ("okey (apps)", "z", 6),
+                ("vkey (misc)", "z", 10),
+            ]
+        );
+        assert_eq!(bindings[0].km_macro.as_deref(), Some("zed: focus"));
+        assert_eq!(bindings[1].km_macro, None);
+        assert_eq!(bindings[2].km_macro.as_deref(), Some("other"));
+
+        let rule = goku_rule(edn, "o", "z").unwrap().unwrap();
+        assert_eq!((rule.line, rule.km_macro.as_deref()), (4, Some("zed: focus")));
+        assert_eq!(goku_rule(edn, "v", "z").unwrap().unwrap().km_macro.as_deref(), Some("other"));
+        assert_eq!(goku_rule(edn, "v", "o").unwrap(), None);
+        assert_eq!(goku_rule(edn, "x", "z").unwrap(), None);
+    }
+
+    #[test]
+    fn edn_balance_check_ignores_strings_and_reports_lines() {
+        check_edn_balanced(r#"{:main [{:des "x" :rules [[:a [:km "a ] \" }"]]]}]}"#).unwrap();
+
+        let err = check_edn_balanced("{:main [\n  {:des \"x\"\n]}").unwrap_err();
+        assert_eq!(err.to_string(), "line 3: ']' closes '{' opened on line 2");
+        let err = check_edn_balanced("{:main [\n  [:a :b]\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: '[' is never closed");
+        let err = check_edn_balanced("{}\n]").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unmatched ']'");
+    }
+}
@@ -12,7 +12,7 @@ use crossterm::{
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Layout},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     widgets::{Block, BorderType, Borders, Row, Table},
     Terminal,
 };
@@ -28,22 +28,36 @@ fn try_main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Shortcuts { all } => list_shortcuts(all),
-        Commands::Apps { limit } => list_apps(limit),
+        Commands::Shortcuts { all, us_layout } => list_shortcuts(all, us_layout),
+        Commands::Apps {
+            limit,
+            watch,
+            tui,
+            interval_secs,
+        } => {
+            if tui {
+                run_apps_tui(limit, interval_secs)
+            } else if watch {
+                watch_apps(limit, interval_secs)
+            } else {
+                list_apps(limit)
+            }
+        }
         Commands::ClipImg => clip_img(),
         Commands::Energy {
             limit,
             kill,
             force,
+            metric,
             tui,
         } => {
             if tui {
                 if !kill.is_empty() || force {
                     anyhow::bail!("--tui does not support --kill or --force");
                 }
-                run_energy_tui(limit)
+                run_energy_tui(limit, metric)
             } else {
-                list_energy(limit, &kill, force)
+                list_energy(limit, &kill, force, metric)
             }
         }
         Commands::Cpu {
@@ -52,6 +66,7 @@ fn try_main() -> Result<()> {
             interval_secs,
             threshold,
             show_system,
+            raw,
             tui,
         } => {
             if tui {
@@ -61,6 +76,7 @@ fn try_main() -> Result<()> {
                     interval_secs,
                     threshold,
                     show_system,
+                    raw,
                 )
             } else {
                 list_cpu(
@@ -69,9 +85,44 @@ fn try_main() -> Result<()> {
                     interval_secs,
                     threshold,
                     show_system,
+                    raw,
                 )
             }
         }
+        Commands::Frontmost { json } => frontmost(json),
+        Commands::Displays { json } => displays(json),
+        Commands::Notify {
+            title,
+            body,
+            sound,
+            list,
+            limit,
+        } => notify(title, body, sound, list, limit),
+        Commands::Volume { level } => volume(level),
+        Commands::Brightness { level } => brightness(level),
+        Commands::Uninstall { app, dry_run, json } => uninstall(&app, dry_run, json),
+        Commands::Wifi { command, json } => match command {
+            None => wifi_status(json),
+            Some(WifiCommands::Scan) => wifi_scan(json),
+        },
+        Commands::Dock(cmd) => match cmd {
+            DockCommands::List { json } => dock_list(json),
+            DockCommands::Add { app, position, yes } => dock_add(&app, position, yes),
+            DockCommands::Remove { app, yes } => dock_remove(&app, yes),
+            DockCommands::Move { app, position, yes } => dock_move(&app, position, yes),
+        },
+        Commands::Plist(cmd) => match cmd {
+            PlistCommands::Read { path, key } => plist_read(&path, key.as_deref()),
+        },
+        Commands::Awake {
+            command,
+            duration,
+            display,
+            while_cmd,
+        } => match command {
+            Some(AwakeCommands::Off) => awake_off(),
+            None => awake(duration, display, while_cmd.as_deref()),
+        },
         Commands::Warp(cmd) => match cmd {
             WarpCommands::Title => warp_title(),
         },
@@ -92,12 +143,24 @@ enum Commands {
         /// Show all shortcuts including disabled ones
         #[arg(long, short)]
         all: bool,
+        /// Name keys as on a US QWERTY keyboard instead of the current layout
+        #[arg(long)]
+        us_layout: bool,
     },
     /// List running apps sorted by RAM usage
     Apps {
         /// Limit number of apps shown (shows all if not specified)
         #[arg(long, short)]
         limit: Option<usize>,
+        /// Keep re-sampling and redraw, marking apps whose memory grew
+        #[arg(long)]
+        watch: bool,
+        /// Show a live-updating TUI (implies --watch)
+        #[arg(long)]
+        tui: bool,
+        /// Seconds between samples with --watch/--tui (default: 2)
+        #[arg(long, default_value_t = 2)]
+        interval_secs: u64,
     },
     /// Save clipboard image to file and put file path in clipboard.
     ///
@@ -117,6 +180,9 @@ enum Commands {
         /// Use SIGKILL instead of SIGTERM
         #[arg(long)]
         force: bool,
+        /// What to rank by: CPU % or macOS "energy impact"
+        #[arg(long, value_enum, default_value_t = EnergyMetric::Cpu)]
+        metric: EnergyMetric,
         /// Show a live-updating TUI
         #[arg(long)]
         tui: bool,
@@ -138,13 +204,176 @@ enum Commands {
         /// Include system processes in output
         #[arg(long)]
         show_system: bool,
+        /// Show raw `top` process names instead of resolving owning apps
+        #[arg(long)]
+        raw: bool,
         /// Show a live-updating TUI
         #[arg(long)]
         tui: bool,
     },
+    /// Print the frontmost app (name, bundle id) and its window title
+    Frontmost {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List connected displays with resolution, scaling and refresh rate
+    Displays {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Send a notification, or list recent ones from Notification Center
+    ///
+    /// --list reads usernoted's database, which needs Full Disk Access.
+    Notify {
+        /// Notification title
+        #[arg(required_unless_present = "list")]
+        title: Option<String>,
+        /// Notification body
+        body: Option<String>,
+        /// Play a sound (e.g. Glass, Ping, Submarine)
+        #[arg(long, conflicts_with = "list")]
+        sound: Option<String>,
+        /// Print recent notifications as JSON instead of sending one
+        #[arg(long, conflicts_with_all = ["title", "body"])]
+        list: bool,
+        /// Number of notifications to list (default: 20)
+        #[arg(long, short, requires = "list")]
+        limit: Option<usize>,
+    },
+    /// Get or set output volume (0-100)
+    Volume {
+        /// New volume level; prints the current level if omitted
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        level: Option<u8>,
+    },
+    /// Get or set display brightness (0.0-1.0)
+    ///
+    /// Requires the `brightness` tool (brew install brightness).
+    Brightness {
+        /// New brightness level; prints the current level if omitted
+        level: Option<f64>,
+    },
+    /// Move an app and its support files (caches, preferences, containers...)
+    /// to the Trash
+    ///
+    /// Accepts an app name, a path to a .app bundle, or a bundle id (for
+    /// leftovers of apps that are already gone).
+    Uninstall {
+        /// App name (e.g. Slack), .app path or bundle id
+        app: String,
+        /// Only list what would be removed
+        #[arg(long)]
+        dry_run: bool,
+        /// Output the file list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the current Wi-Fi network (SSID, signal, channel)
+    ///
+    /// Uses `airport` where available, otherwise `wdutil` (root only) or
+    /// `system_profiler`. Recent macOS hides SSIDs unless the terminal has
+    /// Location Services access.
+    Wifi {
+        #[command(subcommand)]
+        command: Option<WifiCommands>,
+        /// Output as JSON
+        #[arg(long, global = true)]
+        json: bool,
+    },
+    /// List and rearrange apps pinned in the Dock
+    #[command(subcommand)]
+    Dock(DockCommands),
     /// Warp terminal utilities
     #[command(subcommand)]
     Warp(WarpCommands),
+    /// Read property lists as JSON
+    #[command(subcommand)]
+    Plist(PlistCommands),
+    /// Keep the Mac from sleeping, for a while or until Ctrl+C
+    ///
+    /// Holds a `caffeinate` assertion tied to this process, so it's released
+    /// however the command ends.
+    Awake {
+        #[command(subcommand)]
+        command: Option<AwakeCommands>,
+        /// How long to stay awake (e.g. 30m, 2h); until Ctrl+C if omitted
+        #[arg(long, short, value_parser = parse_duration, conflicts_with = "while_cmd")]
+        duration: Option<std::time::Duration>,
+        /// Keep the display on too, not just the system
+        #[arg(long)]
+        display: bool,
+        /// Stay awake only while this shell command runs
+        #[arg(long = "while", value_name = "CMD")]
+        while_cmd: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AwakeCommands {
+    /// Stop a running `macos awake`, e.g. from another terminal
+    Off,
+}
+
+#[derive(Subcommand)]
+enum DockCommands {
+    /// List pinned apps (and spacers) in Dock order
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Pin an app to the Dock
+    Add {
+        /// App name (e.g. Slack) or .app path
+        app: String,
+        /// Position to insert at (1 = first); appends if omitted
+        #[arg(long)]
+        position: Option<usize>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Unpin an app from the Dock
+    Remove {
+        /// App name, bundle id or .app path as shown by `dock list`
+        app: String,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Move a pinned app to another position
+    Move {
+        /// App name, bundle id or .app path as shown by `dock list`
+        app: String,
+        /// New position (1 = first)
+        position: usize,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WifiCommands {
+    /// List nearby networks, strongest first
+    Scan,
+}
+
+#[derive(Subcommand)]
+enum PlistCommands {
+    /// Print a plist (XML or binary) as JSON
+    ///
+    /// Dates become ISO 8601 strings and data becomes base64.
+    Read {
+        /// Path to the plist (e.g. ~/Library/Preferences/com.apple.dock.plist)
+        path: String,
+        /// Print only this value; dotted path, with array indexes as numbers
+        /// (e.g. persistent-apps.0.tile-data.file-label)
+        #[arg(long, short)]
+        key: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -153,11 +382,18 @@ enum WarpCommands {
     Title,
 }
 
-fn list_shortcuts(show_all: bool) -> Result<()> {
+fn list_shortcuts(show_all: bool, us_layout: bool) -> Result<()> {
     let mut shortcuts: BTreeMap<String, Vec<ShortcutInfo>> = BTreeMap::new();
 
+    // Symbolic hotkeys store key codes, which only mean a letter for a given layout
+    let layout = if us_layout {
+        BTreeMap::new()
+    } else {
+        current_layout_keys().unwrap_or_default()
+    };
+
     // System symbolic hotkeys
-    if let Ok(system) = read_symbolic_hotkeys() {
+    if let Ok(system) = read_symbolic_hotkeys(&layout) {
         for s in system {
             if show_all || s.enabled {
                 shortcuts
@@ -217,27 +453,17 @@ struct ShortcutInfo {
     enabled: bool,
 }
 
-fn read_symbolic_hotkeys() -> Result<Vec<ShortcutInfo>> {
-    let plist_path = expand_tilde("~/Library/Preferences/com.apple.symbolichotkeys.plist");
-
-    let output = Command::new("plutil")
-        .args(["-convert", "xml1", "-o", "-", &plist_path])
-        .output()
-        .context("failed to run plutil")?;
-
-    if !output.status.success() {
-        anyhow::bail!("plutil failed to convert plist");
-    }
-
-    let value: plist::Value =
-        plist::from_bytes(&output.stdout).context("failed to parse symbolic hotkeys plist")?;
+fn read_symbolic_hotkeys(layout: &BTreeMap<u16, String>) -> Result<Vec<ShortcutInfo>> {
+    let value = read_plist(&expand_tilde(
+        "~/Library/Preferences/com.apple.symbolichotkeys.plist",
+    ))?;
 
     let mut results = Vec::new();
 
     if let Some(dict) = value.as_dictionary() {
         if let Some(hotkeys) = dict.get("AppleSymbolicHotKeys").and_then(|v| v.as_dictionary()) {
             for (key, val) in hotkeys {
-                if let Some(info) = parse_symbolic_hotkey(key, val) {
+                if let Some(info) = parse_symbolic_hotkey(key, val, layout) {
                     results.push(info);
                 }
             }
@@ -247,7 +473,11 @@ fn read_symbolic_hotkeys() -> Result<Vec<ShortcutInfo>> {
     Ok(results)
 }
 
-fn parse_symbolic_hotkey(id: &str, value: &plist::Value) -> Option<ShortcutInfo> {
+fn parse_symbolic_hotkey(
+    id: &str,
+    value: &plist::Value,
+    layout: &BTreeMap<u16, String>,
+) -> Option<ShortcutInfo> {
     let dict = value.as_dictionary()?;
 
     let enabled = dict
@@ -265,7 +495,7 @@ fn parse_symbolic_hotkey(id: &str, value: &plist::Value) -> Option<ShortcutInfo>
     let key_code = params_array.get(1)?.as_signed_integer()? as u16;
     let modifiers = params_array.get(2)?.as_signed_integer()? as u32;
 
-    let shortcut = format_shortcut(modifiers, key_code);
+    let shortcut = format_shortcut(modifiers, key_code, layout);
     let action = symbolic_hotkey_name(id);
 
     Some(ShortcutInfo {
@@ -471,7 +701,9 @@ fn read_services_shortcuts() -> Result<Vec<ShortcutInfo>> {
     Ok(results)
 }
 
-fn format_shortcut(modifiers: u32, key_code: u16) -> String {
+/// `layout` maps key codes to what the current keyboard layout types (see
+/// `current_layout_keys`); codes it doesn't cover use the US names.
+fn format_shortcut(modifiers: u32, key_code: u16, layout: &BTreeMap<u16, String>) -> String {
     let mut parts = Vec::new();
 
     // Carbon modifier flags
@@ -488,7 +720,10 @@ fn format_shortcut(modifiers: u32, key_code: u16) -> String {
         parts.push("Cmd");
     }
 
-    let key = keycode_to_string(key_code);
+    let key = layout
+        .get(&key_code)
+        .cloned()
+        .unwrap_or_else(|| keycode_to_string(key_code));
     parts.push(&key);
 
     parts.join("+")
@@ -579,6 +814,119 @@ fn keycode_to_string(code: u16) -> String {
     .to_string()
 }
 
+/// Whether a key code is a character key (letter, digit, punctuation, or
+/// one the US table doesn't know such as the ISO § key), as opposed to a
+/// named key like Return or F5 that reads the same on every layout.
+fn is_character_key(code: u16) -> bool {
+    let name = keycode_to_string(code);
+    name.chars().count() == 1 || name.starts_with("Key")
+}
+
+/// Uppercased characters the current keyboard layout types for each
+/// character key, so shortcuts read right on Dvorak, Colemak, AZERTY...
+/// None when the layout can't be read (e.g. an input method without a
+/// Unicode key layout), in which case the US QWERTY names are used.
+fn current_layout_keys() -> Option<BTreeMap<u16, String>> {
+    let codes = (0..128).filter(|&c| is_character_key(c));
+    let keys: BTreeMap<u16, String> = key_layout::translate(codes)?
+        .into_iter()
+        .filter(|(_, s)| !s.is_empty() && !s.chars().any(char::is_control))
+        .map(|(code, s)| (code, s.to_uppercase()))
+        .collect();
+    (!keys.is_empty()).then_some(keys)
+}
+
+/// `UCKeyTranslate` against the current input source's key layout data.
+#[cfg(target_os = "macos")]
+mod key_layout {
+    use std::ffi::c_void;
+    use std::os::raw::c_ulong;
+
+    type CFTypeRef = *const c_void;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISPropertyUnicodeKeyLayoutData: CFTypeRef;
+        fn TISCopyCurrentKeyboardLayoutInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+        fn LMGetKbdType() -> u8;
+        fn UCKeyTranslate(
+            layout: *const c_void,
+            virtual_key_code: u16,
+            key_action: u16,
+            modifier_key_state: u32,
+            keyboard_type: u32,
+            key_translate_options: u32,
+            dead_key_state: *mut u32,
+            max_string_length: c_ulong,
+            actual_string_length: *mut c_ulong,
+            unicode_string: *mut u16,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFDataGetBytePtr(data: CFTypeRef) -> *const u8;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    const KEY_ACTION_DISPLAY: u16 = 3;
+    const TRANSLATE_NO_DEAD_KEYS: u32 = 1 << 0;
+
+    pub fn translate(codes: impl Iterator<Item = u16>) -> Option<Vec<(u16, String)>> {
+        // SAFETY: the layout data belongs to `source`, which is only
+        // released once every key has been translated.
+        unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+            let layout = if data.is_null() {
+                std::ptr::null()
+            } else {
+                CFDataGetBytePtr(data)
+            };
+            if layout.is_null() {
+                CFRelease(source);
+                return None;
+            }
+
+            let keyboard_type = LMGetKbdType() as u32;
+            let mut keys = Vec::new();
+            for code in codes {
+                let mut dead_key_state = 0u32;
+                let mut buf = [0u16; 4];
+                let mut len: c_ulong = 0;
+                let status = UCKeyTranslate(
+                    layout.cast(),
+                    code,
+                    KEY_ACTION_DISPLAY,
+                    0,
+                    keyboard_type,
+                    TRANSLATE_NO_DEAD_KEYS,
+                    &mut dead_key_state,
+                    buf.len() as c_ulong,
+                    &mut len,
+                    buf.as_mut_ptr(),
+                );
+                if status == 0 {
+                    keys.push((code, String::from_utf16_lossy(&buf[..len as usize])));
+                }
+            }
+            CFRelease(source);
+            Some(keys)
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod key_layout {
+    pub fn translate(_codes: impl Iterator<Item = u16>) -> Option<Vec<(u16, String)>> {
+        None
+    }
+}
+
 fn symbolic_hotkey_name(id: &str) -> String {
     match id {
         "7" => "Move focus to menu bar",
@@ -646,6 +994,124 @@ fn expand_tilde(path: &str) -> String {
     path.to_string()
 }
 
+/// Read a plist in any format by having `plutil` convert it to XML first.
+fn read_plist(path: &str) -> Result<plist::Value> {
+    if !Path::new(path).exists() {
+        anyhow::bail!("{} does not exist", path);
+    }
+
+    let output = Command::new("plutil")
+        .args(["-convert", "xml1", "-o", "-", path])
+        .output()
+        .context("failed to run plutil")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("plutil failed to convert {}: {}", path, stderr.trim());
+    }
+
+    plist::from_bytes(&output.stdout).with_context(|| format!("failed to parse {}", path))
+}
+
+fn plist_read(path: &str, key: Option<&str>) -> Result<()> {
+    let path = expand_tilde(path);
+    let value = read_plist(&path)?;
+    let value = match key {
+        Some(key) => plist_lookup(&value, key)?,
+        None => &value,
+    };
+    println!("{}", serde_json::to_string_pretty(&plist_to_json(value))?);
+    Ok(())
+}
+
+/// Follow a dotted key path (`a.b.0.c`) through dictionaries and arrays.
+fn plist_lookup<'a>(value: &'a plist::Value, key_path: &str) -> Result<&'a plist::Value> {
+    let mut current = value;
+    let mut seen: Vec<&str> = Vec::new();
+
+    for segment in key_path.split('.') {
+        let parent = if seen.is_empty() {
+            "the top level".to_string()
+        } else {
+            format!("'{}'", seen.join("."))
+        };
+        current = match current {
+            plist::Value::Dictionary(dict) => match dict.get(segment) {
+                Some(next) => next,
+                None => {
+                    let mut keys: Vec<&str> = dict.keys().map(String::as_str).collect();
+                    keys.sort_unstable();
+                    anyhow::bail!(
+                        "key '{}' not found in {} (keys: {})",
+                        segment,
+                        parent,
+                        keys.join(", ")
+                    );
+                }
+            },
+            plist::Value::Array(items) => {
+                let index: usize = segment.parse().with_context(|| {
+                    format!("{} is an array; '{}' is not an index", parent, segment)
+                })?;
+                items.get(index).with_context(|| {
+                    format!("index {} out of range in {} ({} items)", index, parent, items.len())
+                })?
+            }
+            _ => anyhow::bail!("{} has no keys, so '{}' can't be looked up", parent, segment),
+        };
+        seen.push(segment);
+    }
+
+    Ok(current)
+}
+
+/// JSON has no date or binary types, so those become ISO 8601 and base64 strings.
+fn plist_to_json(value: &plist::Value) -> serde_json::Value {
+    use serde_json::Value as Json;
+
+    match value {
+        plist::Value::Dictionary(dict) => Json::Object(
+            dict.iter()
+                .map(|(key, value)| (key.clone(), plist_to_json(value)))
+                .collect(),
+        ),
+        plist::Value::Array(items) => Json::Array(items.iter().map(plist_to_json).collect()),
+        plist::Value::Boolean(b) => Json::Bool(*b),
+        plist::Value::Integer(n) => match (n.as_signed(), n.as_unsigned()) {
+            (Some(n), _) => Json::from(n),
+            (None, Some(n)) => Json::from(n),
+            (None, None) => Json::Null,
+        },
+        plist::Value::Real(x) => serde_json::Number::from_f64(*x)
+            .map(Json::Number)
+            .unwrap_or(Json::Null),
+        plist::Value::String(s) => Json::String(s.clone()),
+        plist::Value::Date(date) => Json::String(date.to_xml_format()),
+        plist::Value::Data(bytes) => Json::String(base64_encode(bytes)),
+        plist::Value::Uid(uid) => Json::from(uid.get()),
+        _ => Json::Null,
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 // ============================================================================
 // Apps command
 // ============================================================================
@@ -689,6 +1155,212 @@ fn list_apps(limit: Option<usize>) -> Result<()> {
     Ok(())
 }
 
+/// Growth below this between samples is treated as noise.
+const APP_GROWTH_HIGHLIGHT_BYTES: i64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AppSort {
+    Memory,
+    Growth,
+    Name,
+}
+
+/// Sample running apps, pairing each with its memory change since the
+/// previous sample (0 for apps not seen before). Updates `prev`.
+fn sample_apps(
+    prev: &mut std::collections::HashMap<u32, u64>,
+    sort: AppSort,
+) -> Result<Vec<(AppInfo, i64)>> {
+    let apps = get_running_apps()?;
+    let mut samples: Vec<(AppInfo, i64)> = apps
+        .into_iter()
+        .map(|app| {
+            let growth = prev
+                .get(&app.pid)
+                .map_or(0, |&before| app.memory_bytes as i64 - before as i64);
+            (app, growth)
+        })
+        .collect();
+
+    prev.clear();
+    prev.extend(samples.iter().map(|(app, _)| (app.pid, app.memory_bytes)));
+
+    sort_app_samples(&mut samples, sort);
+    Ok(samples)
+}
+
+fn sort_app_samples(samples: &mut [(AppInfo, i64)], sort: AppSort) {
+    match sort {
+        AppSort::Memory => samples.sort_by_key(|(app, _)| std::cmp::Reverse(app.memory_bytes)),
+        AppSort::Growth => samples.sort_by_key(|(_, growth)| std::cmp::Reverse(*growth)),
+        AppSort::Name => samples.sort_by_key(|(app, _)| app.name.to_lowercase()),
+    }
+}
+
+fn format_growth(delta: i64) -> String {
+    match delta {
+        0 => String::new(),
+        d if d > 0 => format!("+{}", format_bytes(d as u64)),
+        d => format!("-{}", format_bytes(d.unsigned_abs())),
+    }
+}
+
+fn watch_apps(limit: Option<usize>, interval_secs: u64) -> Result<()> {
+    let mut prev = std::collections::HashMap::new();
+    let highlight = std::io::IsTerminal::is_terminal(&std::io::stdout());
+
+    loop {
+        let samples = sample_apps(&mut prev, AppSort::Memory)?;
+        let shown = limit.unwrap_or(samples.len()).min(samples.len());
+
+        // Clear screen and move the cursor home
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "Running apps by RAM usage ({}/{}, every {}s, Ctrl-C to stop):\n",
+            shown,
+            samples.len(),
+            interval_secs
+        );
+        for (app, growth) in samples.iter().take(shown) {
+            let line = format!(
+                "{:<32} {:>10} {:>10}  (pid {})",
+                app.name,
+                format_bytes(app.memory_bytes),
+                format_growth(*growth),
+                app.pid
+            );
+            if highlight && *growth >= APP_GROWTH_HIGHLIGHT_BYTES {
+                println!("\x1b[33m{}\x1b[0m", line);
+            } else {
+                println!("{}", line);
+            }
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(interval_secs.max(1)));
+    }
+}
+
+fn run_apps_tui(limit: Option<usize>, interval_secs: u64) -> Result<()> {
+    let interval = std::time::Duration::from_secs(interval_secs.max(1));
+    let mut prev = std::collections::HashMap::new();
+    let mut sort = AppSort::Memory;
+
+    enable_raw_mode().context("failed to enable raw mode")?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen).context("failed to enter alt screen")?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
+
+    struct TuiGuard;
+    impl Drop for TuiGuard {
+        fn drop(&mut self) {
+            let _ = disable_raw_mode();
+            let mut stdout = std::io::stdout();
+            let _ = execute!(stdout, LeaveAlternateScreen);
+        }
+    }
+    let _guard = TuiGuard;
+
+    let mut samples = sample_apps(&mut prev, sort).unwrap_or_default();
+    let mut last_sample = std::time::Instant::now();
+
+    loop {
+        terminal
+            .draw(|f| {
+                let area = f.size();
+                let layout = Layout::vertical([Constraint::Min(3), Constraint::Length(1)]);
+                let chunks = layout.split(area);
+
+                let rows = samples
+                    .iter()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .map(|(app, growth)| {
+                        let row = Row::new(vec![
+                            app.pid.to_string(),
+                            format_bytes(app.memory_bytes),
+                            format_growth(*growth),
+                            app.name.clone(),
+                        ]);
+                        if *growth >= APP_GROWTH_HIGHLIGHT_BYTES {
+                            row.style(Style::default().fg(Color::Yellow))
+                        } else {
+                            row
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let table = Table::new(
+                    rows,
+                    [
+                        Constraint::Length(8),
+                        Constraint::Length(10),
+                        Constraint::Length(10),
+                        Constraint::Min(10),
+                    ],
+                )
+                .header(
+                    Row::new(vec!["PID", "MEMORY", "CHANGE", "APP"])
+                        .style(Style::default().add_modifier(Modifier::BOLD)),
+                )
+                .block(
+                    Block::default()
+                        .title(format!(
+                            "Apps by {} (every {}s)",
+                            match sort {
+                                AppSort::Memory => "memory",
+                                AppSort::Growth => "growth",
+                                AppSort::Name => "name",
+                            },
+                            interval.as_secs()
+                        ))
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Plain),
+                );
+
+                f.render_widget(table, chunks[0]);
+
+                let footer = Block::default()
+                    .title("q: quit  r: refresh  m: sort by memory  g: by growth  n: by name")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain);
+                f.render_widget(footer, chunks[1]);
+            })
+            .context("failed to draw UI")?;
+
+        let wait = interval.saturating_sub(last_sample.elapsed());
+        let mut resample = wait.is_zero();
+        if !resample && event::poll(wait).context("failed to poll events")? {
+            if let Event::Key(key) = event::read().context("failed to read event")? {
+                let new_sort = match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('r') => {
+                        resample = true;
+                        None
+                    }
+                    KeyCode::Char('m') => Some(AppSort::Memory),
+                    KeyCode::Char('g') => Some(AppSort::Growth),
+                    KeyCode::Char('n') => Some(AppSort::Name),
+                    _ => None,
+                };
+                if let Some(new_sort) = new_sort {
+                    sort = new_sort;
+                    // Re-sort the current sample without touching growth
+                    sort_app_samples(&mut samples, sort);
+                }
+            }
+        }
+
+        if resample {
+            if let Ok(next) = sample_apps(&mut prev, sort) {
+                samples = next;
+            }
+            last_sample = std::time::Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
 fn get_running_apps() -> Result<Vec<AppInfo>> {
     use std::collections::HashMap;
 
@@ -713,41 +1385,12 @@ fn get_running_apps() -> Result<Vec<AppInfo>> {
 
     let lsappinfo_stdout = String::from_utf8_lossy(&lsappinfo_output.stdout);
 
-    // Parse lsappinfo output to get app name -> pid mapping
-    // Format:  5) "Warp" ASN:0x0-0xe00e:
-    //              pid = 644 type="Foreground" ...
-    // Only include apps with type="Foreground" (actual GUI apps, not helpers)
-    let mut app_pids: HashMap<String, u32> = HashMap::new();
-    let mut current_app: Option<String> = None;
-
-    for line in lsappinfo_stdout.lines() {
-        let trimmed = line.trim();
-        // App name line starts with number: 5) "Warp" ASN:...
-        if let Some(paren_pos) = trimmed.find(')') {
-            let after_paren = &trimmed[paren_pos + 1..].trim_start();
-            if after_paren.starts_with('"') {
-                if let Some(quote_end) = after_paren[1..].find('"') {
-                    current_app = Some(after_paren[1..quote_end + 1].to_string());
-                }
-            }
-        }
-        // PID line: pid = 660 type="Foreground"
-        // Only include Foreground apps (actual GUI apps with windows)
-        if trimmed.starts_with("pid =") || trimmed.starts_with("pid=") {
-            if let Some(app) = current_app.take() {
-                // Check if this is a Foreground app
-                if trimmed.contains("type=\"Foreground\"") {
-                    // Extract pid value - it's after "pid =" and before next space
-                    let pid_part = trimmed.strip_prefix("pid =").or_else(|| trimmed.strip_prefix("pid=")).unwrap_or("");
-                    if let Some(pid_str) = pid_part.trim().split_whitespace().next() {
-                        if let Ok(pid) = pid_str.parse::<u32>() {
-                            app_pids.insert(app, pid);
-                        }
-                    }
-                }
-            }
-        }
-    }
+    // Only include Foreground apps (actual GUI apps with windows, not helpers)
+    let app_pids: HashMap<String, u32> = parse_lsappinfo(&lsappinfo_stdout)
+        .into_iter()
+        .filter(|app| app.foreground)
+        .map(|app| (app.name, app.pid))
+        .collect();
 
     // Wait for ps
     let ps_output = ps_handle
@@ -785,6 +1428,151 @@ fn get_running_apps() -> Result<Vec<AppInfo>> {
     Ok(apps)
 }
 
+struct LsAppInfoEntry {
+    name: String,
+    pid: u32,
+    foreground: bool,
+}
+
+fn parse_lsappinfo(stdout: &str) -> Vec<LsAppInfoEntry> {
+    // Parse lsappinfo output to get app name -> pid mapping
+    // Format:  5) "Warp" ASN:0x0-0xe00e:
+    //              pid = 644 type="Foreground" ...
+    let mut apps = Vec::new();
+    let mut current_app: Option<String> = None;
+
+    for line in stdout.lines() {
+        let trimmed = line.trim();
+        // App name line starts with number: 5) "Warp" ASN:...
+        if let Some(paren_pos) = trimmed.find(')') {
+            let after_paren = &trimmed[paren_pos + 1..].trim_start();
+            if after_paren.starts_with('"') {
+                if let Some(quote_end) = after_paren[1..].find('"') {
+                    current_app = Some(after_paren[1..quote_end + 1].to_string());
+                }
+            }
+        }
+        // PID line: pid = 660 type="Foreground"
+        if trimmed.starts_with("pid =") || trimmed.starts_with("pid=") {
+            if let Some(app) = current_app.take() {
+                // Extract pid value - it's after "pid =" and before next space
+                let pid_part = trimmed.strip_prefix("pid =").or_else(|| trimmed.strip_prefix("pid=")).unwrap_or("");
+                if let Some(pid_str) = pid_part.trim().split_whitespace().next() {
+                    if let Ok(pid) = pid_str.parse::<u32>() {
+                        apps.push(LsAppInfoEntry {
+                            name: app,
+                            pid,
+                            foreground: trimmed.contains("type=\"Foreground\""),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    apps
+}
+
+/// Maps PIDs to the app that owns them, so helpers like
+/// `Google Chrome Helper (Renderer)` show up under their app.
+///
+/// Process tables are read once and lookups are cached for the lifetime of
+/// the resolver (one CLI invocation).
+struct AppResolver {
+    /// pid -> app name for every app registered with LaunchServices
+    apps: std::collections::HashMap<u32, String>,
+    /// pid -> (parent pid, executable path)
+    procs: std::collections::HashMap<u32, (u32, String)>,
+    cache: std::collections::HashMap<u32, Option<String>>,
+}
+
+impl AppResolver {
+    fn new() -> Self {
+        let apps = Command::new("lsappinfo")
+            .args(["list", "-apps"])
+            .output()
+            .map(|o| parse_lsappinfo(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|app| (app.pid, app.name))
+            .collect();
+
+        let mut procs = std::collections::HashMap::new();
+        if let Ok(output) = Command::new("ps").args(["-axo", "pid=,ppid=,comm="]).output() {
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let mut parts = line.split_whitespace();
+                if let (Some(pid), Some(ppid)) = (parts.next(), parts.next()) {
+                    if let (Ok(pid), Ok(ppid)) = (pid.parse::<u32>(), ppid.parse::<u32>()) {
+                        let comm = parts.collect::<Vec<_>>().join(" ");
+                        procs.insert(pid, (ppid, comm));
+                    }
+                }
+            }
+        }
+
+        AppResolver {
+            apps,
+            procs,
+            cache: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Owning app name for a PID, if one can be found.
+    fn app_for_pid(&mut self, pid: u32) -> Option<String> {
+        if let Some(cached) = self.cache.get(&pid) {
+            return cached.clone();
+        }
+
+        let mut app = None;
+
+        // Walk up the parent chain until we hit a registered app
+        let mut current = pid;
+        for _ in 0..16 {
+            if let Some(name) = self.apps.get(&current) {
+                app = Some(name.clone());
+                break;
+            }
+            match self.procs.get(&current) {
+                Some(&(ppid, _)) if ppid > 1 && ppid != current => current = ppid,
+                _ => break,
+            }
+        }
+
+        // Fall back to the outermost .app bundle in the executable path
+        if app.is_none() {
+            if let Some((_, comm)) = self.procs.get(&pid) {
+                app = comm
+                    .split('/')
+                    .find_map(|c| c.strip_suffix(".app"))
+                    .map(|s| s.to_string());
+            }
+        }
+
+        self.cache.insert(pid, app.clone());
+        app
+    }
+
+    /// Display name like `Safari (WebContent)` for a raw `top` COMMAND.
+    fn display_name(&mut self, pid: u32, raw: &str) -> String {
+        let Some(app) = self.app_for_pid(pid) else {
+            return raw.to_string();
+        };
+
+        // Reverse-DNS helper names: keep just the last component
+        let short = if raw.contains(' ') {
+            raw
+        } else {
+            raw.rsplit('.').next().unwrap_or(raw)
+        };
+
+        if short == app || raw == app {
+            app
+        } else {
+            format!("{} ({})", app, short)
+        }
+    }
+}
+
 fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -916,20 +1704,62 @@ return outPath
 // Energy command
 // ============================================================================
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum EnergyMetric {
+    /// CPU % from `top`
+    Cpu,
+    /// Energy impact (`top -stats power`), which also accounts for GPU use and wakeups
+    Energy,
+}
+
+impl EnergyMetric {
+    /// `top -stats` column name
+    fn stat(self) -> &'static str {
+        match self {
+            EnergyMetric::Cpu => "cpu",
+            EnergyMetric::Energy => "power",
+        }
+    }
+
+    /// Column header as printed by `top`
+    fn top_header(self) -> &'static str {
+        match self {
+            EnergyMetric::Cpu => "CPU",
+            EnergyMetric::Energy => "POWER",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            EnergyMetric::Cpu => "CPU %",
+            EnergyMetric::Energy => "ENERGY",
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ProcessEnergy {
     name: String,
     pid: u32,
-    cpu_percent: f64,
+    /// CPU % or energy impact, depending on the metric
+    value: f64,
 }
 
-fn list_energy(limit: Option<usize>, kill: &[u32], force: bool) -> Result<()> {
+fn list_energy(
+    limit: Option<usize>,
+    kill: &[u32],
+    force: bool,
+    metric: EnergyMetric,
+) -> Result<()> {
     let limit = limit.unwrap_or(15);
 
-    let processes = fetch_energy()?;
+    let processes = fetch_energy(metric)?;
 
     if processes.is_empty() {
-        println!("No processes with significant CPU usage found.");
+        match metric {
+            EnergyMetric::Cpu => println!("No processes with significant CPU usage found."),
+            EnergyMetric::Energy => println!("No processes with significant energy impact found."),
+        }
         return Ok(());
     }
 
@@ -937,11 +1767,14 @@ fn list_energy(limit: Option<usize>, kill: &[u32], force: bool) -> Result<()> {
     let processes: Vec<_> = processes.into_iter().take(limit).collect();
 
     println!("Top energy consumers (showing {}/{}):\n", processes.len(), total);
-    println!("{:<8} {:>8}  {}", "PID", "CPU %", "PROCESS");
+    println!("{:<8} {:>8}  {}", "PID", metric.label(), "PROCESS");
     println!("{}", "-".repeat(50));
 
     for p in &processes {
-        println!("{:<8} {:>7.1}%  {}", p.pid, p.cpu_percent, p.name);
+        match metric {
+            EnergyMetric::Cpu => println!("{:<8} {:>7.1}%  {}", p.pid, p.value, p.name),
+            EnergyMetric::Energy => println!("{:<8} {:>8.1}  {}", p.pid, p.value, p.name),
+        }
     }
 
     if !kill.is_empty() {
@@ -966,9 +1799,17 @@ fn list_cpu(
     interval_secs: u64,
     threshold: f64,
     show_system: bool,
+    raw: bool,
 ) -> Result<()> {
     let limit = limit.unwrap_or(20);
-    let mut processes = fetch_cpu(window_secs, interval_secs, threshold, show_system)?;
+    let mut resolver = (!raw).then(AppResolver::new);
+    let mut processes = fetch_cpu(
+        window_secs,
+        interval_secs,
+        threshold,
+        show_system,
+        resolver.as_mut(),
+    )?;
 
     if processes.is_empty() {
         println!("No processes above threshold.");
@@ -1003,8 +1844,10 @@ fn run_cpu_tui(
     interval_secs: u64,
     threshold: f64,
     show_system: bool,
+    raw: bool,
 ) -> Result<()> {
     let limit = limit.unwrap_or(20);
+    let mut resolver = (!raw).then(AppResolver::new);
 
     enable_raw_mode().context("failed to enable raw mode")?;
     let mut stdout = std::io::stdout();
@@ -1023,8 +1866,14 @@ fn run_cpu_tui(
     let _guard = TuiGuard;
 
     loop {
-        let processes = fetch_cpu(window_secs, interval_secs, threshold, show_system)
-            .unwrap_or_default();
+        let processes = fetch_cpu(
+            window_secs,
+            interval_secs,
+            threshold,
+            show_system,
+            resolver.as_mut(),
+        )
+        .unwrap_or_default();
 
         terminal
             .draw(|f| {
@@ -1091,12 +1940,13 @@ fn run_cpu_tui(
     Ok(())
 }
 
-fn fetch_energy() -> Result<Vec<ProcessEnergy>> {
-    // Use top to get accurate CPU snapshot (samples for 1 second)
-    // -l 2 means 2 samples, second one has actual CPU averages
+fn fetch_energy(metric: EnergyMetric) -> Result<Vec<ProcessEnergy>> {
+    // Use top to get accurate CPU/energy snapshot (samples for 1 second)
+    // -l 2 means 2 samples, second one has actual averages
     // -n 100 limits to top 100 processes
+    let stats = format!("pid,{},command", metric.stat());
     let output = Command::new("top")
-        .args(["-l", "2", "-n", "100", "-stats", "pid,cpu,command"])
+        .args(["-l", "2", "-n", "100", "-stats", &stats])
         .output()
         .context("failed to run top")?;
 
@@ -1108,14 +1958,15 @@ fn fetch_energy() -> Result<Vec<ProcessEnergy>> {
     let stdout = String::from_utf8_lossy(&output.stdout);
 
     // Find the second "PID" header (start of second sample's process list)
-    // top -l 2 outputs two samples, the second one has accurate CPU %
+    // top -l 2 outputs two samples, the second one has accurate values
+    let header = metric.top_header();
     let mut pid_headers = 0;
     let mut start_idx = None;
     let lines: Vec<&str> = stdout.lines().collect();
 
     for (i, line) in lines.iter().enumerate() {
-        // Header line contains both PID and CPU
-        if line.contains("PID") && line.contains("CPU") {
+        // Header line contains both PID and the metric column
+        if line.contains("PID") && line.contains(header) {
             pid_headers += 1;
             if pid_headers == 2 {
                 start_idx = Some(i + 1);
@@ -1129,7 +1980,7 @@ fn fetch_energy() -> Result<Vec<ProcessEnergy>> {
         Some(idx) => idx,
         None if pid_headers == 1 => {
             lines.iter()
-                .position(|l| l.contains("PID") && l.contains("CPU"))
+                .position(|l| l.contains("PID") && l.contains(header))
                 .map(|i| i + 1)
                 .ok_or_else(|| anyhow::anyhow!("failed to parse top output"))?
         }
@@ -1147,24 +1998,24 @@ fn fetch_energy() -> Result<Vec<ProcessEnergy>> {
 
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() >= 3 {
-            if let (Ok(pid), Ok(cpu)) = (parts[0].parse::<u32>(), parts[1].parse::<f64>()) {
-                if cpu > 0.0 {
+            if let (Ok(pid), Ok(value)) = (parts[0].parse::<u32>(), parts[1].parse::<f64>()) {
+                if value > 0.0 {
                     processes.push(ProcessEnergy {
                         name: parts[2..].join(" "),
                         pid,
-                        cpu_percent: cpu,
+                        value,
                     });
                 }
             }
         }
     }
 
-    // Sort by CPU descending
-    processes.sort_by(|a, b| b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap());
+    // Sort by metric descending
+    processes.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap());
     Ok(processes)
 }
 
-fn run_energy_tui(limit: Option<usize>) -> Result<()> {
+fn run_energy_tui(limit: Option<usize>, metric: EnergyMetric) -> Result<()> {
     let limit = limit.unwrap_or(20);
 
     enable_raw_mode().context("failed to enable raw mode")?;
@@ -1184,7 +2035,7 @@ fn run_energy_tui(limit: Option<usize>) -> Result<()> {
     let _guard = TuiGuard;
 
     loop {
-        let processes = fetch_energy().unwrap_or_default();
+        let processes = fetch_energy(metric).unwrap_or_default();
 
         terminal
             .draw(|f| {
@@ -1198,7 +2049,7 @@ fn run_energy_tui(limit: Option<usize>) -> Result<()> {
                     .map(|p| {
                         Row::new(vec![
                             p.pid.to_string(),
-                            format!("{:.1}", p.cpu_percent),
+                            format!("{:.1}", p.value),
                             p.name.clone(),
                         ])
                     })
@@ -1213,12 +2064,15 @@ fn run_energy_tui(limit: Option<usize>) -> Result<()> {
                     ],
                 )
                 .header(
-                    Row::new(vec!["PID", "CPU %", "PROCESS"])
+                    Row::new(vec!["PID", metric.label(), "PROCESS"])
                         .style(Style::default().add_modifier(Modifier::BOLD)),
                 )
                 .block(
                     Block::default()
-                        .title("Top CPU processes")
+                        .title(match metric {
+                            EnergyMetric::Cpu => "Top CPU processes",
+                            EnergyMetric::Energy => "Top energy impact",
+                        })
                         .borders(Borders::ALL)
                         .border_type(BorderType::Plain),
                 );
@@ -1254,6 +2108,7 @@ fn fetch_cpu(
     interval_secs: u64,
     threshold: f64,
     show_system: bool,
+    mut resolver: Option<&mut AppResolver>,
 ) -> Result<Vec<ProcessCpu>> {
     let interval_secs = interval_secs.max(1);
     let window_secs = window_secs.max(interval_secs);
@@ -1316,6 +2171,10 @@ fn fetch_cpu(
             if !show_system && is_system_process(&name) {
                 return None;
             }
+            let name = match resolver.as_deref_mut() {
+                Some(resolver) => resolver.display_name(pid, &name),
+                None => name,
+            };
             Some(ProcessCpu {
                 name,
                 pid,
@@ -1449,51 +2308,1791 @@ fn kill_processes(pids: &[u32], force: bool) -> Result<()> {
 }
 
 // ============================================================================
-// Warp commands
+// Frontmost command
 // ============================================================================
 
-fn warp_title() -> Result<()> {
-    // Get clipboard content
-    let output = Command::new("pbpaste")
+#[derive(Debug, Default, serde::Serialize)]
+struct FrontmostInfo {
+    app_name: String,
+    app_id: String,
+    window_title: String,
+}
+
+fn get_frontmost() -> Result<FrontmostInfo> {
+    // Same query the intent daemon uses for its native context source
+    let script = r#"
+        tell application "System Events"
+            set frontApp to first application process whose frontmost is true
+            set appName to name of frontApp
+            set appId to bundle identifier of frontApp
+            try
+                set windowTitle to name of front window of frontApp
+            on error
+                set windowTitle to ""
+            end try
+        end tell
+        return appId & "\n" & appName & "\n" & windowTitle
+    "#;
+
+    let text = run_osascript(script)?;
+    let mut lines = text.split('\n');
+    Ok(FrontmostInfo {
+        app_id: lines.next().unwrap_or("").to_string(),
+        app_name: lines.next().unwrap_or("").to_string(),
+        window_title: lines.next().unwrap_or("").to_string(),
+    })
+}
+
+fn frontmost(json: bool) -> Result<()> {
+    let info = get_frontmost()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        println!("App:    {}", info.app_name);
+        println!("Bundle: {}", info.app_id);
+        println!("Window: {}", info.window_title);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Displays command
+// ============================================================================
+
+#[derive(Debug, serde::Serialize)]
+struct DisplayInfo {
+    name: String,
+    /// Logical resolution ("looks like"), e.g. 1728x1117
+    resolution: Option<(u32, u32)>,
+    /// Backing pixel resolution, e.g. 3456x2234
+    pixels: Option<(u32, u32)>,
+    /// Backing pixels per point (2.0 for Retina)
+    scale: Option<f64>,
+    refresh_hz: Option<f64>,
+    main: bool,
+    built_in: bool,
+    mirrored: bool,
+}
+
+fn get_displays() -> Result<Vec<DisplayInfo>> {
+    let output = Command::new("system_profiler")
+        .args(["SPDisplaysDataType", "-json"])
         .output()
-        .context("failed to run pbpaste")?;
+        .context("failed to run system_profiler")?;
 
-    let content = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("system_profiler failed: {}", stderr.trim());
+    }
 
-    // Warp titles look like: "~/lang/rust - fish" or "/Users/nikiv/project - zsh"
-    // Extract the last path component before " - shell"
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("failed to parse system_profiler output")?;
+    Ok(parse_displays(&json))
+}
 
-    // Strip " - <shell>" suffix if present
-    let path_part = content
-        .split(" - ")
-        .next()
-        .unwrap_or(&content);
+/// Each GPU in `SPDisplaysDataType` lists its attached displays under
+/// `spdisplays_ndrvs`. GPUs with nothing attached (e.g. a closed laptop lid
+/// and no external monitor) simply have no such key.
+fn parse_displays(json: &serde_json::Value) -> Vec<DisplayInfo> {
+    let gpus = json
+        .get("SPDisplaysDataType")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut displays = Vec::new();
+    for gpu in gpus {
+        let Some(ndrvs) = gpu.get("spdisplays_ndrvs").and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for d in ndrvs {
+            let field = |key: &str| d.get(key).and_then(|v| v.as_str());
+            let is_yes = |key: &str| field(key) == Some("spdisplays_yes");
 
-    // Get last non-empty path component
-    let title = path_part
-        .split('/')
-        .filter(|s| !s.is_empty())
-        .last()
-        .unwrap_or("")
-        .trim();
+            if field("spdisplays_online") == Some("spdisplays_no") {
+                continue;
+            }
 
-    if title.is_empty() {
-        anyhow::bail!("could not extract title from clipboard");
+            // "1728 x 1117 @ 120.00Hz" (newer macOS) or "1920 x 1080 @ 60 Hz"
+            let res_field = field("_spdisplays_resolution").or_else(|| field("spdisplays_resolution"));
+            let resolution = res_field.and_then(parse_dimensions);
+            let refresh_hz = res_field
+                .and_then(|s| s.split_once('@'))
+                .and_then(|(_, hz)| hz.trim().trim_end_matches("Hz").trim().parse().ok());
+            let pixels = field("_spdisplays_pixels").and_then(parse_dimensions);
+            let scale = match (pixels, resolution) {
+                (Some((pw, _)), Some((rw, _))) if rw > 0 => Some(pw as f64 / rw as f64),
+                _ => None,
+            };
+
+            displays.push(DisplayInfo {
+                name: field("_name").unwrap_or("Unknown display").to_string(),
+                resolution,
+                pixels,
+                scale,
+                refresh_hz,
+                main: is_yes("spdisplays_main"),
+                built_in: field("spdisplays_connection_type") == Some("spdisplays_internal"),
+                mirrored: field("spdisplays_mirror") == Some("spdisplays_on"),
+            });
+        }
     }
 
-    // Put back in clipboard
-    let mut pbcopy = Command::new("pbcopy")
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-        .context("failed to run pbcopy")?;
+    // Main display first, then as reported
+    displays.sort_by_key(|d| !d.main);
+    displays
+}
 
-    if let Some(stdin) = pbcopy.stdin.as_mut() {
-        use std::io::Write;
-        stdin.write_all(title.as_bytes())?;
+/// Parse the leading "W x H" of a system_profiler size string.
+fn parse_dimensions(s: &str) -> Option<(u32, u32)> {
+    let mut parts = s.split_whitespace();
+    let w = parts.next()?.parse().ok()?;
+    if parts.next()? != "x" {
+        return None;
     }
+    let h = parts.next()?.parse().ok()?;
+    Some((w, h))
+}
 
-    pbcopy.wait()?;
+fn displays(json: bool) -> Result<()> {
+    let displays = get_displays()?;
 
-    println!("{}", title);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&displays)?);
+        return Ok(());
+    }
+
+    if displays.is_empty() {
+        println!("No active displays found.");
+        return Ok(());
+    }
+
+    let fmt_size = |size: Option<(u32, u32)>| {
+        size.map(|(w, h)| format!("{}x{}", w, h))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    println!(
+        "{:<28} {:>11} {:>11} {:>6} {:>8}  FLAGS",
+        "DISPLAY", "RESOLUTION", "PIXELS", "SCALE", "REFRESH"
+    );
+    for d in &displays {
+        let mut flags = Vec::new();
+        if d.main {
+            flags.push("main");
+        }
+        if d.built_in {
+            flags.push("built-in");
+        }
+        if d.mirrored {
+            flags.push("mirrored");
+        }
+
+        println!(
+            "{:<28} {:>11} {:>11} {:>6} {:>8}  {}",
+            d.name,
+            fmt_size(d.resolution),
+            fmt_size(d.pixels),
+            d.scale
+                .map(|s| format!("{:.1}x", s))
+                .unwrap_or_else(|| "-".to_string()),
+            d.refresh_hz
+                .map(|hz| format!("{:.0}Hz", hz))
+                .unwrap_or_else(|| "-".to_string()),
+            flags.join(", ")
+        );
+    }
+
+    if !displays.iter().any(|d| d.built_in) {
+        println!();
+        println!("(built-in display not active: lid closed or desktop Mac)");
+    }
     Ok(())
 }
+
+// ============================================================================
+// Wifi command
+// ============================================================================
+
+const AIRPORT: &str =
+    "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+#[derive(Debug, Default, serde::Serialize)]
+struct WifiStatus {
+    interface: Option<String>,
+    /// None when Wi-Fi is off or not associated
+    ssid: Option<String>,
+    bssid: Option<String>,
+    rssi_dbm: Option<i32>,
+    noise_dbm: Option<i32>,
+    channel: Option<String>,
+    tx_rate_mbps: Option<f64>,
+    security: Option<String>,
+    /// Which tool the data came from (airport, wdutil or system_profiler)
+    source: &'static str,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct WifiNetwork {
+    ssid: String,
+    rssi_dbm: Option<i32>,
+    channel: Option<String>,
+    security: Option<String>,
+}
+
+/// Run a tool and return its stdout, or None if it is missing or failed.
+/// Used for the fallback chain, where any single tool may be unavailable.
+fn try_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn system_profiler_airport() -> Result<serde_json::Value> {
+    let output = Command::new("system_profiler")
+        .args(["SPAirPortDataType", "-json"])
+        .output()
+        .context("failed to run system_profiler")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("system_profiler failed: {}", stderr.trim());
+    }
+
+    serde_json::from_slice(&output.stdout).context("failed to parse system_profiler output")
+}
+
+/// `airport` was deprecated in macOS 14.4 and now only prints a warning, so
+/// fall back to `wdutil` (which needs root) and then `system_profiler`.
+fn get_wifi_status() -> Result<WifiStatus> {
+    if let Some(status) = try_output(AIRPORT, &["-I"]).and_then(|s| parse_airport_info(&s)) {
+        return Ok(status);
+    }
+    if let Some(status) = try_output("wdutil", &["info"]).and_then(|s| parse_wdutil_info(&s)) {
+        return Ok(status);
+    }
+    let json = system_profiler_airport()?;
+    parse_profiler_status(&json).context("no Wi-Fi interface found")
+}
+
+fn get_wifi_networks() -> Result<Vec<WifiNetwork>> {
+    let mut networks = match try_output(AIRPORT, &["-s"]).map(|s| parse_airport_scan(&s)) {
+        Some(networks) if !networks.is_empty() => networks,
+        _ => parse_profiler_networks(&system_profiler_airport()?),
+    };
+
+    // Strongest first, one row per SSID (mesh/multi-band APs repeat names)
+    networks.sort_by_key(|n| std::cmp::Reverse(n.rssi_dbm.unwrap_or(i32::MIN)));
+    let mut seen = std::collections::HashSet::new();
+    networks.retain(|n| seen.insert(n.ssid.clone()));
+    Ok(networks)
+}
+
+/// Leading integer of strings like "-55 dBm".
+fn parse_dbm(s: &str) -> Option<i32> {
+    s.split_whitespace().next()?.parse().ok()
+}
+
+/// `airport -I` prints right-aligned `key: value` lines. A deprecated
+/// `airport` prints only a warning, so require at least one known key.
+fn parse_airport_info(output: &str) -> Option<WifiStatus> {
+    let mut status = WifiStatus {
+        source: "airport",
+        ..Default::default()
+    };
+    let mut recognized = false;
+
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        recognized |= match key.trim() {
+            "AirPort" => true, // "AirPort: Off"
+            "SSID" => {
+                status.ssid = Some(value.to_string());
+                true
+            }
+            "BSSID" => {
+                status.bssid = Some(value.to_string());
+                true
+            }
+            "agrCtlRSSI" => {
+                status.rssi_dbm = value.parse().ok();
+                true
+            }
+            "agrCtlNoise" => {
+                status.noise_dbm = value.parse().ok();
+                true
+            }
+            "channel" => {
+                status.channel = Some(value.to_string());
+                true
+            }
+            "lastTxRate" => {
+                status.tx_rate_mbps = value.parse().ok();
+                true
+            }
+            "link auth" => {
+                status.security = Some(value.to_string());
+                true
+            }
+            _ => false,
+        };
+    }
+
+    recognized.then_some(status)
+}
+
+/// `wdutil info` groups fields into sections; the Wi-Fi one starts with a
+/// `WIFI` header (between rules of dashes) and uses `Key : value` lines.
+fn parse_wdutil_info(output: &str) -> Option<WifiStatus> {
+    let mut status = WifiStatus {
+        source: "wdutil",
+        ..Default::default()
+    };
+    let mut in_wifi = false;
+    let mut recognized = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if !trimmed.contains(':') && trimmed.chars().any(char::is_alphanumeric) {
+            in_wifi = trimmed == "WIFI";
+            continue;
+        }
+        if !in_wifi {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(" : ") else {
+            continue;
+        };
+        let value = value.trim();
+        let text = || (value != "None").then(|| value.to_string());
+        match key.trim() {
+            "Interface Name" => status.interface = text(),
+            "SSID" => status.ssid = text(),
+            "BSSID" => status.bssid = text(),
+            "RSSI" => status.rssi_dbm = parse_dbm(value),
+            "Noise" => status.noise_dbm = parse_dbm(value),
+            "Channel" => status.channel = text(),
+            // "573.0 Mbps"
+            "Tx Rate" => {
+                status.tx_rate_mbps = value.split_whitespace().next().and_then(|s| s.parse().ok())
+            }
+            "Security" => status.security = text(),
+            _ => continue,
+        }
+        recognized = true;
+    }
+
+    recognized.then_some(status)
+}
+
+/// The Wi-Fi interface in `SPAirPortDataType`: the one that is associated,
+/// or otherwise the first listed.
+fn profiler_interface(json: &serde_json::Value) -> Option<&serde_json::Value> {
+    let interfaces = json
+        .get("SPAirPortDataType")?
+        .as_array()?
+        .first()?
+        .get("spairport_airport_interfaces")?
+        .as_array()?;
+    interfaces
+        .iter()
+        .find(|i| i.get("spairport_current_network_information").is_some())
+        .or_else(|| interfaces.first())
+}
+
+/// Fields shared by the current network and scan results in system_profiler.
+fn profiler_network(net: &serde_json::Value) -> WifiNetwork {
+    let field = |key: &str| net.get(key).and_then(|v| v.as_str());
+    // "-55 dBm / -90 dBm"
+    let rssi_dbm = field("spairport_signal_noise").and_then(parse_dbm);
+    // "36 (5GHz, 80MHz)", or a bare number on older releases
+    let channel = net.get("spairport_network_channel").and_then(|v| match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    });
+    // "spairport_security_mode_wpa2_personal" -> "wpa2 personal"
+    let security = field("spairport_security_mode").map(|s| {
+        s.trim_start_matches("spairport_security_mode_")
+            .replace('_', " ")
+    });
+
+    WifiNetwork {
+        ssid: field("_name").unwrap_or("").to_string(),
+        rssi_dbm,
+        channel,
+        security,
+    }
+}
+
+fn parse_profiler_status(json: &serde_json::Value) -> Option<WifiStatus> {
+    let iface = profiler_interface(json)?;
+    let mut status = WifiStatus {
+        interface: iface.get("_name").and_then(|v| v.as_str()).map(String::from),
+        source: "system_profiler",
+        ..Default::default()
+    };
+
+    if let Some(current) = iface.get("spairport_current_network_information") {
+        let net = profiler_network(current);
+        status.ssid = Some(net.ssid);
+        status.rssi_dbm = net.rssi_dbm;
+        status.noise_dbm = current
+            .get("spairport_signal_noise")
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.split_once('/'))
+            .and_then(|(_, noise)| parse_dbm(noise.trim()));
+        status.channel = net.channel;
+        status.security = net.security;
+        status.tx_rate_mbps = current.get("spairport_network_rate").and_then(|v| v.as_f64());
+    }
+    Some(status)
+}
+
+fn parse_profiler_networks(json: &serde_json::Value) -> Vec<WifiNetwork> {
+    profiler_interface(json)
+        .and_then(|i| i.get("spairport_airport_other_local_wireless_networks"))
+        .and_then(|v| v.as_array())
+        .map(|nets| nets.iter().map(profiler_network).collect())
+        .unwrap_or_default()
+}
+
+fn is_bssid(s: &str) -> bool {
+    let parts: Vec<&str> = s.split(':').collect();
+    parts.len() == 6
+        && parts
+            .iter()
+            .all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `airport -s` columns: SSID BSSID RSSI CHANNEL HT CC SECURITY. SSIDs are
+/// right-aligned and may contain spaces, so split around the BSSID column.
+fn parse_airport_scan(output: &str) -> Vec<WifiNetwork> {
+    let mut networks = Vec::new();
+    for line in output.lines().skip(1) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some(idx) = tokens.iter().position(|t| is_bssid(t)) else {
+            continue;
+        };
+        networks.push(WifiNetwork {
+            ssid: tokens[..idx].join(" "),
+            rssi_dbm: tokens.get(idx + 1).and_then(|s| s.parse().ok()),
+            channel: tokens.get(idx + 2).map(|s| s.to_string()),
+            security: tokens
+                .get(idx + 5..)
+                .filter(|rest| !rest.is_empty())
+                .map(|rest| rest.join(" ")),
+        });
+    }
+    networks
+}
+
+fn signal_quality(rssi: i32) -> &'static str {
+    match rssi {
+        r if r >= -55 => "excellent",
+        r if r >= -67 => "good",
+        r if r >= -75 => "fair",
+        _ => "weak",
+    }
+}
+
+fn wifi_status(json: bool) -> Result<()> {
+    let status = get_wifi_status()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    let Some(ssid) = &status.ssid else {
+        println!("Wi-Fi not connected.");
+        return Ok(());
+    };
+
+    println!("SSID:      {}", ssid);
+    if let Some(iface) = &status.interface {
+        println!("Interface: {}", iface);
+    }
+    if let Some(rssi) = status.rssi_dbm {
+        let noise = status
+            .noise_dbm
+            .map(|n| format!(", noise {} dBm, SNR {} dB", n, rssi - n))
+            .unwrap_or_default();
+        println!("Signal:    {} dBm ({}){}", rssi, signal_quality(rssi), noise);
+    }
+    if let Some(channel) = &status.channel {
+        println!("Channel:   {}", channel);
+    }
+    if let Some(rate) = status.tx_rate_mbps {
+        println!("Tx rate:   {:.0} Mbps", rate);
+    }
+    if let Some(security) = &status.security {
+        println!("Security:  {}", security);
+    }
+    if ssid.is_empty() || ssid == "<redacted>" {
+        println!();
+        println!("(SSID hidden by macOS: grant Location Services to your terminal to see it)");
+    }
+    Ok(())
+}
+
+fn wifi_scan(json: bool) -> Result<()> {
+    let networks = get_wifi_networks()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&networks)?);
+        return Ok(());
+    }
+
+    if networks.is_empty() {
+        println!("No networks found.");
+        return Ok(());
+    }
+
+    println!("{:<32} {:>8} {:<20} SECURITY", "SSID", "SIGNAL", "CHANNEL");
+    for n in &networks {
+        println!(
+            "{:<32} {:>8} {:<20} {}",
+            if n.ssid.is_empty() { "(hidden)" } else { &n.ssid },
+            n.rssi_dbm
+                .map(|r| format!("{} dBm", r))
+                .unwrap_or_else(|| "-".to_string()),
+            n.channel.as_deref().unwrap_or("-"),
+            n.security.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Notify command
+// ============================================================================
+
+fn notify_send(title: &str, body: &str, sound: Option<&str>) -> Result<()> {
+    // Text goes in as arguments so it needs no AppleScript escaping
+    let display = if sound.is_some() {
+        "display notification (item 2 of argv) with title (item 1 of argv) sound name (item 3 of argv)"
+    } else {
+        "display notification (item 2 of argv) with title (item 1 of argv)"
+    };
+    let mut cmd = Command::new("osascript");
+    cmd.args(["-e", "on run argv", "-e", display, "-e", "end run", title, body]);
+    if let Some(sound) = sound {
+        cmd.arg(sound);
+    }
+
+    let output = cmd.output().context("failed to run osascript")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("osascript failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+/// Seconds between the Unix epoch and Core Data's 2001-01-01 reference date.
+const MAC_EPOCH_OFFSET: f64 = 978_307_200.0;
+
+#[derive(Debug, serde::Serialize)]
+struct NotificationRecord {
+    app: String,
+    title: String,
+    subtitle: Option<String>,
+    body: String,
+    /// Unix seconds
+    delivered: Option<i64>,
+}
+
+fn notification_db_path() -> String {
+    expand_tilde("~/Library/Group Containers/group.com.apple.usernoted/db2/db")
+}
+
+/// Query the Notification Center database with the system `sqlite3`.
+/// usernoted keeps it open, so this tries a plain read-only open first and
+/// falls back to `immutable=1`, which skips locking entirely but can miss
+/// rows still sitting in the WAL.
+fn query_notification_db(sql: &str) -> Result<Vec<serde_json::Value>> {
+    let path = notification_db_path();
+    if !Path::new(&path).exists() {
+        anyhow::bail!("notification database not found at {}", path);
+    }
+
+    let mut last_err = String::new();
+    for mode in ["mode=ro", "immutable=1"] {
+        let output = Command::new("sqlite3")
+            .args(["-readonly", "-json", "-cmd", ".timeout 2000"])
+            .arg(format!("file:{}?{}", path.replace(' ', "%20"), mode))
+            .arg(sql)
+            .output()
+            .context("failed to run sqlite3")?;
+
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            // sqlite3 prints nothing at all for an empty result
+            if stdout.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+            return serde_json::from_str(&stdout).context("failed to parse sqlite3 output");
+        }
+
+        last_err = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if !last_err.contains("locked") && !last_err.contains("busy") {
+            break;
+        }
+    }
+
+    if last_err.contains("authorization denied") || last_err.contains("unable to open") {
+        anyhow::bail!(
+            "cannot read {} ({}); grant your terminal Full Disk Access",
+            path,
+            last_err
+        );
+    }
+    anyhow::bail!("sqlite3 failed: {}", last_err)
+}
+
+fn list_notifications(limit: usize) -> Result<Vec<NotificationRecord>> {
+    let sql = format!(
+        "SELECT a.identifier AS app, r.delivered_date AS delivered, hex(r.data) AS data \
+         FROM record r LEFT JOIN app a ON a.app_id = r.app_id \
+         ORDER BY r.delivered_date DESC LIMIT {}",
+        limit
+    );
+
+    let rows = query_notification_db(&sql)?;
+    Ok(rows.iter().filter_map(parse_notification_row).collect())
+}
+
+/// Each row's `data` is a binary plist: `{app, req: {titl, subt, body}, ...}`.
+fn parse_notification_row(row: &serde_json::Value) -> Option<NotificationRecord> {
+    let bytes = decode_hex(row.get("data")?.as_str()?)?;
+    let data = plist::Value::from_reader(std::io::Cursor::new(bytes)).ok()?;
+    let data = data.as_dictionary()?;
+    let req = data.get("req").and_then(|v| v.as_dictionary());
+    let text = |key: &str| {
+        req.and_then(|r| r.get(key))
+            .and_then(|v| v.as_string())
+            .map(|s| s.to_string())
+    };
+
+    let app = row
+        .get("app")
+        .and_then(|v| v.as_str())
+        .or_else(|| data.get("app").and_then(|v| v.as_string()))
+        .unwrap_or("")
+        .to_string();
+    let delivered = row
+        .get("delivered")
+        .and_then(|v| v.as_f64())
+        .map(|secs| (secs + MAC_EPOCH_OFFSET) as i64);
+
+    Some(NotificationRecord {
+        app,
+        title: text("titl").unwrap_or_default(),
+        subtitle: text("subt"),
+        body: text("body").unwrap_or_default(),
+        delivered,
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn notify(
+    title: Option<String>,
+    body: Option<String>,
+    sound: Option<String>,
+    list: bool,
+    limit: Option<usize>,
+) -> Result<()> {
+    if list {
+        let records = list_notifications(limit.unwrap_or(20))?;
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+
+    let title = title.unwrap_or_default();
+    notify_send(&title, body.as_deref().unwrap_or(""), sound.as_deref())
+}
+
+// ============================================================================
+// Volume / Brightness commands
+// ============================================================================
+
+fn run_osascript(script: &str) -> Result<String> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .context("failed to run osascript")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("osascript failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn volume(level: Option<u8>) -> Result<()> {
+    if let Some(level) = level {
+        run_osascript(&format!("set volume output volume {}", level))?;
+    }
+
+    let current = run_osascript("output volume of (get volume settings)")?;
+    // Reports "missing value" when the output device has no volume control
+    let current: u8 = current
+        .parse()
+        .with_context(|| format!("output device has no volume control ({})", current))?;
+    println!("{}", current);
+    Ok(())
+}
+
+fn brightness(level: Option<f64>) -> Result<()> {
+    if let Some(level) = level {
+        if !(0.0..=1.0).contains(&level) {
+            anyhow::bail!("brightness must be between 0.0 and 1.0 (got {})", level);
+        }
+
+        let status = Command::new("brightness")
+            .arg(format!("{:.3}", level))
+            .status()
+            .context("failed to run brightness (install with `brew install brightness`)")?;
+        if !status.success() {
+            anyhow::bail!("brightness failed to set level {}", level);
+        }
+    }
+
+    let output = Command::new("brightness")
+        .arg("-l")
+        .output()
+        .context("failed to run brightness (install with `brew install brightness`)")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Format: display 0: brightness 0.750000
+    let current = stdout
+        .lines()
+        .find_map(|line| line.split_once("brightness ").map(|(_, v)| v.trim()))
+        .and_then(|v| v.parse::<f64>().ok())
+        .ok_or_else(|| anyhow::anyhow!("no display with adjustable brightness found"))?;
+
+    println!("{:.2}", current);
+    Ok(())
+}
+
+// ============================================================================
+// Awake command
+// ============================================================================
+
+/// Holds the pid of the running `macos awake`, for `awake off`.
+fn awake_pidfile() -> String {
+    expand_tilde("~/Library/Caches/macos-awake.pid")
+}
+
+fn parse_duration(raw: &str) -> Result<std::time::Duration, String> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 45s, 30m, 2h)", raw))?;
+    let secs = match unit {
+        "" | "s" => Some(number),
+        "m" => number.checked_mul(60),
+        "h" => number.checked_mul(3600),
+        _ => return Err(format!("unknown unit '{}' in '{}' (use s, m or h)", unit, raw)),
+    }
+    .ok_or_else(|| format!("duration '{}' is too long", raw))?;
+    if secs == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// 1h02m05s, 4m10s, 30s
+fn format_hms(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{:02}s", m, s),
+        (h, m, s) => format!("{}h{:02}m{:02}s", h, m, s),
+    }
+}
+
+/// Pid from the pidfile, if that process is still a running `macos`.
+fn running_awake_pid() -> Option<u32> {
+    let pid: u32 = std::fs::read_to_string(awake_pidfile())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let output = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .ok()?;
+    let comm = String::from_utf8_lossy(&output.stdout);
+    let comm = comm.trim();
+    (comm == "macos" || comm.ends_with("/macos")).then_some(pid)
+}
+
+fn awake(
+    duration: Option<std::time::Duration>,
+    display: bool,
+    while_cmd: Option<&str>,
+) -> Result<()> {
+    if let Some(pid) = running_awake_pid() {
+        anyhow::bail!("already keeping awake (pid {}); run `macos awake off` first", pid);
+    }
+
+    // `-w` ties the assertion to this process: Ctrl+C, `awake off` or a crash
+    // all release it without any cleanup on our side
+    let mut caffeinate = Command::new("caffeinate");
+    caffeinate.arg("-i");
+    if display {
+        caffeinate.arg("-d");
+    }
+    let mut caffeinate = caffeinate
+        .args(["-w", &std::process::id().to_string()])
+        .spawn()
+        .context("failed to run caffeinate")?;
+
+    let pidfile = awake_pidfile();
+    if let Some(parent) = Path::new(&pidfile).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&pidfile, std::process::id().to_string())
+        .with_context(|| format!("failed to write {}", pidfile))?;
+
+    let what = if display { "Mac and display" } else { "Mac" };
+    let result = match while_cmd {
+        Some(cmd) => {
+            println!("Keeping the {} awake while `{}` runs", what, cmd);
+            Command::new("sh")
+                .args(["-c", cmd])
+                .status()
+                .with_context(|| format!("failed to run {}", cmd))
+                .map(Some)
+        }
+        None => awake_countdown(what, duration, &mut caffeinate).map(|()| None),
+    };
+
+    let _ = caffeinate.kill();
+    let _ = caffeinate.wait();
+    let _ = std::fs::remove_file(&pidfile);
+
+    let status = result?;
+    println!("Released keep-awake");
+    if let Some(status) = status {
+        if !status.success() {
+            std::process::exit(status.code().unwrap_or(1));
+        }
+    }
+    Ok(())
+}
+
+/// Show time left (or time awake, without a duration) until it runs out.
+fn awake_countdown(
+    what: &str,
+    duration: Option<std::time::Duration>,
+    caffeinate: &mut std::process::Child,
+) -> Result<()> {
+    let interactive = std::io::IsTerminal::is_terminal(&std::io::stdout());
+    let started = std::time::Instant::now();
+    if !interactive {
+        match duration {
+            Some(duration) => println!("Keeping the {} awake for {}", what, format_hms(duration)),
+            None => println!("Keeping the {} awake until stopped", what),
+        }
+    }
+
+    loop {
+        let elapsed = started.elapsed();
+        if duration.is_some_and(|duration| elapsed >= duration) {
+            break;
+        }
+        if let Some(status) = caffeinate.try_wait()? {
+            anyhow::bail!("caffeinate exited early ({})", status);
+        }
+        if interactive {
+            let line = match duration {
+                // Rounded up, so the last second reads "1s left" rather than "0s"
+                Some(duration) => {
+                    let left = (duration - elapsed).as_secs_f64().ceil() as u64;
+                    format!("{} left", format_hms(std::time::Duration::from_secs(left)))
+                }
+                None => format!("awake for {}", format_hms(elapsed)),
+            };
+            // Trailing spaces clear what's left of a longer previous line
+            print!("\rKeeping the {} awake: {} (Ctrl+C to stop)   ", what, line);
+            std::io::Write::flush(&mut std::io::stdout())?;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+
+    if interactive {
+        println!();
+    }
+    Ok(())
+}
+
+fn awake_off() -> Result<()> {
+    let pidfile = awake_pidfile();
+    let Some(pid) = running_awake_pid() else {
+        let _ = std::fs::remove_file(&pidfile);
+        println!("Not keeping awake");
+        return Ok(());
+    };
+
+    // Its caffeinate waits on this pid and exits with it
+    let status = Command::new("kill")
+        .args(["-15", &pid.to_string()])
+        .status()
+        .context("failed to run kill")?;
+    if !status.success() {
+        anyhow::bail!("failed to stop macos awake (pid {})", pid);
+    }
+    let _ = std::fs::remove_file(&pidfile);
+    println!("Stopped keep-awake (pid {})", pid);
+    Ok(())
+}
+
+// ============================================================================
+// Uninstall command
+// ============================================================================
+
+/// ~/Library folders that hold per-app files, named after the bundle id
+/// (and for some, after the app name).
+const LIBRARY_DIRS: &[&str] = &[
+    "Application Support",
+    "Caches",
+    "Preferences",
+    "Logs",
+    "Containers",
+    "Saved Application State",
+    "HTTPStorages",
+    "WebKit",
+];
+
+/// Folders where apps also commonly use their display name.
+const NAME_DIRS: &[&str] = &["Application Support", "Caches", "Logs"];
+
+#[derive(Debug, serde::Serialize)]
+struct AppFile {
+    path: String,
+    /// "app" for the bundle itself, otherwise the ~/Library folder it's in
+    location: String,
+    size_bytes: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct UninstallPlan {
+    name: String,
+    bundle_id: String,
+    app_path: Option<String>,
+    files: Vec<AppFile>,
+    total_bytes: u64,
+}
+
+/// Find the .app bundle for a name or path in the usual install locations.
+fn find_app_bundle(app: &str) -> Option<String> {
+    if app.ends_with(".app") && Path::new(&expand_tilde(app)).is_dir() {
+        return Some(expand_tilde(app));
+    }
+    let name = app.trim_end_matches(".app");
+    ["/Applications", &expand_tilde("~/Applications")]
+        .iter()
+        .map(|dir| format!("{}/{}.app", dir, name))
+        .find(|path| Path::new(path).is_dir())
+}
+
+/// Bundle id of an app via Spotlight (same lookup as km's macro generator),
+/// falling back to its Info.plist when the app isn't indexed.
+fn get_bundle_identifier(app_path: &str) -> Option<String> {
+    let output = Command::new("mdls")
+        .args(["-name", "kMDItemCFBundleIdentifier", "-raw", app_path])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if id != "(null)" && !id.is_empty() {
+            return Some(id);
+        }
+    }
+
+    let info = plist::Value::from_file(format!("{}/Contents/Info.plist", app_path)).ok()?;
+    info.as_dictionary()?
+        .get("CFBundleIdentifier")?
+        .as_string()
+        .map(|s| s.to_string())
+}
+
+fn looks_like_bundle_id(s: &str) -> bool {
+    s.contains('.') && !s.ends_with(".app") && !s.contains(['/', ' '])
+}
+
+/// Size of a file or folder tree, not following symlinks.
+fn path_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Suffixes macOS adds to a bundle id in ~/Library. Anything else after the
+/// id, like `com.foo.App.beta`, is another app's.
+const BUNDLE_ID_SUFFIXES: &[&str] = &[".plist", ".savedstate", ".binarycookies"];
+
+/// Whether `entry` (a file name in a ~/Library folder) belongs to the app:
+/// `com.foo.App`, `com.foo.App.plist`, `com.foo.App.savedState`, or the
+/// app's name where apps use that.
+fn matches_app(entry: &str, bundle_id: &str, name: Option<&str>, by_name: bool) -> bool {
+    let entry = entry.to_lowercase();
+    let id = bundle_id.to_lowercase();
+    if entry == id
+        || entry
+            .strip_prefix(&id)
+            .is_some_and(|rest| BUNDLE_ID_SUFFIXES.contains(&rest))
+    {
+        return true;
+    }
+    by_name && name.is_some_and(|name| entry == name.to_lowercase())
+}
+
+fn plan_uninstall(app: &str) -> Result<UninstallPlan> {
+    let app_path = find_app_bundle(app);
+    let (bundle_id, name) = match &app_path {
+        Some(path) => {
+            let id = get_bundle_identifier(path)
+                .with_context(|| format!("no bundle id found for {}", path))?;
+            let name = Path::new(path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| app.to_string());
+            (id, Some(name))
+        }
+        None if looks_like_bundle_id(app) => (app.to_string(), None),
+        None => anyhow::bail!(
+            "{} not found in /Applications or ~/Applications (pass its bundle id to clean up leftovers)",
+            app
+        ),
+    };
+    if bundle_id.starts_with("com.apple.") {
+        anyhow::bail!("refusing to uninstall Apple app {}", bundle_id);
+    }
+
+    let mut files = Vec::new();
+    if let Some(path) = &app_path {
+        files.push(AppFile {
+            path: path.clone(),
+            location: "app".to_string(),
+            size_bytes: path_size(Path::new(path)),
+        });
+    }
+
+    let library = expand_tilde("~/Library");
+    for dir in LIBRARY_DIRS {
+        let Ok(entries) = std::fs::read_dir(Path::new(&library).join(dir)) else {
+            continue;
+        };
+        let by_name = NAME_DIRS.contains(dir);
+        let mut found: Vec<_> = entries
+            .flatten()
+            .filter(|e| {
+                matches_app(
+                    &e.file_name().to_string_lossy(),
+                    &bundle_id,
+                    name.as_deref(),
+                    by_name,
+                )
+            })
+            .map(|e| AppFile {
+                path: e.path().to_string_lossy().to_string(),
+                location: dir.to_string(),
+                size_bytes: path_size(&e.path()),
+            })
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        files.extend(found);
+    }
+
+    let total_bytes = files.iter().map(|f| f.size_bytes).sum();
+    Ok(UninstallPlan {
+        name: name.unwrap_or_else(|| bundle_id.clone()),
+        bundle_id,
+        app_path,
+        files,
+        total_bytes,
+    })
+}
+
+fn app_is_running(bundle_id: &str) -> bool {
+    Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "return application id (item 1 of argv) is running",
+            "-e",
+            "end run",
+            bundle_id,
+        ])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+/// Move paths to the Trash through Finder, so "Put Back" works.
+fn move_to_trash(paths: &[&str]) -> Result<()> {
+    let mut cmd = Command::new("osascript");
+    cmd.args([
+        "-e",
+        "on run argv",
+        "-e",
+        "repeat with p in argv",
+        "-e",
+        "tell application \"Finder\" to delete (POSIX file (p as text))",
+        "-e",
+        "end repeat",
+        "-e",
+        "end run",
+    ]);
+    cmd.args(paths);
+
+    let output = cmd.output().context("failed to run osascript")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Finder could not move files to the Trash: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+fn uninstall(app: &str, dry_run: bool, json: bool) -> Result<()> {
+    let plan = plan_uninstall(app)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        println!("{} ({})", plan.name, plan.bundle_id);
+        for file in &plan.files {
+            println!(
+                "  {:>10}  {:<24}  {}",
+                format_bytes(file.size_bytes),
+                file.location,
+                file.path
+            );
+        }
+        if !plan.files.is_empty() {
+            println!("  {:>10}  total", format_bytes(plan.total_bytes));
+        }
+    }
+
+    if plan.files.is_empty() {
+        eprintln!("Nothing to remove.");
+        return Ok(());
+    }
+    if dry_run {
+        return Ok(());
+    }
+    if app_is_running(&plan.bundle_id) {
+        anyhow::bail!("{} is running; quit it first", plan.name);
+    }
+
+    eprint!("Move {} item(s) to the Trash? [y/N] ", plan.files.len());
+    std::io::Write::flush(&mut std::io::stderr())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        eprintln!("Cancelled.");
+        return Ok(());
+    }
+
+    let paths: Vec<&str> = plan.files.iter().map(|f| f.path.as_str()).collect();
+    move_to_trash(&paths)?;
+    eprintln!(
+        "Moved {} item(s) ({}) to the Trash.",
+        paths.len(),
+        format_bytes(plan.total_bytes)
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Dock command
+// ============================================================================
+
+const DOCK_PLIST: &str = "~/Library/Preferences/com.apple.dock.plist";
+
+#[derive(Debug, serde::Serialize)]
+struct DockItem {
+    position: usize,
+    /// "app", "spacer", "small-spacer" or "flex-spacer"
+    kind: String,
+    name: Option<String>,
+    bundle_id: Option<String>,
+    path: Option<String>,
+}
+
+/// The Dock preferences as stored on disk (usually a binary plist).
+fn read_dock_plist() -> Result<plist::Value> {
+    read_plist(&expand_tilde(DOCK_PLIST))
+}
+
+fn persistent_apps(dock: &plist::Value) -> &[plist::Value] {
+    dock.as_dictionary()
+        .and_then(|d| d.get("persistent-apps"))
+        .and_then(|v| v.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+fn persistent_apps_mut(dock: &mut plist::Value) -> Result<&mut Vec<plist::Value>> {
+    let dict = dock
+        .as_dictionary_mut()
+        .context("Dock plist is not a dictionary")?;
+    if !dict.contains_key("persistent-apps") {
+        dict.insert("persistent-apps".to_string(), plist::Value::Array(Vec::new()));
+    }
+    dict.get_mut("persistent-apps")
+        .and_then(|v| v.as_array_mut())
+        .context("persistent-apps is not an array")
+}
+
+/// Decode a `file://` URL from `_CFURLString` into a plain path.
+fn file_url_to_path(url: &str) -> String {
+    let raw = url.strip_prefix("file://").unwrap_or(url);
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(b) = raw
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    let path = String::from_utf8_lossy(&out).to_string();
+    match path.strip_suffix('/') {
+        Some(trimmed) if !trimmed.is_empty() => trimmed.to_string(),
+        _ => path,
+    }
+}
+
+fn parse_dock_item(position: usize, tile: &plist::Value) -> DockItem {
+    let dict = tile.as_dictionary();
+    let tile_type = dict
+        .and_then(|d| d.get("tile-type"))
+        .and_then(|v| v.as_string())
+        .unwrap_or("file-tile");
+    let data = dict
+        .and_then(|d| d.get("tile-data"))
+        .and_then(|v| v.as_dictionary());
+    let field = |key: &str| {
+        data.and_then(|d| d.get(key))
+            .and_then(|v| v.as_string())
+            .map(|s| s.to_string())
+    };
+
+    let kind = match tile_type {
+        "file-tile" => "app",
+        "spacer-tile" => "spacer",
+        "small-spacer-tile" => "small-spacer",
+        "flex-spacer-tile" => "flex-spacer",
+        other => other,
+    };
+
+    DockItem {
+        position,
+        kind: kind.to_string(),
+        name: field("file-label"),
+        bundle_id: field("bundle-identifier"),
+        path: data
+            .and_then(|d| d.get("file-data"))
+            .and_then(|v| v.as_dictionary())
+            .and_then(|d| d.get("_CFURLString"))
+            .and_then(|v| v.as_string())
+            .map(file_url_to_path),
+    }
+}
+
+fn dock_items(dock: &plist::Value) -> Vec<DockItem> {
+    persistent_apps(dock)
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| parse_dock_item(i + 1, tile))
+        .collect()
+}
+
+/// Index of the pinned app matching a name, bundle id or path.
+fn find_dock_item(items: &[DockItem], app: &str) -> Result<usize> {
+    let path = expand_tilde(app);
+    let path = path.trim_end_matches('/');
+    let name = app.trim_end_matches(".app");
+    items
+        .iter()
+        .position(|item| {
+            item.name.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(name))
+                || item.bundle_id.as_deref().is_some_and(|b| b.eq_ignore_ascii_case(app))
+                || item.path.as_deref() == Some(path)
+        })
+        .with_context(|| format!("{} is not pinned in the Dock (see `macos dock list`)", app))
+}
+
+fn dock_item_label(item: &DockItem) -> String {
+    item.name
+        .clone()
+        .or_else(|| item.bundle_id.clone())
+        .unwrap_or_else(|| format!("({})", item.kind))
+}
+
+/// A persistent-apps tile for an app bundle, in the shape the Dock writes.
+fn app_tile(name: &str, app_path: &str, bundle_id: Option<&str>) -> plist::Value {
+    let mut file_data = plist::Dictionary::new();
+    file_data.insert(
+        "_CFURLString".to_string(),
+        format!("file://{}/", app_path.replace(' ', "%20")).into(),
+    );
+    file_data.insert("_CFURLStringType".to_string(), 15.into());
+
+    let mut tile_data = plist::Dictionary::new();
+    tile_data.insert("file-data".to_string(), file_data.into());
+    tile_data.insert("file-label".to_string(), name.into());
+    tile_data.insert("file-type".to_string(), 41.into());
+    if let Some(id) = bundle_id {
+        tile_data.insert("bundle-identifier".to_string(), id.into());
+    }
+
+    let mut tile = plist::Dictionary::new();
+    tile.insert("tile-data".to_string(), tile_data.into());
+    tile.insert("tile-type".to_string(), "file-tile".into());
+    tile.into()
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", prompt);
+    std::io::Write::flush(&mut std::io::stderr())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Save the edited preferences and restart the Dock so it picks them up.
+///
+/// Goes through `defaults import` rather than writing the file directly,
+/// since cfprefsd would otherwise overwrite it with its cached copy. The
+/// previous plist is kept next to it as com.apple.dock.plist.bak.
+fn write_dock_plist(dock: &plist::Value) -> Result<()> {
+    let plist_path = expand_tilde(DOCK_PLIST);
+    let backup = format!("{}.bak", plist_path);
+    std::fs::copy(&plist_path, &backup)
+        .with_context(|| format!("failed to back up {}", plist_path))?;
+
+    let tmp = std::env::temp_dir().join(format!("macos-dock-{}.plist", std::process::id()));
+    dock.to_file_xml(&tmp).context("failed to write Dock plist")?;
+
+    let output = Command::new("defaults")
+        .args(["import", "com.apple.dock"])
+        .arg(&tmp)
+        .output()
+        .context("failed to run defaults")?;
+    let _ = std::fs::remove_file(&tmp);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("defaults import failed: {}", stderr.trim());
+    }
+
+    Command::new("killall")
+        .arg("Dock")
+        .output()
+        .context("failed to restart Dock")?;
+    eprintln!("Dock restarted (previous settings saved to {}).", backup);
+    Ok(())
+}
+
+fn dock_list(json: bool) -> Result<()> {
+    let items = dock_items(&read_dock_plist()?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        println!("No apps pinned in the Dock.");
+        return Ok(());
+    }
+    for item in &items {
+        if item.kind == "app" {
+            println!(
+                "{:>3}  {:<24}  {}",
+                item.position,
+                dock_item_label(item),
+                item.path.as_deref().unwrap_or("")
+            );
+        } else {
+            println!("{:>3}  ({})", item.position, item.kind);
+        }
+    }
+    Ok(())
+}
+
+fn dock_add(app: &str, position: Option<usize>, yes: bool) -> Result<()> {
+    let app_path = find_app_bundle(app)
+        .with_context(|| format!("could not find {} in /Applications or ~/Applications", app))?;
+    let name = Path::new(&app_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| app.to_string());
+    let bundle_id = get_bundle_identifier(&app_path);
+
+    let mut dock = read_dock_plist()?;
+    let items = dock_items(&dock);
+    if items.iter().any(|item| item.path.as_deref() == Some(app_path.as_str())) {
+        anyhow::bail!("{} is already pinned in the Dock", name);
+    }
+    let index = position
+        .map(|p| p.clamp(1, items.len() + 1) - 1)
+        .unwrap_or(items.len());
+
+    if !yes && !confirm(&format!("Pin {} to the Dock at position {}?", name, index + 1))? {
+        eprintln!("Cancelled.");
+        return Ok(());
+    }
+
+    let tile = app_tile(&name, &app_path, bundle_id.as_deref());
+    persistent_apps_mut(&mut dock)?.insert(index, tile);
+    write_dock_plist(&dock)
+}
+
+fn dock_remove(app: &str, yes: bool) -> Result<()> {
+    let mut dock = read_dock_plist()?;
+    let items = dock_items(&dock);
+    let index = find_dock_item(&items, app)?;
+    let label = dock_item_label(&items[index]);
+
+    if !yes && !confirm(&format!("Unpin {} from the Dock?", label))? {
+        eprintln!("Cancelled.");
+        return Ok(());
+    }
+
+    persistent_apps_mut(&mut dock)?.remove(index);
+    write_dock_plist(&dock)
+}
+
+fn dock_move(app: &str, position: usize, yes: bool) -> Result<()> {
+    let mut dock = read_dock_plist()?;
+    let items = dock_items(&dock);
+    let from = find_dock_item(&items, app)?;
+    let to = position.clamp(1, items.len()) - 1;
+    let label = dock_item_label(&items[from]);
+
+    if from == to {
+        eprintln!("{} is already at position {}.", label, to + 1);
+        return Ok(());
+    }
+    if !yes && !confirm(&format!("Move {} from position {} to {}?", label, from + 1, to + 1))? {
+        eprintln!("Cancelled.");
+        return Ok(());
+    }
+
+    let apps = persistent_apps_mut(&mut dock)?;
+    let tile = apps.remove(from);
+    apps.insert(to, tile);
+    write_dock_plist(&dock)
+}
+
+// ============================================================================
+// Warp commands
+// ============================================================================
+
+fn warp_title() -> Result<()> {
+    // Get clipboard content
+    let output = Command::new("pbpaste")
+        .output()
+        .context("failed to run pbpaste")?;
+
+    let content = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Warp titles look like: "~/lang/rust - fish" or "/Users/nikiv/project - zsh"
+    // Extract the last path component before " - shell"
+
+    // Strip " - <shell>" suffix if present
+    let path_part = content
+        .split(" - ")
+        .next()
+        .unwrap_or(&content);
+
+    // Get last non-empty path component
+    let title = path_part
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .last()
+        .unwrap_or("")
+        .trim();
+
+    if title.is_empty() {
+        anyhow::bail!("could not extract title from clipboard");
+    }
+
+    // Put back in clipboard
+    let mut pbcopy = Command::new("pbcopy")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .context("failed to run pbcopy")?;
+
+    if let Some(stdin) = pbcopy.stdin.as_mut() {
+        use std::io::Write;
+        stdin.write_all(title.as_bytes())?;
+    }
+
+    pbcopy.wait()?;
+
+    println!("{}", title);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_app_skips_sibling_bundle_ids() {
+        let id = "com.google.Chrome";
+        for entry in [
+            "com.google.Chrome",
+            "com.google.Chrome.plist",
+            "com.google.Chrome.savedState",
+            "com.google.Chrome.binarycookies",
+        ] {
+            assert!(matches_app(entry, id, None, false), "{entry}");
+        }
+        for entry in [
+            "com.google.Chrome.canary",
+            "com.google.Chrome.canary.plist",
+            "com.google.ChromeHelper",
+            "Google",
+        ] {
+            assert!(!matches_app(entry, id, Some("Google Chrome"), true), "{entry}");
+        }
+        // Display names only count in folders where apps use them
+        assert!(matches_app("Google Chrome", id, Some("Google Chrome"), true));
+        assert!(!matches_app("Google Chrome", id, Some("Google Chrome"), false));
+    }
+
+    #[test]
+    fn plist_lookup_follows_dicts_and_arrays() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>CFBundleName</key>
+    <string>Zed</string>
+    <key>CFBundleURLTypes</key>
+    <array>
+        <dict>
+            <key>CFBundleURLSchemes</key>
+            <array><string>zed</string></array>
+        </dict>
+    </array>
+</dict>
+</plist>"#;
+        let plist = plist::Value::from_reader(std::io::Cursor::new(xml)).unwrap();
+        let lookup = |path| plist_lookup(&plist, path).map_err(|e| format!("{e:#}"));
+
+        let scheme = lookup("CFBundleURLTypes.0.CFBundleURLSchemes.0").unwrap();
+        assert_eq!(scheme.as_string(), Some("zed"));
+        assert_eq!(
+            lookup("Version").unwrap_err(),
+            "key 'Version' not found in the top level (keys: CFBundleName, CFBundleURLTypes)"
+        );
+        assert_eq!(
+            lookup("CFBundleURLTypes.0.Missing").unwrap_err(),
+            "key 'Missing' not found in 'CFBundleURLTypes.0' (keys: CFBundleURLSchemes)"
+        );
+        assert_eq!(
+            lookup("CFBundleURLTypes.first").unwrap_err(),
+            "'CFBundleURLTypes' is an array; 'first' is not an index: invalid digit found in string"
+        );
+        assert_eq!(
+            lookup("CFBundleURLTypes.3").unwrap_err(),
+            "index 3 out of range in 'CFBundleURLTypes' (1 items)"
+        );
+        assert_eq!(
+            lookup("CFBundleName.x").unwrap_err(),
+            "'CFBundleName' has no keys, so 'x' can't be looked up"
+        );
+    }
+
+    #[test]
+    fn base64_matches_rfc_4648_vectors() {
+        for (input, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64_encode(input.as_bytes()), encoded, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn durations_parse_with_units_and_reject_overflow() {
+        assert_eq!(parse_duration("45"), Ok(std::time::Duration::from_secs(45)));
+        assert_eq!(parse_duration(" 30m "), Ok(std::time::Duration::from_secs(1800)));
+        assert_eq!(parse_duration("2h"), Ok(std::time::Duration::from_secs(7200)));
+        assert_eq!(
+            parse_duration("0s"),
+            Err("duration must be greater than zero".to_string())
+        );
+        assert_eq!(
+            parse_duration("5d"),
+            Err("unknown unit 'd' in '5d' (use s, m or h)".to_string())
+        );
+        let huge = format!("{}h", u64::MAX / 60);
+        assert_eq!(parse_duration(&huge), Err(format!("duration '{}' is too long", huge)));
+    }
+
+    #[test]
+    fn airport_info_needs_a_known_key() {
+        let info = "\
+     agrCtlRSSI: -52
+    agrCtlNoise: -94
+          state: running
+     lastTxRate: 866
+      link auth: wpa2-psk
+          BSSID: a4:2b:b0:d1:4e:2c
+           SSID: Home Network 5G
+        channel: 149,80
+";
+        let status = parse_airport_info(info).unwrap();
+        assert_eq!(status.ssid.as_deref(), Some("Home Network 5G"));
+        assert_eq!(status.bssid.as_deref(), Some("a4:2b:b0:d1:4e:2c"));
+        assert_eq!((status.rssi_dbm, status.noise_dbm), (Some(-52), Some(-94)));
+        assert_eq!(status.channel.as_deref(), Some("149,80"));
+        assert_eq!(status.tx_rate_mbps, Some(866.0));
+        assert_eq!(status.security.as_deref(), Some("wpa2-psk"));
+
+        // What airport prints since macOS 14.4
+        let deprecated = "\
+WARNING: The airport command line tool is deprecated and will be removed in a future release.
+For diagnosing Wi-Fi related issues, use the Wireless Diagnostics app or wdutil command line tool.
+";
+        assert!(parse_airport_info(deprecated).is_none());
+    }
+
+    #[test]
+    fn wdutil_info_reads_only_the_wifi_section() {
+        let info = "\
+————————————————————————————————————————————————————————————————————
+NETWORK
+————————————————————————————————————————————————————————————————————
+    Primary IPv4         : en0 (Wi-Fi / 192.168.1.20)
+————————————————————————————————————————————————————————————————————
+WIFI
+————————————————————————————————————————————————————————————————————
+    MAC Address          : 3c:22:fb:00:00:00 (hw=3c:22:fb:00:00:00)
+    Interface Name       : en0
+    Power                : On [On]
+    SSID                 : None
+    BSSID                : None
+    RSSI                 : -58 dBm
+    Noise                : -92 dBm
+    Tx Rate              : 573.0 Mbps
+    Security             : WPA2 Personal
+    Channel              : 5g44/80
+————————————————————————————————————————————————————————————————————
+BLUETOOTH
+————————————————————————————————————————————————————————————————————
+    Power                : On
+";
+        let status = parse_wdutil_info(info).unwrap();
+        assert_eq!(status.interface.as_deref(), Some("en0"));
+        // Redacted without Location Services access
+        assert_eq!((status.ssid, status.bssid), (None, None));
+        assert_eq!((status.rssi_dbm, status.noise_dbm), (Some(-58), Some(-92)));
+        assert_eq!(status.tx_rate_mbps, Some(573.0));
+        assert_eq!(status.security.as_deref(), Some("WPA2 Personal"));
+        assert_eq!(status.channel.as_deref(), Some("5g44/80"));
+
+        assert!(parse_wdutil_info("Usage: sudo wdutil <command>\n").is_none());
+    }
+
+    #[test]
+    fn profiler_status_prefers_the_associated_interface() {
+        let json = serde_json::json!({
+            "SPAirPortDataType": [{
+                "spairport_airport_interfaces": [
+                    { "_name": "awdl0" },
+                    {
+                        "_name": "en0",
+                        "spairport_current_network_information": {
+                            "_name": "Home Network",
+                            "spairport_network_channel": "36 (5GHz, 80MHz)",
+                            "spairport_network_rate": 866,
+                            "spairport_security_mode": "spairport_security_mode_wpa2_personal",
+                            "spairport_signal_noise": "-55 dBm / -90 dBm"
+                        }
+                    }
+                ]
+            }]
+        });
+        let status = parse_profiler_status(&json).unwrap();
+        assert_eq!(status.interface.as_deref(), Some("en0"));
+        assert_eq!(status.ssid.as_deref(), Some("Home Network"));
+        assert_eq!((status.rssi_dbm, status.noise_dbm), (Some(-55), Some(-90)));
+        assert_eq!(status.channel.as_deref(), Some("36 (5GHz, 80MHz)"));
+        assert_eq!(status.tx_rate_mbps, Some(866.0));
+        assert_eq!(status.security.as_deref(), Some("wpa2 personal"));
+
+        assert!(parse_profiler_status(&serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn airport_scan_keeps_spaces_in_ssids() {
+        let scan = "\
+                            SSID BSSID             RSSI CHANNEL HT CC SECURITY (auth/unicast/group)
+                 Home Network 5G a4:2b:b0:d1:4e:2c -52  149,+1  Y  US WPA2(PSK/AES/AES)
+                           Guest 10:0c:6b:00:00:01 -71  6       Y  -- NONE
+";
+        let networks: Vec<_> = parse_airport_scan(scan)
+            .into_iter()
+            .map(|n| (n.ssid, n.rssi_dbm, n.channel, n.security))
+            .collect();
+        let network = |ssid: &str, rssi, channel: &str, security: &str| {
+            (ssid.to_string(), Some(rssi), Some(channel.to_string()), Some(security.to_string()))
+        };
+        assert_eq!(
+            networks,
+            [
+                network("Home Network 5G", -52, "149,+1", "WPA2(PSK/AES/AES)"),
+                network("Guest", -71, "6", "NONE"),
+            ]
+        );
+    }
+}
@@ -1,12 +1,31 @@
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+use std::sync::Mutex;
+use std::thread;
 use std::time::{Duration, SystemTime};
 
 use anyhow::{bail, Context, Result};
 use clap::{Args, Parser, Subcommand};
 use claude_code_sdk::{query, AssistantMessage, ClaudeCodeOptions, ContentBlock, Message, TextBlock};
-use serde::Serialize;
+use crossterm::{
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Row, Table, TableState},
+    Terminal,
+};
+use serde::{Deserialize, Serialize};
 use tokio_stream::StreamExt;
 use walkdir::WalkDir;
 
@@ -22,6 +41,7 @@ async fn try_main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
         Command::Suggest(args) => run_suggest(args).await,
+        Command::Archive(args) => run_archive(args),
     }
 }
 
@@ -36,6 +56,9 @@ struct Cli {
 enum Command {
     /// Suggest files and folders to delete or archive to free space.
     Suggest(SuggestArgs),
+    /// Pack files or folders into timestamped .tar.zst archives and remove
+    /// the originals once the archive checks out.
+    Archive(ArchiveArgs),
 }
 
 #[derive(Args)]
@@ -44,7 +67,8 @@ struct SuggestArgs {
     #[arg(long, value_name = "DIR")]
     root: Option<PathBuf>,
 
-    /// Minimum file size to include (e.g. 200MB, 1.5GB).
+    /// Minimum file size to include (e.g. 200MB, 1.5GB). Applies to the file
+    /// lists; smaller files still count towards their folder's total.
     #[arg(long, default_value = "200MB", value_name = "SIZE")]
     min_size: String,
 
@@ -64,10 +88,26 @@ struct SuggestArgs {
     #[arg(long, default_value_t = 30, value_name = "COUNT")]
     top_folders: usize,
 
+    /// Rank folders by total size or by number of files (count surfaces
+    /// trees of tiny files like node_modules and caches).
+    #[arg(long, value_enum, default_value_t = TopBy::Size, value_name = "METRIC")]
+    top_by: TopBy,
+
+    /// Files untouched for at least this many days are flagged as stale.
+    #[arg(long, default_value_t = 180, value_name = "DAYS")]
+    stale_days: u64,
+
     /// Bucket depth for folder aggregation (1 = immediate child of root).
     #[arg(long, default_value_t = 2, value_name = "LEVELS")]
     bucket_depth: usize,
 
+    /// Minimum total size for a folder bucket to be listed (defaults to
+    /// --min-size). Buckets sum every file below them, so a folder of many
+    /// files under --min-size can still clear it; raise it to hide small
+    /// deep folders. With --top-by count it only applies when given.
+    #[arg(long, value_name = "SIZE")]
+    bucket_min_size: Option<String>,
+
     /// Skip any path containing these substrings (repeatable).
     #[arg(long, value_name = "TEXT")]
     exclude: Vec<String>,
@@ -76,10 +116,36 @@ struct SuggestArgs {
     #[arg(long)]
     include_system: bool,
 
+    /// Follow symlinks while scanning (by default they're skipped and counted).
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Rescan everything instead of reusing folder sizes from the last run.
+    #[arg(long)]
+    no_cache: bool,
+
     /// Skip calling Claude and only print the local scan.
     #[arg(long)]
     no_claude: bool,
 
+    /// How to print the local scan; json prints the report Claude would see
+    /// to stdout and skips Claude.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text, conflicts_with_all = ["tui", "apply", "free_text"])]
+    format: ReportFormat,
+
+    /// Review candidates in an interactive table and trash the selected ones.
+    #[arg(long)]
+    tui: bool,
+
+    /// Print Claude's suggestions as prose instead of a structured table.
+    #[arg(long, conflicts_with = "apply")]
+    free_text: bool,
+
+    /// After Claude's suggestions, move the "delete" ones to ~/trash and pack
+    /// the "archive" ones into ~/archive (asks first).
+    #[arg(long)]
+    apply: bool,
+
     /// Claude model override.
     #[arg(long, value_name = "MODEL")]
     model: Option<String>,
@@ -89,30 +155,97 @@ struct SuggestArgs {
     system: Option<String>,
 }
 
+#[derive(Args)]
+struct ArchiveArgs {
+    /// Files or folders to archive.
+    #[arg(required = true, value_name = "PATH")]
+    paths: Vec<PathBuf>,
+
+    /// Directory the archives are written to (defaults to ~/archive).
+    #[arg(long, value_name = "DIR")]
+    to: Option<PathBuf>,
+
+    /// Show what would be archived without writing or removing anything.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TopBy {
+    Size,
+    Count,
+}
+
 #[derive(Serialize)]
 struct ScanReport {
     root: PathBuf,
     min_size_bytes: u64,
+    /// Folder buckets smaller than this are left out of top_folders.
+    bucket_min_size_bytes: u64,
     scanned_files: u64,
     scanned_dirs: u64,
     errors: u64,
+    /// Symlinks not followed (see --follow-symlinks).
+    skipped_symlinks: u64,
+    /// Files with more than one hard link; each inode is counted once.
+    hardlinked_files: u64,
+    /// Folders whose listing came from the size cache.
+    #[serde(skip)]
+    cached_dirs: u64,
+    stale_days: u64,
     top_files: Vec<FileEntry>,
+    stale_files: Vec<FileEntry>,
+    /// What top_folders is ranked by.
+    top_folders_by: TopBy,
+    /// Levels below the root that folders are bucketed at.
+    #[serde(skip)]
+    bucket_depth: usize,
     top_folders: Vec<FolderEntry>,
+    /// Every scanned file grouped by extension, largest first.
+    by_extension: Vec<ExtensionEntry>,
+    /// Well-known caches under the root that are safe to clear, largest first.
+    known_caches: Vec<CacheEntry>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct FileEntry {
     path: PathBuf,
     size_bytes: u64,
     modified_secs: Option<u64>,
+    /// More than 1 means deleting this path alone frees nothing.
+    #[serde(skip_serializing_if = "is_single_link")]
+    hard_links: u64,
+}
+
+fn is_single_link(links: &u64) -> bool {
+    *links <= 1
 }
 
 #[derive(Serialize)]
 struct FolderEntry {
     path: PathBuf,
     size_bytes: u64,
+    files: u64,
 }
 
+#[derive(Serialize)]
+struct ExtensionEntry {
+    /// Lowercased with a leading dot, or "(none)".
+    extension: String,
+    size_bytes: u64,
+    files: u64,
+}
+
+/// Rows kept in the by-extension breakdown.
+const TOP_EXTENSIONS: usize = 20;
+
 async fn run_suggest(args: SuggestArgs) -> Result<()> {
     let root = args
         .root
@@ -131,22 +264,46 @@ async fn run_suggest(args: SuggestArgs) -> Result<()> {
     }
 
     let min_size_bytes = parse_size(&args.min_size)?;
+    let bucket_min_size_bytes = args.bucket_min_size.as_deref().map(parse_size).transpose()?;
 
     let mut excludes = args.exclude.clone();
     if should_exclude_system(&root, args.include_system) {
         excludes.extend(system_excludes());
     }
 
-    let report = scan_root(
+    let cache_path = if args.no_cache {
+        None
+    } else {
+        size_cache_path(&root)
+    };
+
+    let mut report = scan_root(
         &root,
         min_size_bytes,
         args.max_depth,
         args.max_files,
         args.top_files,
         args.top_folders,
+        args.top_by,
         args.bucket_depth,
+        bucket_min_size_bytes,
+        args.stale_days,
         &excludes,
+        args.follow_symlinks,
+        cache_path.as_deref(),
     )?;
+    if let Some(home) = default_root() {
+        report.known_caches = scan_known_caches(&home, &root, &args.exclude);
+    }
+
+    if args.tui {
+        return run_review_tui(&report);
+    }
+
+    if args.format == ReportFormat::Json {
+        println!("{}", report_json(&report)?);
+        return Ok(());
+    }
 
     print_local_report(&report);
 
@@ -154,7 +311,7 @@ async fn run_suggest(args: SuggestArgs) -> Result<()> {
         return Ok(());
     }
 
-    let prompt = build_claude_prompt(&report)?;
+    let prompt = build_claude_prompt(&report, !args.free_text)?;
     let options = ClaudeCodeOptions {
         model: args.model.clone(),
         system_prompt: args.system.clone().or_else(|| Some(default_system_prompt())),
@@ -165,7 +322,31 @@ async fn run_suggest(args: SuggestArgs) -> Result<()> {
     println!("Claude suggestions:");
     println!("------------------");
 
-    run_claude(prompt, options).await?;
+    let output = run_claude(prompt, options).await?;
+
+    if args.free_text {
+        print!("{}", output);
+        if !output.ends_with('\n') {
+            println!();
+        }
+        return Ok(());
+    }
+
+    let suggestions = parse_suggestions(&output)?;
+    let (suggestions, rejected) = validate_suggestions(suggestions, &report.root);
+    print_suggestions(&suggestions, &report);
+
+    if !rejected.is_empty() {
+        println!();
+        println!("Ignored (not under {}):", report.root.display());
+        for (path, why) in &rejected {
+            println!("  {}  ({})", path.display(), why);
+        }
+    }
+
+    if args.apply {
+        apply_suggestions(&suggestions)?;
+    }
 
     Ok(())
 }
@@ -181,30 +362,52 @@ fn scan_root(
     max_files: Option<usize>,
     top_files: usize,
     top_folders: usize,
+    top_by: TopBy,
     bucket_depth: usize,
+    bucket_min_size_bytes: Option<u64>,
+    stale_days: u64,
     exclude: &[String],
+    follow_symlinks: bool,
+    cache_path: Option<&Path>,
 ) -> Result<ScanReport> {
-    let mut builder = WalkDir::new(root).follow_links(false);
+    if max_depth == Some(0) {
+        bail!("max-depth must be greater than zero");
+    }
+
+    let mut totals = Totals::new(root, bucket_depth, min_size_bytes);
+
+    // The cache folds small files into per-folder sums and can't stop mid-folder,
+    // so it only serves scans that never need those files individually.
+    let cache_path = cache_path
+        .filter(|_| !follow_symlinks && max_files.is_none() && min_size_bytes >= CACHE_FILE_FLOOR);
+    match cache_path {
+        Some(cache_path) => scan_cached(root, max_depth, exclude, cache_path, &mut totals),
+        None => scan_walkdir(root, max_depth, max_files, exclude, follow_symlinks, &mut totals),
+    }
+
+    Ok(totals.finish(stale_days, top_files, top_folders, top_by, bucket_min_size_bytes))
+}
+
+fn scan_walkdir(
+    root: &Path,
+    max_depth: Option<usize>,
+    max_files: Option<usize>,
+    exclude: &[String],
+    follow_symlinks: bool,
+    totals: &mut Totals,
+) {
+    let mut builder = WalkDir::new(root).follow_links(follow_symlinks);
     if let Some(depth) = max_depth {
-        if depth == 0 {
-            bail!("max-depth must be greater than zero");
-        }
         builder = builder.max_depth(depth);
     }
 
-    let mut files = Vec::new();
-    let mut folder_sizes: HashMap<PathBuf, u64> = HashMap::new();
-    let mut scanned_files = 0u64;
-    let mut scanned_dirs = 0u64;
-    let mut errors = 0u64;
-
     let mut iter = builder.into_iter();
 
     while let Some(entry) = iter.next() {
         let entry = match entry {
             Ok(entry) => entry,
             Err(err) => {
-                errors += 1;
+                totals.errors += 1;
                 eprintln!("Skipping entry: {err}");
                 continue;
             }
@@ -218,8 +421,13 @@ fn scan_root(
             continue;
         }
 
+        if entry.file_type().is_symlink() {
+            totals.skipped_symlinks += 1;
+            continue;
+        }
+
         if entry.file_type().is_dir() {
-            scanned_dirs += 1;
+            totals.scanned_dirs += 1;
             continue;
         }
 
@@ -227,9 +435,9 @@ fn scan_root(
             continue;
         }
 
-        scanned_files += 1;
+        totals.scanned_files += 1;
         if let Some(limit) = max_files {
-            if scanned_files > limit {
+            if totals.scanned_files > limit as u64 {
                 break;
             }
         }
@@ -237,69 +445,245 @@ fn scan_root(
         let metadata = match entry.metadata() {
             Ok(meta) => meta,
             Err(err) => {
-                errors += 1;
+                totals.errors += 1;
                 eprintln!("Skipping metadata for {}: {err}", path.display());
                 continue;
             }
         };
 
-        let size = metadata.len();
-        let bucket = bucket_path(root, path, bucket_depth);
-        *folder_sizes.entry(bucket).or_insert(0) += size;
+        let hard_links = metadata.nlink();
+        // A followed symlink can reach a file that was already counted, too
+        let inode = (hard_links > 1 || follow_symlinks).then(|| (metadata.dev(), metadata.ino()));
+        totals.add_file(
+            path,
+            metadata.len(),
+            modified_secs(&metadata),
+            hard_links,
+            inode,
+        );
+    }
+}
 
-        if size < min_size_bytes {
-            continue;
+/// Counters and size buckets shared by the walkdir scan and the cached scan.
+struct Totals<'a> {
+    root: &'a Path,
+    bucket_depth: usize,
+    min_size_bytes: u64,
+    scanned_files: u64,
+    scanned_dirs: u64,
+    cached_dirs: u64,
+    errors: u64,
+    skipped_symlinks: u64,
+    hardlinked_files: u64,
+    files: Vec<FileEntry>,
+    // bucket -> (bytes, files)
+    folder_sizes: HashMap<PathBuf, (u64, u64)>,
+    // extension -> (bytes, files)
+    extension_sizes: HashMap<String, (u64, u64)>,
+    // (device, inode) of multiply-linked files already counted
+    seen_inodes: HashSet<(u64, u64)>,
+}
+
+impl<'a> Totals<'a> {
+    fn new(root: &'a Path, bucket_depth: usize, min_size_bytes: u64) -> Self {
+        Self {
+            root,
+            bucket_depth,
+            min_size_bytes,
+            scanned_files: 0,
+            scanned_dirs: 0,
+            cached_dirs: 0,
+            errors: 0,
+            skipped_symlinks: 0,
+            hardlinked_files: 0,
+            files: Vec::new(),
+            folder_sizes: HashMap::new(),
+            extension_sizes: HashMap::new(),
+            seen_inodes: HashSet::new(),
+        }
+    }
+
+    /// Count a file towards its folder bucket and extension, and keep it if
+    /// it's large enough. `inode` is set when the file may have been seen before.
+    fn add_file(
+        &mut self,
+        path: &Path,
+        size: u64,
+        modified_secs: Option<u64>,
+        hard_links: u64,
+        inode: Option<(u64, u64)>,
+    ) {
+        if let Some(inode) = inode {
+            if !self.seen_inodes.insert(inode) {
+                return;
+            }
+            if hard_links > 1 {
+                self.hardlinked_files += 1;
+            }
         }
 
-        let modified_secs = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
+        let folder = path.parent().unwrap_or(path);
+        self.add_to_folder(folder, size, 1);
+        self.add_to_extension(extension_key(path), size, 1);
 
-        files.push(FileEntry {
+        if size < self.min_size_bytes {
+            return;
+        }
+
+        self.files.push(FileEntry {
             path: path.to_path_buf(),
             size_bytes: size,
             modified_secs,
+            hard_links,
         });
     }
 
-    files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
-    if files.len() > top_files {
-        files.truncate(top_files);
+    fn add_to_folder(&mut self, folder: &Path, bytes: u64, files: u64) {
+        let bucket = bucket_for_folder(self.root, folder, self.bucket_depth);
+        let entry = self.folder_sizes.entry(bucket).or_insert((0, 0));
+        entry.0 += bytes;
+        entry.1 += files;
     }
 
-    let mut folders: Vec<FolderEntry> = folder_sizes
-        .into_iter()
-        .filter(|(_, size)| *size >= min_size_bytes)
-        .map(|(path, size_bytes)| FolderEntry { path, size_bytes })
-        .collect();
+    fn add_to_extension(&mut self, extension: String, bytes: u64, files: u64) {
+        let entry = self.extension_sizes.entry(extension).or_insert((0, 0));
+        entry.0 += bytes;
+        entry.1 += files;
+    }
 
-    folders.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
-    if folders.len() > top_folders {
-        folders.truncate(top_folders);
+    fn add_listing(&mut self, dir: &Path, listing: &DirListing) {
+        self.scanned_files += listing.file_count;
+        self.skipped_symlinks += listing.symlinks;
+        // Small files are only summed in the listing; the rest go through add_file
+        let small_files = listing.file_count - listing.files.len() as u64;
+        self.add_to_folder(dir, listing.small_bytes, small_files);
+        for (extension, &(bytes, files)) in &listing.small_by_extension {
+            self.add_to_extension(extension.clone(), bytes, files);
+        }
+        for file in &listing.files {
+            let inode = (file.hard_links > 1).then_some((file.dev, file.ino));
+            self.add_file(
+                &dir.join(&file.name),
+                file.size,
+                file.modified_secs,
+                file.hard_links,
+                inode,
+            );
+        }
     }
 
-    Ok(ScanReport {
-        root: root.to_path_buf(),
-        min_size_bytes,
-        scanned_files,
-        scanned_dirs,
-        errors,
-        top_files: files,
-        top_folders: folders,
-    })
+    fn finish(
+        self,
+        stale_days: u64,
+        top_files: usize,
+        top_folders: usize,
+        top_by: TopBy,
+        bucket_min_size_bytes: Option<u64>,
+    ) -> ScanReport {
+        let mut files = self.files;
+        files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+
+        // Large files nobody has touched in a while, still sorted by size
+        let mut stale_files: Vec<FileEntry> = files
+            .iter()
+            .filter(|f| age_days(f.modified_secs).is_some_and(|days| days >= stale_days))
+            .cloned()
+            .collect();
+        if stale_files.len() > top_files {
+            stale_files.truncate(top_files);
+        }
+
+        if files.len() > top_files {
+            files.truncate(top_files);
+        }
+
+        let min_size_bytes = self.min_size_bytes;
+        // Ranked by count, a folder of many small files matters however small
+        // it is, unless a bucket threshold was asked for
+        let bucket_min_size_bytes = bucket_min_size_bytes.unwrap_or(match top_by {
+            TopBy::Size => min_size_bytes,
+            TopBy::Count => 0,
+        });
+        let mut folders: Vec<FolderEntry> = self
+            .folder_sizes
+            .into_iter()
+            .filter(|(_, (size, files))| *files > 0 && *size >= bucket_min_size_bytes)
+            .map(|(path, (size_bytes, files))| FolderEntry {
+                path,
+                size_bytes,
+                files,
+            })
+            .collect();
+
+        folders.sort_by(|a, b| match top_by {
+            TopBy::Size => b.size_bytes.cmp(&a.size_bytes).then(b.files.cmp(&a.files)),
+            TopBy::Count => b.files.cmp(&a.files).then(b.size_bytes.cmp(&a.size_bytes)),
+        });
+        if folders.len() > top_folders {
+            folders.truncate(top_folders);
+        }
+
+        let mut by_extension: Vec<ExtensionEntry> = self
+            .extension_sizes
+            .into_iter()
+            .map(|(extension, (size_bytes, files))| ExtensionEntry {
+                extension,
+                size_bytes,
+                files,
+            })
+            .collect();
+        by_extension.sort_by(|a, b| {
+            b.size_bytes
+                .cmp(&a.size_bytes)
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+        by_extension.truncate(TOP_EXTENSIONS);
+
+        ScanReport {
+            root: self.root.to_path_buf(),
+            min_size_bytes,
+            bucket_min_size_bytes,
+            scanned_files: self.scanned_files,
+            scanned_dirs: self.scanned_dirs,
+            cached_dirs: self.cached_dirs,
+            errors: self.errors,
+            skipped_symlinks: self.skipped_symlinks,
+            hardlinked_files: self.hardlinked_files,
+            stale_days,
+            top_files: files,
+            stale_files,
+            top_folders_by: top_by,
+            bucket_depth: self.bucket_depth,
+            top_folders: folders,
+            by_extension,
+            known_caches: Vec::new(),
+        }
+    }
+}
+
+/// ".ext", lowercased, or "(none)".
+fn extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
 }
 
-fn bucket_path(root: &Path, path: &Path, depth: usize) -> PathBuf {
+fn bucket_for_folder(root: &Path, folder: &Path, depth: usize) -> PathBuf {
     if depth == 0 {
         return root.to_path_buf();
     }
 
     let root_count = root.components().count();
-    let base = path.parent().unwrap_or(path);
     let mut buf = PathBuf::new();
-    for (idx, component) in base.components().enumerate() {
+    for (idx, component) in folder.components().enumerate() {
         buf.push(component.as_os_str());
         if idx + 1 >= root_count + depth {
             break;
@@ -324,11 +708,37 @@ fn is_excluded(path: &Path, excludes: &[String]) -> bool {
 
 fn print_local_report(report: &ScanReport) {
     println!("Scan root: {}", report.root.display());
-    println!("Min size: {}", format_size(report.min_size_bytes));
+    if report.bucket_min_size_bytes == report.min_size_bytes {
+        println!("Min size: {}", format_size(report.min_size_bytes));
+    } else {
+        println!(
+            "Min size: {} (folders: {})",
+            format_size(report.min_size_bytes),
+            format_size(report.bucket_min_size_bytes)
+        );
+    }
     println!(
         "Scanned {} files, {} dirs, {} errors",
         report.scanned_files, report.scanned_dirs, report.errors
     );
+    if report.skipped_symlinks > 0 {
+        println!(
+            "Skipped {} symlinks (use --follow-symlinks to follow them)",
+            report.skipped_symlinks
+        );
+    }
+    if report.hardlinked_files > 0 {
+        println!(
+            "{} files have multiple hard links; each is counted once",
+            report.hardlinked_files
+        );
+    }
+    if report.cached_dirs > 0 {
+        println!(
+            "Reused cached sizes for {} of {} folders (--no-cache to rescan)",
+            report.cached_dirs, report.scanned_dirs
+        );
+    }
 
     println!();
     println!("Largest files:");
@@ -338,52 +748,882 @@ fn print_local_report(report: &ScanReport) {
         for entry in &report.top_files {
             let age = format_age(entry.modified_secs);
             println!(
-                "  {:>10}  {:>8}  {}",
+                "  {:>10}  {:>10}  {}{}",
                 format_size(entry.size_bytes),
                 age,
-                entry.path.display()
+                entry.path.display(),
+                link_note(entry.hard_links)
             );
         }
     }
 
     println!();
-    println!("Largest folders (approx):");
+    println!("Stale & large (untouched {}+ days):", report.stale_days);
+    if report.stale_files.is_empty() {
+        println!("  (none)");
+    } else {
+        for entry in &report.stale_files {
+            println!(
+                "  {:>10}  {:>10}  score {:>6.1}  {}{}",
+                format_size(entry.size_bytes),
+                format_age(entry.modified_secs),
+                stale_score(entry),
+                entry.path.display(),
+                link_note(entry.hard_links)
+            );
+        }
+    }
+
+    println!();
+    match report.top_folders_by {
+        TopBy::Size => println!("Largest folders (approx):"),
+        TopBy::Count => println!("Folders with the most files (approx):"),
+    }
     if report.top_folders.is_empty() {
         println!("  (none above threshold)");
     } else {
         for entry in &report.top_folders {
-            println!("  {:>10}  {}", format_size(entry.size_bytes), entry.path.display());
+            let size = format_size(entry.size_bytes);
+            let noun = if entry.files == 1 { "file" } else { "files" };
+            let files = format!("{} {}", entry.files, noun);
+            let (first, second) = match report.top_folders_by {
+                TopBy::Size => (size, files),
+                TopBy::Count => (files, size),
+            };
+            println!("  {:>12}  {:>12}  {}", first, second, entry.path.display());
+        }
+    }
+
+    print_known_caches(&report.known_caches);
+
+    println!();
+    println!("By type:");
+    if report.by_extension.is_empty() {
+        println!("  (no files)");
+    } else {
+        for entry in &report.by_extension {
+            println!(
+                "  {:>10}  {:>6} {}  {}",
+                format_size(entry.size_bytes),
+                entry.files,
+                if entry.files == 1 { "file " } else { "files" },
+                entry.extension
+            );
         }
     }
 }
 
-fn format_age(modified_secs: Option<u64>) -> String {
-    let Some(modified) = modified_secs else {
-        return "unknown".to_string();
+fn link_note(hard_links: u64) -> String {
+    if hard_links > 1 {
+        format!("  ({} hard links: deleting this copy frees nothing)", hard_links)
+    } else {
+        String::new()
+    }
+}
+
+// ============================================================================
+// Known caches
+// ============================================================================
+
+/// Cache folders (relative to HOME) that tools rebuild or re-download on
+/// demand, with what clearing each one costs.
+/// Docker's VM disk and ~/.docker hold images, volumes and credentials, so
+/// they don't belong here even though they grow like caches.
+const KNOWN_CACHES: &[(&str, &str, &str)] = &[
+    (
+        "Library/Caches",
+        "macOS app caches",
+        "apps rebuild these; quit them first",
+    ),
+    (
+        ".cache",
+        "XDG caches",
+        "pip, huggingface etc.; downloaded models are fetched again",
+    ),
+    (
+        "Library/Developer/Xcode/DerivedData",
+        "Xcode DerivedData",
+        "build products and indexes; rebuilt on the next build",
+    ),
+    (".npm/_cacache", "npm cache", "`npm cache clean --force`"),
+    ("Library/Caches/Yarn", "Yarn cache", "`yarn cache clean`"),
+    (".cache/yarn", "Yarn cache", "`yarn cache clean`"),
+    (".yarn/berry/cache", "Yarn Berry cache", "`yarn cache clean --all`"),
+    ("Library/pnpm/store", "pnpm store", "`pnpm store prune` keeps what projects use"),
+    (".local/share/pnpm/store", "pnpm store", "`pnpm store prune` keeps what projects use"),
+    (
+        ".cargo/registry/cache",
+        "Cargo crate downloads",
+        ".crate files; re-downloaded when a build needs them",
+    ),
+];
+
+#[derive(Serialize)]
+struct CacheEntry {
+    name: &'static str,
+    path: PathBuf,
+    /// Space on disk, which is what clearing it frees.
+    size_bytes: u64,
+    files: u64,
+    note: &'static str,
+    /// Another listed cache this one is inside (already counted in its size).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    within: Option<PathBuf>,
+}
+
+/// Size every `KNOWN_CACHES` folder under `home` that exists and lies within
+/// `root`. Caches inside another listed one are kept but marked `within`, so
+/// they aren't counted twice.
+fn scan_known_caches(home: &Path, root: &Path, exclude: &[String]) -> Vec<CacheEntry> {
+    let home = home.canonicalize().unwrap_or_else(|_| home.to_path_buf());
+    let mut caches: Vec<CacheEntry> = KNOWN_CACHES
+        .iter()
+        .map(|(rel, name, note)| (home.join(rel), *name, *note))
+        .filter(|(path, _, _)| path.starts_with(root) && !is_excluded(path, exclude))
+        .filter(|(path, _, _)| path.is_dir())
+        .map(|(path, name, note)| {
+            let (size_bytes, files) = disk_usage(&path, exclude);
+            CacheEntry {
+                name,
+                path,
+                size_bytes,
+                files,
+                note,
+                within: None,
+            }
+        })
+        .filter(|cache| cache.size_bytes > 0)
+        .collect();
+
+    let paths: Vec<PathBuf> = caches.iter().map(|c| c.path.clone()).collect();
+    for cache in &mut caches {
+        cache.within = paths
+            .iter()
+            .find(|outer| **outer != cache.path && cache.path.starts_with(outer))
+            .cloned();
+    }
+    caches.sort_by_key(|c| std::cmp::Reverse(c.size_bytes));
+    caches
+}
+
+/// Allocated bytes and file count under `path`, without following symlinks.
+/// Allocated rather than apparent size, since VM disks are sparse.
+fn disk_usage(path: &Path, exclude: &[String]) -> (u64, u64) {
+    let mut seen = HashSet::new();
+    let mut bytes = 0;
+    let mut files = 0;
+    let walk = WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| !is_excluded(e.path(), exclude))
+        .flatten();
+    for entry in walk {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.nlink() > 1 && !seen.insert((metadata.dev(), metadata.ino())) {
+            continue;
+        }
+        bytes += metadata.blocks() * 512;
+        files += 1;
+    }
+    (bytes, files)
+}
+
+fn print_known_caches(caches: &[CacheEntry]) {
+    if caches.is_empty() {
+        return;
+    }
+    let total: u64 = caches
+        .iter()
+        .filter(|c| c.within.is_none())
+        .map(|c| c.size_bytes)
+        .sum();
+
+    println!();
+    println!("Safe to clear (known caches, {} total):", format_size(total));
+    for cache in caches {
+        let within = cache
+            .within
+            .as_ref()
+            .map(|outer| format!(" (inside {})", outer.display()))
+            .unwrap_or_default();
+        println!(
+            "  {:>10}  {}  {}{}",
+            format_size(cache.size_bytes),
+            cache.name,
+            cache.path.display(),
+            within
+        );
+        println!("  {:>10}  {}", "", cache.note);
+    }
+}
+
+// ============================================================================
+// Size cache
+// ============================================================================
+
+const SIZE_CACHE_VERSION: u32 = 2;
+
+/// Files below this are folded into their folder's sum in the cache, so
+/// scans with a smaller --min-size bypass it.
+const CACHE_FILE_FLOOR: u64 = 1024 * 1024;
+
+/// Folder listings from the previous scan of a root, keyed by path. A
+/// listing is reused while the folder's (device, inode, mtime) is unchanged:
+/// adding, removing or renaming an entry bumps the folder's mtime, so its
+/// small files don't need to be listed and stat'ed again. Large files are
+/// re-stat'ed on reuse, since one rewritten in place doesn't touch its folder.
+#[derive(Serialize, Deserialize)]
+struct SizeCache {
+    version: u32,
+    /// Exclusions change what a listing contains, so they're part of the key.
+    exclude: Vec<String>,
+    dirs: HashMap<String, DirListing>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct DirListing {
+    dev: u64,
+    ino: u64,
+    mtime: (i64, i64),
+    file_count: u64,
+    /// Summed size of single-link files under CACHE_FILE_FLOOR.
+    small_bytes: u64,
+    /// The same files' (bytes, files) by extension.
+    small_by_extension: HashMap<String, (u64, u64)>,
+    /// Everything else: large files and all multiply-linked ones.
+    files: Vec<ListedFile>,
+    subdirs: Vec<PathBuf>,
+    symlinks: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ListedFile {
+    name: PathBuf,
+    size: u64,
+    modified_secs: Option<u64>,
+    hard_links: u64,
+    dev: u64,
+    ino: u64,
+}
+
+impl DirListing {
+    /// Names that aren't UTF-8 can't round-trip through JSON.
+    fn is_cacheable(&self) -> bool {
+        self.subdirs.iter().all(|name| name.to_str().is_some())
+            && self.files.iter().all(|file| file.name.to_str().is_some())
+    }
+}
+
+fn size_cache_path(root: &Path) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    let home = default_root()?;
+    Some(
+        home.join(".cache")
+            .join("move")
+            .join(format!("sizes-{:016x}.json", hasher.finish())),
+    )
+}
+
+fn load_size_cache(path: &Path, exclude: &[String]) -> SizeCache {
+    let cached = fs::read(path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<SizeCache>(&raw).ok());
+    match cached {
+        Some(cache) if cache.version == SIZE_CACHE_VERSION && cache.exclude == exclude => cache,
+        _ => SizeCache {
+            version: SIZE_CACHE_VERSION,
+            exclude: exclude.to_vec(),
+            dirs: HashMap::new(),
+        },
+    }
+}
+
+fn save_size_cache(path: &Path, cache: &SizeCache) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let raw = serde_json::to_vec(cache)?;
+    fs::write(&tmp, raw).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+/// Walk `root` one folder at a time, reusing cached listings where the folder
+/// hasn't changed. The root's subfolders are spread across worker threads.
+fn scan_cached(
+    root: &Path,
+    max_depth: Option<usize>,
+    exclude: &[String],
+    cache_path: &Path,
+    totals: &mut Totals,
+) {
+    let cache = load_size_cache(cache_path, exclude);
+    let walker = CachedWalk {
+        cache: &cache,
+        exclude,
+        max_depth,
+    };
+
+    let mut out = WalkOutput::default();
+    if !is_excluded(root, exclude) {
+        walker.walk_root(root, &mut out);
+    }
+
+    totals.scanned_dirs += out.scanned_dirs;
+    totals.errors += out.errors;
+    for (dir, listing, reused) in &out.listings {
+        if *reused {
+            totals.cached_dirs += 1;
+        }
+        totals.add_listing(dir, listing);
+    }
+
+    // A depth-limited scan only saw part of the tree; keep the rest for later
+    let mut dirs = if max_depth.is_some() {
+        cache.dirs
+    } else {
+        HashMap::new()
+    };
+    for (dir, listing, _) in out.listings {
+        if listing.is_cacheable() {
+            dirs.insert(dir.to_string_lossy().into_owned(), listing);
+        }
+    }
+    let cache = SizeCache {
+        version: SIZE_CACHE_VERSION,
+        exclude: exclude.to_vec(),
+        dirs,
     };
+    if let Err(err) = save_size_cache(cache_path, &cache) {
+        eprintln!("Skipping size cache update: {err:#}");
+    }
+}
+
+struct CachedWalk<'a> {
+    cache: &'a SizeCache,
+    exclude: &'a [String],
+    max_depth: Option<usize>,
+}
+
+#[derive(Default)]
+struct WalkOutput {
+    /// Each folder's listing and whether it came from the cache.
+    listings: Vec<(PathBuf, DirListing, bool)>,
+    scanned_dirs: u64,
+    errors: u64,
+}
+
+impl CachedWalk<'_> {
+    fn walk_root(&self, root: &Path, out: &mut WalkOutput) {
+        out.scanned_dirs += 1;
+        let Some((listing, reused)) = self.listing(root, out) else {
+            return;
+        };
+        let queue: Mutex<Vec<PathBuf>> =
+            Mutex::new(listing.subdirs.iter().map(|name| root.join(name)).collect());
+        out.listings.push((root.to_path_buf(), listing, reused));
+
+        let workers = thread::available_parallelism()
+            .map_or(4, |n| n.get())
+            .min(queue.lock().unwrap().len());
+        let results: Vec<WalkOutput> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut out = WalkOutput::default();
+                        loop {
+                            let next = queue.lock().unwrap().pop();
+                            let Some(dir) = next else { break };
+                            self.walk(&dir, 1, &mut out);
+                        }
+                        out
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("scan worker panicked"))
+                .collect()
+        });
+
+        for result in results {
+            out.listings.extend(result.listings);
+            out.scanned_dirs += result.scanned_dirs;
+            out.errors += result.errors;
+        }
+    }
+
+    fn walk(&self, dir: &Path, depth: usize, out: &mut WalkOutput) {
+        out.scanned_dirs += 1;
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+        let Some((listing, reused)) = self.listing(dir, out) else {
+            return;
+        };
+        for name in &listing.subdirs {
+            self.walk(&dir.join(name), depth + 1, out);
+        }
+        out.listings.push((dir.to_path_buf(), listing, reused));
+    }
+
+    /// The cached listing for `dir` if it's still current, otherwise a fresh one.
+    fn listing(&self, dir: &Path, out: &mut WalkOutput) -> Option<(DirListing, bool)> {
+        let meta = match fs::symlink_metadata(dir) {
+            Ok(meta) => meta,
+            Err(err) => {
+                out.errors += 1;
+                eprintln!("Skipping metadata for {}: {err}", dir.display());
+                return None;
+            }
+        };
+        let key = (meta.dev(), meta.ino(), (meta.mtime(), meta.mtime_nsec()));
+
+        if let Some(cached) = self.cache.dirs.get(dir.to_string_lossy().as_ref()) {
+            if (cached.dev, cached.ino, cached.mtime) == key {
+                if let Some(listing) = restat_files(dir, cached) {
+                    return Some((listing, true));
+                }
+            }
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                out.errors += 1;
+                eprintln!("Skipping entry: {}: {err}", dir.display());
+                return None;
+            }
+        };
 
+        let mut listing = DirListing {
+            dev: key.0,
+            ino: key.1,
+            mtime: key.2,
+            file_count: 0,
+            small_bytes: 0,
+            small_by_extension: HashMap::new(),
+            files: Vec::new(),
+            subdirs: Vec::new(),
+            symlinks: 0,
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    out.errors += 1;
+                    eprintln!("Skipping entry: {err}");
+                    continue;
+                }
+            };
+            let path = entry.path();
+            if is_excluded(&path, self.exclude) {
+                continue;
+            }
+            let metadata = match fs::symlink_metadata(&path) {
+                Ok(meta) => meta,
+                Err(err) => {
+                    out.errors += 1;
+                    eprintln!("Skipping metadata for {}: {err}", path.display());
+                    continue;
+                }
+            };
+
+            let file_type = metadata.file_type();
+            if file_type.is_symlink() {
+                listing.symlinks += 1;
+            } else if file_type.is_dir() {
+                listing.subdirs.push(PathBuf::from(entry.file_name()));
+            } else if file_type.is_file() {
+                listing.file_count += 1;
+                let size = metadata.len();
+                let hard_links = metadata.nlink();
+                if hard_links <= 1 && size < CACHE_FILE_FLOOR {
+                    listing.small_bytes += size;
+                    let extension = extension_key(Path::new(&entry.file_name()));
+                    let by_extension =
+                        listing.small_by_extension.entry(extension).or_insert((0, 0));
+                    by_extension.0 += size;
+                    by_extension.1 += 1;
+                } else {
+                    listing.files.push(ListedFile {
+                        name: PathBuf::from(entry.file_name()),
+                        size,
+                        modified_secs: modified_secs(&metadata),
+                        hard_links,
+                        dev: metadata.dev(),
+                        ino: metadata.ino(),
+                    });
+                }
+            }
+        }
+
+        Some((listing, false))
+    }
+}
+
+/// `cached` with its large files' sizes re-read. A file that grows in place
+/// (disk images, VM volumes, logs) leaves its folder's mtime alone, so the
+/// listing alone would report it at its old size. Small files stay summed;
+/// None if a listed file is gone or replaced, to re-read the folder.
+fn restat_files(dir: &Path, cached: &DirListing) -> Option<DirListing> {
+    let mut listing = cached.clone();
+    for file in &mut listing.files {
+        let meta = fs::symlink_metadata(dir.join(&file.name)).ok()?;
+        if !meta.is_file() || (meta.dev(), meta.ino()) != (file.dev, file.ino) {
+            return None;
+        }
+        file.size = meta.len();
+        file.modified_secs = modified_secs(&meta);
+        file.hard_links = meta.nlink();
+    }
+    Some(listing)
+}
+
+// ============================================================================
+// Review TUI
+// ============================================================================
+
+struct Candidate {
+    path: PathBuf,
+    size_bytes: u64,
+    is_dir: bool,
+    hard_links: u64,
+    selected: bool,
+}
+
+impl Candidate {
+    /// Trashing one name of a hard-linked file frees nothing.
+    fn reclaimable_bytes(&self) -> u64 {
+        if self.hard_links > 1 {
+            0
+        } else {
+            self.size_bytes
+        }
+    }
+}
+
+fn review_candidates(report: &ScanReport) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = report
+        .top_files
+        .iter()
+        .map(|f| Candidate {
+            path: f.path.clone(),
+            size_bytes: f.size_bytes,
+            is_dir: false,
+            hard_links: f.hard_links,
+            selected: false,
+        })
+        .chain(
+            report
+                .top_folders
+                .iter()
+                .filter(|f| is_whole_subtree(report, &f.path))
+                .map(|f| Candidate {
+                    path: f.path.clone(),
+                    size_bytes: f.size_bytes,
+                    is_dir: true,
+                    hard_links: 1,
+                    selected: false,
+                }),
+        )
+        .collect();
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.size_bytes));
+    candidates
+}
+
+/// Whether a folder bucket's size covers everything under it. Buckets above
+/// --bucket-depth (the root included) only count the files directly inside,
+/// yet trashing one would move its whole subtree.
+fn is_whole_subtree(report: &ScanReport, folder: &Path) -> bool {
+    folder != report.root
+        && folder.components().count() == report.root.components().count() + report.bucket_depth
+}
+
+/// Bytes freed by trashing the selection. Entries inside a selected folder
+/// are skipped so they aren't counted twice.
+fn selected_reclaimable(candidates: &[Candidate]) -> u64 {
+    let folders: Vec<&Path> = candidates
+        .iter()
+        .filter(|c| c.selected && c.is_dir)
+        .map(|c| c.path.as_path())
+        .collect();
+    candidates
+        .iter()
+        .filter(|c| c.selected)
+        .filter(|c| !folders.iter().any(|f| c.path != *f && c.path.starts_with(f)))
+        .map(Candidate::reclaimable_bytes)
+        .sum()
+}
+
+/// Move a path into ~/trash (same convention as the `trash` CLI). Only a
+/// rename is attempted: copying across volumes wouldn't free any space.
+fn trash_path(path: &Path) -> Result<PathBuf> {
+    let home = env::var("HOME").context("HOME not set")?;
+    let trash_dir = PathBuf::from(home).join("trash");
+    fs::create_dir_all(&trash_dir)
+        .with_context(|| format!("Unable to create {}", trash_dir.display()))?;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut dest = trash_dir.join(name.as_ref());
+    let mut i = 2;
+    while dest.exists() {
+        dest = trash_dir.join(format!("{}.{}", name, i));
+        i += 1;
+    }
+
+    fs::rename(path, &dest).map_err(|err| {
+        if err.raw_os_error() == Some(18) {
+            anyhow::anyhow!("{} is on a different volume than ~/trash", path.display())
+        } else {
+            anyhow::anyhow!("{}: {}", path.display(), err)
+        }
+    })?;
+    Ok(dest)
+}
+
+fn run_review_tui(report: &ScanReport) -> Result<()> {
+    let mut candidates = review_candidates(report);
+    if candidates.is_empty() {
+        println!("Nothing above {} to review.", format_size(report.min_size_bytes));
+        return Ok(());
+    }
+
+    enable_raw_mode().context("failed to enable raw mode")?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen).context("failed to enter alt screen")?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
+
+    struct TuiGuard;
+    impl Drop for TuiGuard {
+        fn drop(&mut self) {
+            let _ = disable_raw_mode();
+            let mut stdout = std::io::stdout();
+            let _ = execute!(stdout, LeaveAlternateScreen);
+        }
+    }
+    let _guard = TuiGuard;
+
+    let mut state = TableState::default();
+    state.select(Some(0));
+    let mut confirming = false;
+    let mut status = String::new();
+    let mut trashed_bytes: u64 = 0;
+
+    loop {
+        let reclaimable = selected_reclaimable(&candidates);
+        let selected_count = candidates.iter().filter(|c| c.selected).count();
+
+        terminal
+            .draw(|f| {
+                let area = f.size();
+                let layout = Layout::vertical([Constraint::Min(3), Constraint::Length(3)]);
+                let chunks = layout.split(area);
+
+                let rows = candidates
+                    .iter()
+                    .map(|c| {
+                        Row::new(vec![
+                            if c.selected { "[x]" } else { "[ ]" }.to_string(),
+                            format_size(c.size_bytes),
+                            match (c.is_dir, c.hard_links > 1) {
+                                (true, _) => "dir",
+                                (false, true) => "link",
+                                (false, false) => "file",
+                            }
+                            .to_string(),
+                            c.path.display().to_string(),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+
+                let table = Table::new(
+                    rows,
+                    [
+                        Constraint::Length(3),
+                        Constraint::Length(10),
+                        Constraint::Length(4),
+                        Constraint::Min(10),
+                    ],
+                )
+                .header(
+                    Row::new(vec!["", "SIZE", "KIND", "PATH"])
+                        .style(Style::default().add_modifier(Modifier::BOLD)),
+                )
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    Block::default()
+                        .title(format!(
+                            "Cleanup candidates in {} ({} selected, {} reclaimable)",
+                            report.root.display(),
+                            selected_count,
+                            format_size(reclaimable)
+                        ))
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Plain),
+                );
+
+                f.render_stateful_widget(table, chunks[0], &mut state);
+
+                let footer = if confirming {
+                    format!(
+                        "Move {} item(s) ({}) to ~/trash? y: yes  n: no",
+                        selected_count,
+                        format_size(reclaimable)
+                    )
+                } else if !status.is_empty() {
+                    status.clone()
+                } else {
+                    "space: toggle  enter: trash selected  q: quit".to_string()
+                };
+                let footer = Block::default()
+                    .title(footer)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain);
+                f.render_widget(footer, chunks[1]);
+            })
+            .context("failed to draw UI")?;
+
+        let Event::Key(key) = event::read().context("failed to read event")? else {
+            continue;
+        };
+
+        if confirming {
+            confirming = false;
+            if key.code != KeyCode::Char('y') {
+                status = "Cancelled".to_string();
+                continue;
+            }
+
+            let mut trashed = 0;
+            let mut failures = Vec::new();
+            let mut freed = 0;
+            let mut i = 0;
+            while i < candidates.len() {
+                let c = &candidates[i];
+                if !c.selected {
+                    i += 1;
+                    continue;
+                }
+                // Already gone with a trashed parent folder (folders sort
+                // first as they're larger, so nothing is counted twice)
+                if !c.path.exists() {
+                    candidates.remove(i);
+                    continue;
+                }
+                match trash_path(&c.path) {
+                    Ok(_) => {
+                        trashed += 1;
+                        freed += c.reclaimable_bytes();
+                        candidates.remove(i);
+                    }
+                    Err(err) => {
+                        failures.push(err.to_string());
+                        i += 1;
+                    }
+                }
+            }
+            trashed_bytes += freed;
+
+            status = match failures.first() {
+                None => format!(
+                    "Trashed {} item(s), {} total this session",
+                    trashed,
+                    format_size(trashed_bytes)
+                ),
+                Some(first) => format!(
+                    "Trashed {} item(s), {} failed (e.g. {})",
+                    trashed,
+                    failures.len(),
+                    first
+                ),
+            };
+
+            if candidates.is_empty() {
+                break;
+            }
+            let selected = state.selected().unwrap_or(0).min(candidates.len() - 1);
+            state.select(Some(selected));
+            continue;
+        }
+
+        let selected = state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.select(Some((selected + 1).min(candidates.len() - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(c) = candidates.get_mut(selected) {
+                    c.selected = !c.selected;
+                }
+                status.clear();
+            }
+            KeyCode::Enter => {
+                if selected_count > 0 {
+                    confirming = true;
+                } else {
+                    status = "Nothing selected (space to toggle)".to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    drop(_guard);
+    if trashed_bytes > 0 {
+        println!("Moved {} to ~/trash", format_size(trashed_bytes));
+    }
+    Ok(())
+}
+
+fn age_secs(modified_secs: Option<u64>) -> Option<u64> {
+    let modified = modified_secs?;
     let now = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or(Duration::from_secs(0))
         .as_secs();
+    Some(now.saturating_sub(modified))
+}
 
-    if modified > now {
-        return "0d".to_string();
-    }
+fn age_days(modified_secs: Option<u64>) -> Option<u64> {
+    age_secs(modified_secs).map(|secs| secs / 86_400)
+}
+
+/// Deletion-candidate score: size in GB weighted by years untouched.
+fn stale_score(entry: &FileEntry) -> f64 {
+    let gb = entry.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    let years = age_days(entry.modified_secs).unwrap_or(0) as f64 / 365.0;
+    gb * years
+}
+
+fn format_age(modified_secs: Option<u64>) -> String {
+    let Some(age_secs) = age_secs(modified_secs) else {
+        return "unknown".to_string();
+    };
 
-    let age_secs = now - modified;
     let days = age_secs / 86_400;
     if days > 0 {
-        return format!("{}d", days);
+        return format!("{}d ago", days);
     }
 
     let hours = age_secs / 3_600;
     if hours > 0 {
-        return format!("{}h", hours);
+        return format!("{}h ago", hours);
     }
 
     let minutes = age_secs / 60;
-    format!("{}m", minutes)
+    format!("{}m ago", minutes)
 }
 
 fn format_size(bytes: u64) -> String {
@@ -442,10 +1682,20 @@ fn parse_size(input: &str) -> Result<u64> {
     Ok((value * multiplier) as u64)
 }
 
-fn build_claude_prompt(report: &ScanReport) -> Result<String> {
-    let payload = serde_json::to_string_pretty(report)?;
+/// The report as `--format json` prints it and Claude receives it.
+fn report_json(report: &ScanReport) -> Result<String> {
+    Ok(serde_json::to_string_pretty(report)?)
+}
+
+fn build_claude_prompt(report: &ScanReport, structured: bool) -> Result<String> {
+    let payload = report_json(report)?;
+    if structured {
+        return Ok(format!(
+            "Here is a macOS disk usage scan summary. Suggest what the user can archive or delete to free space.\n\nRules:\n- Only use paths provided.\n- Avoid suggesting deletes for system-critical paths (/System, /Library, /Applications).\n- Prefer archiving older large files and deleting caches/build artifacts if safe.\n- Provide concise reasons.\n- Leave out paths that should be kept.\n- Files with hard_links > 1 have other names; deleting one frees no space.\n- known_caches are caches tools rebuild on demand; follow each note (some should be cleared with the tool's own command).\n\nRespond with ONLY a JSON array, no other text:\n[{{\"path\": \"/abs/path\", \"action\": \"delete\" or \"archive\", \"reason\": \"...\"}}]\n\nScan summary JSON:\n{payload}\n"
+        ));
+    }
     Ok(format!(
-        "Here is a macOS disk usage scan summary. Suggest what the user can archive or delete to free space.\n\nRules:\n- Only use paths provided.\n- Avoid suggesting deletes for system-critical paths (/System, /Library, /Applications).\n- Prefer archiving older large files and deleting caches/build artifacts if safe.\n- Provide concise reasons.\n\nOutput format:\nArchive candidates:\n- path | size | reason\nDelete candidates:\n- path | size | reason\nKeep (if any path should be kept despite size):\n- path | reason\n\nScan summary JSON:\n{payload}\n"
+        "Here is a macOS disk usage scan summary. Suggest what the user can archive or delete to free space.\n\nRules:\n- Only use paths provided.\n- Avoid suggesting deletes for system-critical paths (/System, /Library, /Applications).\n- Prefer archiving older large files and deleting caches/build artifacts if safe.\n- Provide concise reasons.\n- Files with hard_links > 1 have other names; deleting one frees no space.\n- known_caches are caches tools rebuild on demand; follow each note (some should be cleared with the tool's own command).\n\nOutput format:\nArchive candidates:\n- path | size | reason\nDelete candidates:\n- path | size | reason\nKeep (if any path should be kept despite size):\n- path | reason\n\nScan summary JSON:\n{payload}\n"
     ))
 }
 
@@ -473,7 +1723,7 @@ fn system_excludes() -> Vec<String> {
     ]
 }
 
-async fn run_claude(prompt: String, options: ClaudeCodeOptions) -> Result<()> {
+async fn run_claude(prompt: String, options: ClaudeCodeOptions) -> Result<String> {
     let mut stream = query(prompt, Some(options)).await?;
 
     let mut output = String::new();
@@ -497,10 +1747,800 @@ async fn run_claude(prompt: String, options: ClaudeCodeOptions) -> Result<()> {
         }
     }
 
-    print!("{}", output);
-    if !output.ends_with('\n') {
+    Ok(output)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SuggestedAction {
+    Delete,
+    Archive,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Suggestion {
+    path: PathBuf,
+    action: SuggestedAction,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Pull the suggestion array out of Claude's reply, tolerating prose or
+/// code fences around it.
+fn parse_suggestions(text: &str) -> Result<Vec<Suggestion>> {
+    for (start, _) in text.match_indices('[') {
+        let candidate = &text[start..];
+        let Some(end) = matching_bracket(candidate) else {
+            continue;
+        };
+        if let Ok(suggestions) = serde_json::from_str::<Vec<Suggestion>>(&candidate[..end]) {
+            return Ok(suggestions);
+        }
+    }
+    bail!("Claude did not return a JSON suggestion list (use --free-text to see prose):\n{}", text.trim())
+}
+
+/// Byte length of the JSON array starting at `text[0]`, skipping brackets
+/// inside strings.
+fn matching_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Keep suggestions that exist under `root`; return the rest with a reason.
+/// Paths must be absolute: a relative one would resolve against the cwd.
+fn validate_suggestions(
+    suggestions: Vec<Suggestion>,
+    root: &Path,
+) -> (Vec<Suggestion>, Vec<(PathBuf, &'static str)>) {
+    let mut valid = Vec::new();
+    let mut rejected = Vec::new();
+
+    for mut suggestion in suggestions {
+        if !suggestion.path.is_absolute() {
+            rejected.push((suggestion.path, "not an absolute path"));
+            continue;
+        }
+        match suggestion.path.canonicalize() {
+            Ok(path) if path.starts_with(root) && path != root => {
+                suggestion.path = path;
+                valid.push(suggestion);
+            }
+            Ok(_) => rejected.push((suggestion.path, "outside scan root")),
+            Err(_) => rejected.push((suggestion.path, "does not exist")),
+        }
+    }
+
+    (valid, rejected)
+}
+
+fn suggestion_size(path: &Path, report: &ScanReport) -> Option<u64> {
+    report
+        .top_files
+        .iter()
+        .map(|f| (&f.path, f.size_bytes))
+        .chain(report.top_folders.iter().map(|f| (&f.path, f.size_bytes)))
+        .find(|(p, _)| p.as_path() == path)
+        .map(|(_, size)| size)
+        .or_else(|| {
+            let meta = fs::metadata(path).ok()?;
+            meta.is_file().then_some(meta.len())
+        })
+}
+
+fn print_suggestions(suggestions: &[Suggestion], report: &ScanReport) {
+    if suggestions.is_empty() {
+        println!("  (no suggestions)");
+        return;
+    }
+
+    for action in [SuggestedAction::Delete, SuggestedAction::Archive] {
+        let group: Vec<_> = suggestions.iter().filter(|s| s.action == action).collect();
+        if group.is_empty() {
+            continue;
+        }
+        println!();
+        println!(
+            "{}:",
+            match action {
+                SuggestedAction::Delete => "Delete",
+                SuggestedAction::Archive => "Archive",
+            }
+        );
+        for s in group {
+            let size = suggestion_size(&s.path, report)
+                .map(format_size)
+                .unwrap_or_else(|| "?".to_string());
+            println!("  {:>10}  {}  ({})", size, s.path.display(), s.reason);
+        }
+    }
+}
+
+/// Trash the "delete" suggestions and archive the "archive" ones after a
+/// y/N confirmation.
+fn apply_suggestions(suggestions: &[Suggestion]) -> Result<()> {
+    let of = |action| -> Vec<_> { suggestions.iter().filter(|s| s.action == action).collect() };
+    let deletes = of(SuggestedAction::Delete);
+    let archives = of(SuggestedAction::Archive);
+    if deletes.is_empty() && archives.is_empty() {
         println!();
+        println!("Nothing to apply.");
+        return Ok(());
+    }
+    let archive_dir = default_archive_dir()?;
+
+    let mut plan = Vec::new();
+    if !deletes.is_empty() {
+        plan.push(format!("move {} item(s) to ~/trash", deletes.len()));
+    }
+    if !archives.is_empty() {
+        plan.push(format!(
+            "archive {} item(s) into {}",
+            archives.len(),
+            archive_dir.display()
+        ));
+    }
+    let plan = plan.join(" and ");
+
+    println!();
+    print!("{}{}? [y/N] ", plan[..1].to_uppercase(), &plan[1..]);
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("Cancelled.");
+        return Ok(());
     }
 
+    for s in deletes {
+        match trash_path(&s.path) {
+            Ok(dest) => println!("  trashed {} -> {}", s.path.display(), dest.display()),
+            Err(err) => eprintln!("  failed: {err}"),
+        }
+    }
+    let mut archived = Vec::new();
+    for s in archives {
+        match archive_path(&s.path, &archive_dir, false) {
+            Ok(done) => {
+                print_archived(&done, false);
+                archived.push(done);
+            }
+            Err(err) => eprintln!("  failed: {err:#}"),
+        }
+    }
+    print_archive_totals(&archived, false);
     Ok(())
 }
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+struct Archived {
+    source: PathBuf,
+    archive: PathBuf,
+    original_bytes: u64,
+    /// Size of the written .tar.zst (0 for a dry run).
+    archive_bytes: u64,
+}
+
+fn default_archive_dir() -> Result<PathBuf> {
+    let home = env::var("HOME").context("HOME not set")?;
+    Ok(PathBuf::from(home).join("archive"))
+}
+
+fn run_archive(args: ArchiveArgs) -> Result<()> {
+    let dest_dir = match args.to {
+        Some(dir) => dir,
+        None => default_archive_dir()?,
+    };
+
+    let mut archived = Vec::new();
+    let mut failed = 0;
+    for path in &args.paths {
+        match archive_path(path, &dest_dir, args.dry_run) {
+            Ok(done) => {
+                print_archived(&done, args.dry_run);
+                archived.push(done);
+            }
+            Err(err) => {
+                eprintln!("  failed: {err:#}");
+                failed += 1;
+            }
+        }
+    }
+    print_archive_totals(&archived, args.dry_run);
+
+    if failed > 0 {
+        bail!("{} of {} paths could not be archived", failed, args.paths.len());
+    }
+    Ok(())
+}
+
+/// Pack `path` into `<dest_dir>/<name>-<timestamp>.tar.zst`, check that the
+/// archive decompresses and lists every entry, then remove the original.
+fn archive_path(path: &Path, dest_dir: &Path, dry_run: bool) -> Result<Archived> {
+    let source = path
+        .canonicalize()
+        .with_context(|| format!("Unable to resolve {}", path.display()))?;
+    let Some(name) = source.file_name() else {
+        bail!("Refusing to archive {}", source.display());
+    };
+    if env::var_os("HOME").is_some_and(|home| source == Path::new(&home)) {
+        bail!("Refusing to archive the home directory");
+    }
+
+    if !dry_run {
+        fs::create_dir_all(dest_dir)
+            .with_context(|| format!("Unable to create {}", dest_dir.display()))?;
+    }
+    let dest_dir = dest_dir.canonicalize().unwrap_or_else(|_| dest_dir.to_path_buf());
+    if dest_dir.starts_with(&source) {
+        bail!("{} is inside {}", dest_dir.display(), source.display());
+    }
+
+    let (entries, original_bytes) = tree_stats(&source)?;
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let stem = format!("{}-{}", name.to_string_lossy(), stamp);
+    let mut archive = dest_dir.join(format!("{stem}.tar.zst"));
+    let mut i = 2;
+    while archive.exists() {
+        archive = dest_dir.join(format!("{stem}.{i}.tar.zst"));
+        i += 1;
+    }
+
+    let mut done = Archived {
+        source,
+        archive,
+        original_bytes,
+        archive_bytes: 0,
+    };
+    if dry_run {
+        return Ok(done);
+    }
+
+    let verified = write_tar_zst(&done.source, &done.archive).and_then(|()| {
+        let listed = count_tar_zst_entries(&done.archive)?;
+        if listed != entries {
+            bail!("archive lists {listed} entries, expected {entries}");
+        }
+        Ok(())
+    });
+    if let Err(err) = verified {
+        let _ = fs::remove_file(&done.archive);
+        return Err(err.context(format!("{} was left in place", done.source.display())));
+    }
+
+    done.archive_bytes = fs::metadata(&done.archive)?.len();
+    if done.source.is_dir() {
+        fs::remove_dir_all(&done.source)
+    } else {
+        fs::remove_file(&done.source)
+    }
+    .with_context(|| {
+        format!(
+            "Archived to {} but could not remove {}",
+            done.archive.display(),
+            done.source.display()
+        )
+    })?;
+    Ok(done)
+}
+
+/// Entry count (including `path` itself) and total file bytes, without
+/// following symlinks, matching what tar stores.
+fn tree_stats(path: &Path) -> Result<(usize, u64)> {
+    let mut entries = 0;
+    let mut bytes = 0;
+    for entry in WalkDir::new(path) {
+        let entry = entry.with_context(|| format!("Unable to read {}", path.display()))?;
+        entries += 1;
+        if entry.file_type().is_file() {
+            bytes += entry.metadata()?.len();
+        }
+    }
+    Ok((entries, bytes))
+}
+
+fn write_tar_zst(source: &Path, archive: &Path) -> Result<()> {
+    let (Some(parent), Some(name)) = (source.parent(), source.file_name()) else {
+        bail!("Refusing to archive {}", source.display());
+    };
+    // Without COPYFILE_DISABLE, macOS tar adds a `._name` entry for every
+    // file with extended attributes (most downloads), and the entry count
+    // check fails. Other tars ignore it.
+    let mut tar = process::Command::new("tar")
+        .env("COPYFILE_DISABLE", "1")
+        .arg("-cf")
+        .arg("-")
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run tar")?;
+    let stream = tar.stdout.take().context("tar has no stdout")?;
+    let zstd = process::Command::new("zstd")
+        .args(["-q", "-T0", "-o"])
+        .arg(archive)
+        .stdin(stream)
+        .status()
+        .context("Failed to run zstd (brew install zstd)")?;
+    let tar = tar.wait()?;
+
+    if !tar.success() {
+        bail!("tar exited with {tar}");
+    }
+    if !zstd.success() {
+        bail!("zstd exited with {zstd}");
+    }
+    Ok(())
+}
+
+fn count_tar_zst_entries(archive: &Path) -> Result<usize> {
+    let mut zstd = process::Command::new("zstd")
+        .args(["-dcq"])
+        .arg(archive)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run zstd (brew install zstd)")?;
+    let stream = zstd.stdout.take().context("zstd has no stdout")?;
+    let listing = process::Command::new("tar")
+        .args(["-tf", "-"])
+        .stdin(stream)
+        .output()
+        .context("Failed to run tar")?;
+    let zstd = zstd.wait()?;
+
+    if !zstd.success() {
+        bail!("{} does not decompress ({zstd})", archive.display());
+    }
+    if !listing.status.success() {
+        bail!(
+            "{} does not list: {}",
+            archive.display(),
+            String::from_utf8_lossy(&listing.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&listing.stdout).lines().count())
+}
+
+fn print_archived(done: &Archived, dry_run: bool) {
+    if dry_run {
+        println!(
+            "  would archive {} ({}) -> {}",
+            done.source.display(),
+            format_size(done.original_bytes),
+            done.archive.display()
+        );
+        return;
+    }
+    println!(
+        "  archived {} -> {}  ({} -> {})",
+        done.source.display(),
+        done.archive.display(),
+        format_size(done.original_bytes),
+        format_size(done.archive_bytes)
+    );
+}
+
+fn print_archive_totals(archived: &[Archived], dry_run: bool) {
+    if archived.is_empty() {
+        return;
+    }
+    let original: u64 = archived.iter().map(|a| a.original_bytes).sum();
+    if dry_run {
+        println!("Would archive {} in {} item(s).", format_size(original), archived.len());
+        return;
+    }
+    let packed: u64 = archived.iter().map(|a| a.archive_bytes).sum();
+    println!(
+        "Freed {}; archives take {} ({} saved).",
+        format_size(original),
+        format_size(packed),
+        format_size(original.saturating_sub(packed))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    /// 20 top-level folders x 10 subfolders x 50 small files, plus a few large
+    /// sparse files so the report has something to list.
+    fn synthetic_tree(root: &Path) {
+        for a in 0..20 {
+            for b in 0..10 {
+                let dir = root.join(format!("a{a}")).join(format!("b{b}"));
+                fs::create_dir_all(&dir).unwrap();
+                for f in 0..50 {
+                    fs::write(dir.join(format!("f{f}.txt")), vec![b'x'; 100 + f]).unwrap();
+                }
+            }
+            let big = fs::File::create(root.join(format!("a{a}")).join("big.bin")).unwrap();
+            big.set_len((a as u64 + 1) * 10 * MB).unwrap();
+        }
+    }
+
+    fn scan(root: &Path, cache: &Path) -> ScanReport {
+        scan_root(root, MB, None, None, 50, 30, TopBy::Size, 1, None, 180, &[], false, Some(cache))
+            .unwrap()
+    }
+
+    fn folder_sizes(report: &ScanReport) -> Vec<(PathBuf, u64)> {
+        let mut sizes: Vec<_> = report
+            .top_folders
+            .iter()
+            .map(|f| (f.path.clone(), f.size_bytes))
+            .collect();
+        sizes.sort();
+        sizes
+    }
+
+    #[test]
+    fn known_caches_are_sized_and_nesting_is_marked() {
+        let home = env::temp_dir().join(format!("move-caches-{}", process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(home.join(".cache/yarn")).unwrap();
+        fs::create_dir_all(home.join(".cargo/registry/cache")).unwrap();
+        fs::create_dir_all(home.join("Library/Caches")).unwrap();
+        fs::write(home.join(".cache/yarn/pkg.tgz"), vec![b'x'; 2 * MB as usize]).unwrap();
+        fs::write(home.join(".cache/pip.whl"), vec![b'x'; MB as usize]).unwrap();
+        fs::write(home.join(".cargo/registry/cache/serde.crate"), vec![b'x'; 4096]).unwrap();
+        let home = home.canonicalize().unwrap();
+
+        let caches = scan_known_caches(&home, &home, &[]);
+        let names: Vec<&str> = caches.iter().map(|c| c.name).collect();
+        // Empty Library/Caches is left out; largest first
+        assert_eq!(names, vec!["XDG caches", "Yarn cache", "Cargo crate downloads"]);
+        assert!(caches[0].size_bytes >= 3 * MB);
+        assert_eq!(caches[0].files, 2);
+        assert_eq!(caches[0].within, None);
+        assert_eq!(caches[1].within.as_deref(), Some(home.join(".cache").as_path()));
+
+        // Only caches under the scan root, and --exclude applies
+        let under_cargo = scan_known_caches(&home, &home.join(".cargo"), &[]);
+        assert_eq!(under_cargo.len(), 1);
+        let excluded = scan_known_caches(&home, &home, &["yarn".to_string()]);
+        assert!(excluded.iter().all(|c| c.name != "Yarn cache"));
+
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn size_cache_reuses_unchanged_folders() {
+        let base = env::temp_dir().join(format!("move-size-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("tree");
+        let cache = base.join("sizes.json");
+        synthetic_tree(&root);
+
+        let cold = scan(&root, &cache);
+        let warm = scan(&root, &cache);
+
+        assert_eq!(cold.scanned_files, 20 * 10 * 50 + 20);
+        assert_eq!(cold.cached_dirs, 0);
+        assert_eq!(warm.cached_dirs, warm.scanned_dirs);
+        assert_eq!(warm.scanned_files, cold.scanned_files);
+        assert_eq!(folder_sizes(&warm), folder_sizes(&cold));
+        let uncached =
+            scan_root(&root, MB, None, None, 50, 30, TopBy::Size, 1, None, 180, &[], false, None)
+                .unwrap();
+        assert_eq!(folder_sizes(&uncached), folder_sizes(&cold));
+        for report in [&cold, &warm, &uncached] {
+            // 10 x 50 small files plus big.bin per top-level folder
+            assert!(report.top_folders.iter().all(|f| f.files == 501));
+            // Small files are counted by type too, cached or not
+            let by_extension: Vec<_> = report
+                .by_extension
+                .iter()
+                .map(|e| (e.extension.as_str(), e.size_bytes, e.files))
+                .collect();
+            let txt_bytes = 20 * 10 * (100..150).sum::<u64>();
+            assert_eq!(
+                by_extension,
+                [
+                    (".bin", (1..=20).sum::<u64>() * 10 * MB, 20),
+                    (".txt", txt_bytes, 20 * 10 * 50)
+                ]
+            );
+        }
+
+        // Adding a file bumps only that folder's mtime
+        let big = fs::File::create(root.join("a0").join("b0").join("new.bin")).unwrap();
+        big.set_len(5 * MB).unwrap();
+        let changed = scan(&root, &cache);
+        assert_eq!(changed.cached_dirs, changed.scanned_dirs - 1);
+        let a0 = |report: &ScanReport| {
+            report
+                .top_folders
+                .iter()
+                .find(|f| f.path == root.join("a0"))
+                .map(|f| f.size_bytes)
+        };
+        assert_eq!(a0(&changed).unwrap(), a0(&cold).unwrap() + 5 * MB);
+        assert!(changed
+            .top_files
+            .iter()
+            .any(|f| f.path.ends_with("a0/b0/new.bin")));
+
+        // Growing a file in place doesn't touch its folder's mtime
+        fs::OpenOptions::new()
+            .write(true)
+            .open(root.join("a3").join("big.bin"))
+            .unwrap()
+            .set_len(300 * MB)
+            .unwrap();
+        let grown = scan(&root, &cache);
+        assert_eq!(grown.cached_dirs, grown.scanned_dirs);
+        assert_eq!(grown.top_files[0].size_bytes, 300 * MB);
+        assert!(grown.top_files[0].path.ends_with("a3/big.bin"));
+
+        // Lots of tiny files: too small to rank by size, first by count
+        let tiny = root.join("tiny");
+        fs::create_dir_all(&tiny).unwrap();
+        for f in 0..600 {
+            fs::write(tiny.join(format!("t{f}")), b"x").unwrap();
+        }
+        assert!(!scan(&root, &cache).top_folders.iter().any(|f| f.path == tiny));
+        for cache in [Some(cache.as_path()), None] {
+            let by_count = scan_root(
+                &root, MB, None, None, 50, 30, TopBy::Count, 1, None, 180, &[], false, cache,
+            )
+            .unwrap();
+            let first = &by_count.top_folders[0];
+            assert_eq!((&first.path, first.files, first.size_bytes), (&tiny, 600, 600));
+            assert_eq!(by_count.top_folders[1].path, root.join("a0"));
+        }
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn hard_links_count_once_and_symlinks_are_skipped() {
+        let base = env::temp_dir().join(format!("move-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("tree");
+        let (a, b) = (root.join("a"), root.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        fs::File::create(a.join("disk.img")).unwrap().set_len(3 * MB).unwrap();
+        fs::write(a.join("notes.txt"), "hello").unwrap();
+        fs::hard_link(a.join("disk.img"), b.join("disk-copy.img")).unwrap();
+        fs::hard_link(a.join("notes.txt"), b.join("notes-copy.txt")).unwrap();
+        std::os::unix::fs::symlink(a.join("disk.img"), b.join("disk-alias.img")).unwrap();
+        std::os::unix::fs::symlink(&a, b.join("a-alias")).unwrap();
+
+        let scan_with = |cache: Option<&Path>| {
+            scan_root(
+                &root, MB, None, None, 50, 30, TopBy::Size, 1, Some(0), 180, &[], false, cache,
+            )
+            .unwrap()
+        };
+        let cache = base.join("sizes.json");
+        let uncached = scan_with(None);
+        let cold = scan_with(Some(&cache));
+        let warm = scan_with(Some(&cache));
+        assert_eq!(warm.cached_dirs, warm.scanned_dirs);
+
+        for report in [&uncached, &cold, &warm] {
+            // Every name is seen, but each inode's bytes count once
+            assert_eq!(report.scanned_files, 4);
+            assert_eq!(report.hardlinked_files, 2);
+            assert_eq!(report.skipped_symlinks, 2);
+            let (bytes, files) = report
+                .top_folders
+                .iter()
+                .fold((0, 0), |(bytes, files), f| (bytes + f.size_bytes, files + f.files));
+            assert_eq!((bytes, files), (3 * MB + 5, 2));
+            assert_eq!(report.top_files.len(), 1);
+            assert_eq!(report.top_files[0].hard_links, 2);
+        }
+        assert_eq!(folder_sizes(&cold), folder_sizes(&warm));
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn bucket_min_size_filters_folders_not_files() {
+        let base = env::temp_dir().join(format!("move-bucket-min-{}", process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("tree");
+        let cache = base.join("sizes.json");
+        // media/videos: one 3MB file; media/thumbs: 2MB of 16KB files;
+        // notes/daily: a few KB, buried a level deeper
+        fs::create_dir_all(root.join("media/videos")).unwrap();
+        fs::create_dir_all(root.join("media/thumbs")).unwrap();
+        fs::create_dir_all(root.join("notes/daily/2024")).unwrap();
+        let video = fs::File::create(root.join("media/videos/trip.mov")).unwrap();
+        video.set_len(3 * MB).unwrap();
+        for f in 0..128 {
+            fs::write(root.join(format!("media/thumbs/t{f}.jpg")), vec![b'x'; 16 * 1024]).unwrap();
+        }
+        for f in 0..4 {
+            fs::write(root.join(format!("notes/daily/2024/{f}.md")), vec![b'x'; 1024]).unwrap();
+        }
+        let root = root.canonicalize().unwrap();
+        let videos = root.join("media/videos");
+        let thumbs = root.join("media/thumbs");
+        let notes = root.join("notes/daily");
+
+        let folders = |top_by: TopBy, bucket_min: Option<u64>, cache: Option<&Path>| {
+            let report = scan_root(
+                &root, MB, None, None, 50, 30, top_by, 2, bucket_min, 180, &[], false, cache,
+            )
+            .unwrap();
+            // --min-size alone decides the file list
+            let files: Vec<&Path> = report.top_files.iter().map(|f| f.path.as_path()).collect();
+            assert_eq!(files, vec![root.join("media/videos/trip.mov")]);
+            let mut paths: Vec<PathBuf> = report.top_folders.into_iter().map(|f| f.path).collect();
+            paths.sort();
+            (report.bucket_min_size_bytes, paths)
+        };
+
+        for cache in [Some(cache.as_path()), None] {
+            // Buckets default to --min-size; thumbs clears it on small files alone
+            let (min, paths) = folders(TopBy::Size, None, cache);
+            assert_eq!((min, paths), (MB, vec![thumbs.clone(), videos.clone()]));
+            // A higher bucket threshold hides it without touching the file list
+            let (_, paths) = folders(TopBy::Size, Some(5 * MB / 2), cache);
+            assert_eq!(paths, vec![videos.clone()]);
+            // A lower one brings back the tiny deep folder
+            let (_, paths) = folders(TopBy::Size, Some(1024), cache);
+            assert_eq!(paths, vec![thumbs.clone(), videos.clone(), notes.clone()]);
+            // By count everything shows unless a threshold is given
+            let (min, paths) = folders(TopBy::Count, None, cache);
+            assert_eq!((min, paths.len()), (0, 3));
+            let (_, paths) = folders(TopBy::Count, Some(MB), cache);
+            assert_eq!(paths, vec![thumbs.clone(), videos.clone()]);
+        }
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn parse_suggestions_finds_the_array_in_prose_and_fences() {
+        let reply = r#"[note] Looking at the report, two things stand out:
+
+```json
+[
+  {"path": "/data/old [2019]", "action": "archive", "reason": "closed \"]\" project"},
+  {"path": "/data/cache.bin", "action": "delete", "reason": "regenerable"}
+]
+```
+
+Let me know if you want more [details]."#;
+        let suggestions = parse_suggestions(reply).unwrap();
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].path, PathBuf::from("/data/old [2019]"));
+        assert_eq!(suggestions[0].action, SuggestedAction::Archive);
+        assert_eq!(suggestions[0].reason, "closed \"]\" project");
+        assert_eq!(suggestions[1].action, SuggestedAction::Delete);
+
+        assert_eq!(matching_bracket(r#"["a]", ["b"]] tail"#), Some(13));
+        assert_eq!(matching_bracket("[unterminated"), None);
+        assert!(parse_suggestions("Nothing to clean up [yet].").is_err());
+    }
+
+    #[test]
+    fn validate_suggestions_rejects_paths_outside_root() {
+        let base = std::env::temp_dir().join(format!("move-validate-{}", std::process::id()));
+        let root = base.join("root");
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::write(base.join("outside.txt"), "x").unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+        let suggestion = |path: PathBuf| Suggestion {
+            path,
+            action: SuggestedAction::Delete,
+            reason: String::new(),
+        };
+
+        let (valid, rejected) = validate_suggestions(
+            vec![
+                suggestion(root.join("old")),
+                suggestion(root.join("missing")),
+                suggestion(root.join("..").join("outside.txt")),
+                suggestion(root.clone()),
+                suggestion(PathBuf::from("old")),
+            ],
+            &root,
+        );
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].path, root.join("old"));
+        let reasons: Vec<_> = rejected.iter().map(|(_, reason)| *reason).collect();
+        assert_eq!(
+            reasons,
+            [
+                "does not exist",
+                "outside scan root",
+                "outside scan root",
+                "not an absolute path"
+            ]
+        );
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn review_offers_only_whole_subtrees_never_the_root() {
+        let base = env::temp_dir().join(format!("move-review-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("root");
+        fs::create_dir_all(root.join("sub").join("deep")).unwrap();
+        for (path, size) in [("top.bin", MB), ("sub/x.bin", 2 * MB), ("sub/deep/y.bin", 3 * MB)] {
+            fs::File::create(root.join(path)).unwrap().set_len(size).unwrap();
+        }
+
+        let folders = |bucket_depth| {
+            let report = scan_root(
+                &root, MB, None, None, 50, 30, TopBy::Size, bucket_depth, Some(0), 180, &[],
+                false, None,
+            )
+            .unwrap();
+            let mut folders: Vec<_> = review_candidates(&report)
+                .into_iter()
+                .filter(|c| c.is_dir)
+                .map(|c| (c.path, c.size_bytes))
+                .collect();
+            folders.sort();
+            folders
+        };
+        // The root and sub only count the files directly inside them
+        assert_eq!(folders(0), []);
+        assert_eq!(folders(1), [(root.join("sub"), 5 * MB)]);
+        assert_eq!(folders(2), [(root.join("sub").join("deep"), 3 * MB)]);
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn archive_replaces_folder_with_verified_tar_zst() {
+        let base = env::temp_dir().join(format!("move-archive-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let project = base.join("old-project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("README.md"), "hello\n".repeat(1000)).unwrap();
+        fs::write(project.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+        let dest = base.join("archive");
+
+        let planned = archive_path(&project, &dest, true).unwrap();
+        assert!(project.exists());
+        assert!(!planned.archive.exists());
+        assert_eq!(planned.original_bytes, 6000 + 13);
+
+        let done = archive_path(&project, &dest, false).unwrap();
+        assert!(!project.exists());
+        assert!(done.archive.extension().is_some_and(|ext| ext == "zst"));
+        assert!(done.archive_bytes > 0 && done.archive_bytes < done.original_bytes);
+        assert_eq!(count_tar_zst_entries(&done.archive).unwrap(), 4);
+
+        assert!(archive_path(&base, &dest, true).is_err());
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::{AdaptiveBitrate, LocalConfig};
+
+/// The fields of an ffmpeg progress line the controller cares about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressSample {
+    pub frame: u64,
+    pub drop: u64,
+    pub speed: Option<f64>,
+    /// Output bitrate in kbit/s; `N/A` until ffmpeg has written something.
+    pub bitrate_kbps: Option<f64>,
+}
+
+/// Parse a progress line like
+/// `frame= 1200 fps= 60 q=-0.0 size= 8192kB time=00:00:20.00 bitrate=3355.4kbits/s dup=0 drop=3 speed=0.98x`.
+/// ffmpeg separates updates with `\r`, so callers should split on that too.
+pub fn parse_progress(line: &str) -> Option<ProgressSample> {
+    let frame = progress_field(line, "frame=")?.parse().ok()?;
+    let drop = progress_field(line, "drop=")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let speed = progress_field(line, "speed=")
+        .and_then(|v| v.trim_end_matches('x').parse().ok());
+    let bitrate_kbps = progress_field(line, "bitrate=")
+        .and_then(|v| v.trim_end_matches("kbits/s").parse().ok());
+    Some(ProgressSample {
+        frame,
+        drop,
+        speed,
+        bitrate_kbps,
+    })
+}
+
+/// ffmpeg pads values after `=` to keep columns aligned (`frame= 1200`).
+fn progress_field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let start = line.find(key)? + key.len();
+    line[start..].split_whitespace().next()
+}
+
+/// A bitrate change the daemon should apply by restarting ffmpeg.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adaptation {
+    pub from: String,
+    pub to: String,
+    pub reason: String,
+}
+
+/// Walks down `video_bitrate` followed by the configured ladder while ffmpeg
+/// keeps dropping frames or running below realtime, and back to the top once
+/// things have been quiet for `stable_secs`.
+pub struct BitrateController {
+    rungs: Vec<String>,
+    rung: usize,
+    min_speed: f64,
+    degraded_samples: usize,
+    window: Duration,
+    stable: Duration,
+    degraded: VecDeque<Instant>,
+    last_drop: Option<u64>,
+    calm_since: Instant,
+}
+
+impl BitrateController {
+    pub fn new(local: &LocalConfig, adaptive: &AdaptiveBitrate) -> Self {
+        let mut rungs = vec![local.video_bitrate.clone()];
+        rungs.extend(adaptive.ladder.iter().cloned());
+        Self {
+            rungs,
+            rung: 0,
+            min_speed: adaptive.min_speed,
+            degraded_samples: adaptive.degraded_samples,
+            window: Duration::from_secs(adaptive.window_secs),
+            stable: Duration::from_secs(adaptive.stable_secs),
+            degraded: VecDeque::new(),
+            last_drop: None,
+            calm_since: Instant::now(),
+        }
+    }
+
+    pub fn current_bitrate(&self) -> &str {
+        &self.rungs[self.rung]
+    }
+
+    /// `local` with the current rung's bitrate, ready for `build_command`.
+    pub fn apply(&self, local: &LocalConfig) -> LocalConfig {
+        if self.rung == 0 {
+            local.clone()
+        } else {
+            local.with_video_bitrate(self.current_bitrate())
+        }
+    }
+
+    /// Forget ffmpeg's counters; call whenever a new ffmpeg process starts.
+    pub fn restarted(&mut self, now: Instant) {
+        self.degraded.clear();
+        self.last_drop = None;
+        self.calm_since = now;
+    }
+
+    /// Feed one progress sample. Returns the change to make, if any.
+    pub fn observe(&mut self, sample: &ProgressSample, now: Instant) -> Option<Adaptation> {
+        let new_drops = match self.last_drop {
+            Some(last) if sample.drop >= last => sample.drop - last,
+            _ => 0,
+        };
+        self.last_drop = Some(sample.drop);
+        let slow = sample.speed.is_some_and(|speed| speed < self.min_speed);
+
+        if new_drops > 0 || slow {
+            self.degraded.push_back(now);
+            self.calm_since = now;
+        }
+        while self
+            .degraded
+            .front()
+            .is_some_and(|&t| now.duration_since(t) > self.window)
+        {
+            self.degraded.pop_front();
+        }
+
+        if self.degraded.len() >= self.degraded_samples && self.rung + 1 < self.rungs.len() {
+            let reason = if slow {
+                format!(
+                    "speed {:.2}x below {:.2}x",
+                    sample.speed.unwrap_or_default(),
+                    self.min_speed
+                )
+            } else {
+                format!(
+                    "{} degraded progress updates within {}s ({} frames dropped)",
+                    self.degraded.len(),
+                    self.window.as_secs(),
+                    sample.drop
+                )
+            };
+            return Some(self.move_to(self.rung + 1, reason, now));
+        }
+
+        if self.rung > 0 && now.duration_since(self.calm_since) >= self.stable {
+            let reason = format!("stable for {}s", self.stable.as_secs());
+            return Some(self.move_to(0, reason, now));
+        }
+
+        None
+    }
+
+    fn move_to(&mut self, rung: usize, reason: String, now: Instant) -> Adaptation {
+        let from = self.current_bitrate().to_string();
+        self.rung = rung;
+        self.restarted(now);
+        Adaptation {
+            from,
+            to: self.current_bitrate().to_string(),
+            reason,
+        }
+    }
+}
+
+impl std::fmt::Display for Adaptation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "adaptive bitrate: {} -> {} ({})",
+            self.from, self.to, self.reason
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local() -> LocalConfig {
+        toml::from_str(
+            r#"
+ffmpeg_path = "ffmpeg"
+video_bitrate = "9000k"
+maxrate = "9000k"
+bufsize = "18000k"
+
+[capture]
+type = "avfoundation"
+video_device = "1"
+
+[encoder]
+type = "h264_videotoolbox"
+
+[adaptive]
+ladder = ["6000k", "3000k"]
+degraded_samples = 3
+window_secs = 10
+stable_secs = 60
+"#,
+        )
+        .unwrap()
+    }
+
+    fn sample(drop: u64, speed: f64) -> ProgressSample {
+        ProgressSample {
+            frame: 1000,
+            drop,
+            speed: Some(speed),
+            bitrate_kbps: None,
+        }
+    }
+
+    #[test]
+    fn parses_progress_lines() {
+        let line = "frame= 1200 fps= 60 q=-0.0 size=    8192kB time=00:00:20.00 \
+                    bitrate=3355.4kbits/s dup=0 drop=3 speed=0.98x";
+        let sample = parse_progress(line).unwrap();
+        assert_eq!(sample.frame, 1200);
+        assert_eq!(sample.drop, 3);
+        assert_eq!(sample.speed, Some(0.98));
+        assert_eq!(sample.bitrate_kbps, Some(3355.4));
+
+        let early = parse_progress("frame=    0 fps=0.0 q=0.0 bitrate=N/A speed=N/A").unwrap();
+        assert_eq!(early.bitrate_kbps, None);
+        assert_eq!(early.speed, None);
+        assert!(parse_progress("[avfoundation @ 0x1] Configuration of video device").is_none());
+    }
+
+    #[test]
+    fn steps_down_the_ladder_and_back_to_the_top() {
+        let local = local();
+        let mut controller = BitrateController::new(&local, local.adaptive.as_ref().unwrap());
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        controller.restarted(start);
+
+        // The first sample only sets the drop baseline
+        assert_eq!(controller.observe(&sample(5, 1.0), at(0)), None);
+        assert_eq!(controller.observe(&sample(7, 1.0), at(1)), None);
+        assert_eq!(controller.observe(&sample(9, 1.0), at(2)), None);
+        let step = controller.observe(&sample(12, 1.0), at(3)).unwrap();
+        assert_eq!((step.from.as_str(), step.to.as_str()), ("9000k", "6000k"));
+
+        let applied = controller.apply(&local);
+        assert_eq!(applied.video_bitrate, "6000k");
+        assert_eq!(applied.maxrate.as_deref(), Some("6000k"));
+        assert_eq!(applied.bufsize.as_deref(), Some("12000k"));
+
+        // Running below realtime counts as degraded too
+        for secs in 4..6 {
+            assert_eq!(controller.observe(&sample(0, 0.8), at(secs)), None);
+        }
+        let step = controller.observe(&sample(0, 0.8), at(6)).unwrap();
+        assert_eq!(step.to, "3000k");
+        assert!(step.reason.starts_with("speed 0.80x"), "{}", step.reason);
+
+        // Nothing below the last rung
+        for secs in 7..12 {
+            assert_eq!(controller.observe(&sample(0, 0.5), at(secs)), None);
+        }
+        assert_eq!(controller.current_bitrate(), "3000k");
+
+        // Calm again, but not for stable_secs yet
+        assert_eq!(controller.observe(&sample(0, 1.0), at(40)), None);
+
+        let reset = controller.observe(&sample(0, 1.0), at(11 + 60)).unwrap();
+        assert_eq!((reset.from.as_str(), reset.to.as_str()), ("3000k", "9000k"));
+        assert_eq!(controller.apply(&local).video_bitrate, "9000k");
+    }
+}
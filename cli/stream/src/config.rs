@@ -0,0 +1,885 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::util::render_remote_target;
+
+pub fn load_from(path: &Path) -> Result<Config> {
+    let mut cfg = parse_from(path)?;
+    cfg.validate()
+        .with_context(|| format!("invalid config {}", path.display()))?;
+    Ok(cfg)
+}
+
+/// Parse without value validation, for `config validate` to check every
+/// profile instead of stopping at the first bad one.
+pub fn parse_from(path: &Path) -> Result<Config> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read config {}", path.display()))?;
+    toml::from_str(&raw).with_context(|| format!("parse config {}", path.display()))
+}
+
+const MAX_FPS: u32 = 240;
+
+/// Rewrite the config at `path` with every key that has a default spelled
+/// out, for `config upgrade`. User values, comments and key order are kept;
+/// the previous file is saved as `<path>.bak`. Returns the keys added.
+pub fn upgrade_config(path: &Path) -> Result<Vec<String>> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read config {}", path.display()))?;
+    let (upgraded, added) =
+        upgrade_config_str(&raw).with_context(|| format!("upgrade config {}", path.display()))?;
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    let backup = path.with_extension("toml.bak");
+    fs::write(&backup, &raw).with_context(|| format!("write {}", backup.display()))?;
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, upgraded).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(added)
+}
+
+/// `upgrade_config` on a string: returns the new text and the dotted keys
+/// that were filled in from defaults.
+pub fn upgrade_config_str(raw: &str) -> Result<(String, Vec<String>)> {
+    let cfg: Config = toml::from_str(raw).context("parse config")?;
+    let defaults: toml_edit::DocumentMut = toml::to_string(&cfg)
+        .context("serialize config")?
+        .parse()
+        .context("reparse config")?;
+    let mut doc: toml_edit::DocumentMut = raw.parse().context("parse config")?;
+
+    let mut added = Vec::new();
+    fill_missing(doc.as_table_mut(), defaults.as_table(), "", &mut added);
+    Ok((doc.to_string(), added))
+}
+
+fn fill_missing(
+    target: &mut dyn toml_edit::TableLike,
+    defaults: &dyn toml_edit::TableLike,
+    prefix: &str,
+    added: &mut Vec<String>,
+) {
+    for (key, default) in defaults.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match target.get_mut(key) {
+            Some(existing) => {
+                if let (Some(existing), Some(default)) =
+                    (existing.as_table_like_mut(), default.as_table_like())
+                {
+                    fill_missing(existing, default, &path, added);
+                }
+            }
+            // Empty lists and tables spell out nothing useful
+            None if is_empty_item(default) => {}
+            None => {
+                target.insert(key, default.clone());
+                added.push(path);
+            }
+        }
+    }
+}
+
+fn is_empty_item(item: &toml_edit::Item) -> bool {
+    item.as_array().is_some_and(|a| a.is_empty())
+        || item.as_table_like().is_some_and(|t| t.is_empty())
+}
+
+pub fn write_default_config(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+
+    if path.exists() {
+        anyhow::bail!("config {} already exists", path.display());
+    }
+
+    let example = include_str!("../config.example.toml");
+    fs::write(path, example).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_profile_name")]
+    pub default_profile: String,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    pub fn profile(&self, name: Option<&str>) -> Result<(String, &Profile)> {
+        let resolved = name.unwrap_or(&self.default_profile);
+        let profile = self
+            .profiles
+            .get(resolved)
+            .with_context(|| format!("profile \"{resolved}\" not found in config"))?;
+        Ok((resolved.to_string(), profile))
+    }
+
+    /// Catch values ffmpeg would reject with a cryptic error at stream time,
+    /// normalizing resolutions to `WIDTHxHEIGHT` along the way.
+    pub fn validate(&mut self) -> Result<()> {
+        for (name, profile) in &mut self.profiles {
+            profile.validate(&format!("profiles.{name}"))?;
+        }
+        Ok(())
+    }
+
+    /// Like `validate`, but checks every profile and returns the first
+    /// problem in each (`None` when the profile is fine), in name order.
+    /// A `default_profile` that doesn't exist is reported under its name.
+    pub fn validate_each(&mut self) -> Vec<(String, Option<String>)> {
+        let mut report: Vec<(String, Option<String>)> = self
+            .profiles
+            .iter_mut()
+            .map(|(name, profile)| {
+                let issue = profile
+                    .validate(&format!("profiles.{name}"))
+                    .err()
+                    .map(|err| format!("{err:#}"));
+                (name.clone(), issue)
+            })
+            .collect();
+        if !self.profiles.contains_key(&self.default_profile) {
+            report.push((
+                self.default_profile.clone(),
+                Some("default_profile names a profile that isn't defined".to_string()),
+            ));
+        }
+        report
+    }
+
+    /// One line per profile with its key settings; `*` marks the default.
+    pub fn profile_lines(&self) -> Vec<String> {
+        let width = self.profiles.keys().map(|n| n.len()).max().unwrap_or(0);
+        self.profiles
+            .iter()
+            .map(|(name, profile)| {
+                let marker = if *name == self.default_profile { '*' } else { ' ' };
+                format!("{marker} {name:<width$}  {}", profile.summary())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub description: Option<String>,
+    /// Receiver started over ssh in tmux. Set this or `output`, not both.
+    #[serde(default)]
+    pub remote: Option<RemoteConfig>,
+    /// Standard ingest URL (`srt://`, `rtmp://`, `rtmps://`) that ffmpeg
+    /// streams to directly, with no remote receiver to start or stop.
+    #[serde(default)]
+    pub output: Option<String>,
+    pub local: LocalConfig,
+}
+
+/// Where a profile's stream goes.
+#[derive(Debug, Clone, Copy)]
+pub enum StreamTarget<'a> {
+    /// The tmux receiver on `remote.host`, reached over `local.transport`.
+    Remote(&'a RemoteConfig),
+    /// An ingest URL ffmpeg publishes to itself.
+    Output(&'a str, OutputProtocol),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputProtocol {
+    Srt,
+    Rtmp,
+}
+
+impl OutputProtocol {
+    /// The ffmpeg `-f` muxer the protocol carries.
+    pub fn muxer(self) -> &'static str {
+        match self {
+            OutputProtocol::Srt => "mpegts",
+            OutputProtocol::Rtmp => "flv",
+        }
+    }
+}
+
+impl Profile {
+    fn validate(&mut self, key: &str) -> Result<()> {
+        self.local.validate(&format!("{key}.local"))?;
+        self.target().with_context(|| key.to_string())?;
+        if self.output.is_some() && self.local.transport.is_some() {
+            anyhow::bail!(
+                "{key}.local.transport only applies to a remote receiver; \
+                 put the full URL in {key}.output instead"
+            );
+        }
+        Ok(())
+    }
+
+    pub fn target(&self) -> Result<StreamTarget<'_>> {
+        match (&self.remote, &self.output) {
+            (Some(remote), None) => Ok(StreamTarget::Remote(remote)),
+            (None, Some(url)) => {
+                let protocol =
+                    check_output_url(url).with_context(|| format!("output = \"{url}\""))?;
+                Ok(StreamTarget::Output(url, protocol))
+            }
+            (Some(_), Some(_)) => anyhow::bail!("set either remote or output, not both"),
+            (None, None) => {
+                anyhow::bail!("needs a [remote] receiver or an output URL (srt:// or rtmp://)")
+            }
+        }
+    }
+
+    /// `h264_videotoolbox 9000k @ scale=-2:1440 60fps -> stream@box:6000 (ffmpeg)`
+    pub fn summary(&self) -> String {
+        let local = &self.local;
+        let size = match (&local.resolution, &local.scale_filter) {
+            (Some(res), _) => res.clone(),
+            (None, Some(filter)) => filter.clone(),
+            (None, None) => "native".to_string(),
+        };
+        let destination = match self.target() {
+            Ok(StreamTarget::Remote(remote)) => {
+                let runner = match &remote.runner {
+                    RemoteRunner::Ffmpeg(_) => "ffmpeg",
+                    RemoteRunner::HeadlessObs(_) => "headless_obs",
+                    RemoteRunner::Custom { .. } => "custom",
+                };
+                format!(
+                    "{}:{} ({})",
+                    render_remote_target(remote.user.as_deref(), &remote.host),
+                    remote.ingest_port,
+                    runner
+                )
+            }
+            Ok(StreamTarget::Output(url, _)) => format!("{} (direct)", redact_output_url(url)),
+            Err(_) => "(no target)".to_string(),
+        };
+        format!(
+            "{} {} @ {} {}fps -> {}",
+            local.encoder.codec_name(),
+            local.video_bitrate,
+            size,
+            local.fps,
+            destination
+        )
+    }
+}
+
+/// Check a direct output URL: srt needs `host:port`, rtmp(s) a host and an
+/// app/stream-key path. Returns which protocol it is.
+pub fn check_output_url(url: &str) -> Result<OutputProtocol> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .context("expected srt://host:port or rtmp://host/app/key")?;
+    let protocol = match scheme.to_ascii_lowercase().as_str() {
+        "srt" => OutputProtocol::Srt,
+        "rtmp" | "rtmps" => OutputProtocol::Rtmp,
+        other => anyhow::bail!("unsupported scheme \"{other}\": expected srt, rtmp or rtmps"),
+    };
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (authority, None),
+    };
+    if host.is_empty() {
+        anyhow::bail!("missing host");
+    }
+    if let Some(port) = port {
+        port.parse::<u16>()
+            .with_context(|| format!("port \"{port}\" is not a number"))?;
+    }
+    match protocol {
+        OutputProtocol::Srt if port.is_none() => {
+            anyhow::bail!("srt needs a port (srt://host:port)")
+        }
+        OutputProtocol::Rtmp if rest[authority.len()..].trim_matches('/').is_empty() => {
+            anyhow::bail!("rtmp needs an app and stream key (rtmp://host/app/key)")
+        }
+        _ => {}
+    }
+    Ok(protocol)
+}
+
+/// A direct output URL safe to print: the stream key (everything after the
+/// app path segment) and any query (srt passphrase, streamid) are elided.
+pub fn redact_output_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let without_query = rest.split('?').next().unwrap_or_default();
+    let mut parts = without_query.splitn(3, '/');
+    let authority = parts.next().unwrap_or_default();
+    let mut shown = format!("{scheme}://{authority}");
+    if let Some(app) = parts.next().filter(|app| !app.is_empty()) {
+        shown.push('/');
+        shown.push_str(app);
+    }
+    if parts.next().is_some() || rest.len() > without_query.len() {
+        shown.push_str("/…");
+    }
+    shown
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    /// Default: "streamd".
+    #[serde(default = "default_tmux_session")]
+    pub tmux_session: String,
+    /// Default: 6000.
+    #[serde(default = "default_ingest_port")]
+    pub ingest_port: u16,
+    /// Default: 1316 (7 MPEG-TS packets, fits a 1500-byte MTU).
+    #[serde(default = "default_remote_packet_size")]
+    pub packet_size: u32,
+    #[serde(default)]
+    pub log_path: Option<PathBuf>,
+    /// Default: ffmpeg remuxing to ~/stream/current.ts.
+    #[serde(default = "default_remote_runner")]
+    pub runner: RemoteRunner,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalConfig {
+    pub ffmpeg_path: PathBuf,
+    /// Default: 60.
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+    #[serde(default)]
+    pub resolution: Option<String>,
+    /// Default: "9000k".
+    #[serde(default = "default_video_bitrate")]
+    pub video_bitrate: String,
+    #[serde(default)]
+    pub maxrate: Option<String>,
+    #[serde(default)]
+    pub bufsize: Option<String>,
+    /// Default: "160k".
+    #[serde(default = "default_audio_bitrate")]
+    pub audio_bitrate: String,
+    #[serde(default)]
+    pub scale_filter: Option<String>,
+    #[serde(default)]
+    pub filters: Vec<String>,
+    pub capture: CaptureSource,
+    pub encoder: Encoder,
+    #[serde(default)]
+    pub transport: Option<Transport>,
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Process priority: -20 (highest) to 19 (lowest). Default: 10 (low priority).
+    #[serde(default = "default_nice")]
+    pub nice: i32,
+    /// Use realtime scheduling (requires root or proper entitlements).
+    #[serde(default)]
+    pub realtime: bool,
+    /// Probing size in bytes (lower = faster start, less accurate detection).
+    #[serde(default = "default_probesize")]
+    pub probesize: u32,
+    /// Analysis duration in microseconds.
+    #[serde(default = "default_analyzeduration")]
+    pub analyzeduration: u32,
+    /// Step the video bitrate down a ladder when ffmpeg falls behind.
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveBitrate>,
+}
+
+/// Lower bitrate rungs to fall back to, below `video_bitrate`, plus the
+/// thresholds that decide when to move between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveBitrate {
+    pub ladder: Vec<String>,
+    /// A progress line with `speed=` below this counts as degraded. Default: 0.95.
+    #[serde(default = "default_adaptive_min_speed")]
+    pub min_speed: f64,
+    /// Degraded progress lines within `window_secs` needed to step down. Default: 5.
+    #[serde(default = "default_adaptive_degraded_samples")]
+    pub degraded_samples: usize,
+    /// Default: 10.
+    #[serde(default = "default_adaptive_window_secs")]
+    pub window_secs: u64,
+    /// Seconds without degradation before going back to the top rung. Default: 300.
+    #[serde(default = "default_adaptive_stable_secs")]
+    pub stable_secs: u64,
+}
+
+impl LocalConfig {
+    fn validate(&mut self, key: &str) -> Result<()> {
+        if self.fps == 0 || self.fps > MAX_FPS {
+            anyhow::bail!("{key}.fps = {}: expected 1-{MAX_FPS}", self.fps);
+        }
+
+        if let Some(res) = &self.resolution {
+            let (width, height) = parse_resolution(res)
+                .with_context(|| format!("{key}.resolution = \"{res}\""))?;
+            self.resolution = Some(format!("{width}x{height}"));
+        }
+
+        check_bitrate(&format!("{key}.video_bitrate"), &self.video_bitrate)?;
+        check_bitrate(&format!("{key}.audio_bitrate"), &self.audio_bitrate)?;
+        if let Some(maxrate) = &self.maxrate {
+            check_bitrate(&format!("{key}.maxrate"), maxrate)?;
+        }
+        if let Some(bufsize) = &self.bufsize {
+            check_bitrate(&format!("{key}.bufsize"), bufsize)?;
+        }
+        if let Some(adaptive) = &self.adaptive {
+            let mut previous = bitrate_kbps(&self.video_bitrate).unwrap_or(f64::MAX);
+            for (i, rung) in adaptive.ladder.iter().enumerate() {
+                let rung_key = format!("{key}.adaptive.ladder[{i}]");
+                check_bitrate(&rung_key, rung)?;
+                let kbps = bitrate_kbps(rung).unwrap_or(0.0);
+                if kbps >= previous {
+                    anyhow::bail!(
+                        "{rung_key} = \"{rung}\": rungs must be lower than video_bitrate and descending"
+                    );
+                }
+                previous = kbps;
+            }
+            if adaptive.degraded_samples == 0 {
+                anyhow::bail!("{key}.adaptive.degraded_samples must be at least 1");
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy of this config streaming at `bitrate`. `maxrate` and `bufsize`
+    /// are scaled by the same factor so the rate control stays proportional.
+    pub fn with_video_bitrate(&self, bitrate: &str) -> LocalConfig {
+        let mut local = self.clone();
+        let factor = match (bitrate_kbps(bitrate), bitrate_kbps(&self.video_bitrate)) {
+            (Some(new), Some(old)) if old > 0.0 => new / old,
+            _ => 1.0,
+        };
+        let scale = |value: &Option<String>| {
+            value
+                .as_deref()
+                .and_then(bitrate_kbps)
+                .map(|kbps| format!("{}k", (kbps * factor).round() as u64))
+        };
+        local.maxrate = scale(&self.maxrate);
+        local.bufsize = scale(&self.bufsize);
+        local.video_bitrate = bitrate.to_string();
+        local
+    }
+
+    /// Resolution as ffmpeg's `scale` filter expects it (`W:H`).
+    pub fn scale_size(&self) -> Option<String> {
+        let (width, height) = parse_resolution(self.resolution.as_deref()?).ok()?;
+        Some(format!("{width}:{height}"))
+    }
+}
+
+/// Parse `1920x1080` (also `1920X1080`, `1920:1080`). Both sides must be even,
+/// since the usual yuv420p output can't encode odd dimensions.
+pub fn parse_resolution(value: &str) -> Result<(u32, u32)> {
+    let value = value.trim();
+    let (w, h) = value
+        .split_once(['x', 'X', ':'])
+        .with_context(|| format!("expected WIDTHxHEIGHT like 1920x1080, got \"{value}\""))?;
+    let parse = |side: &str, what: &str| -> Result<u32> {
+        let n: u32 = side
+            .trim()
+            .parse()
+            .with_context(|| format!("{what} \"{side}\" is not a number"))?;
+        if n == 0 || !n.is_multiple_of(2) {
+            anyhow::bail!("{what} must be a positive even number, got {n}");
+        }
+        Ok(n)
+    };
+    Ok((parse(w, "width")?, parse(h, "height")?))
+}
+
+/// Bitrate in kbit/s, for comparing `9000k` against `6M`.
+pub fn bitrate_kbps(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (digits, scale) = match value.chars().last()? {
+        'k' | 'K' => (&value[..value.len() - 1], 1.0),
+        'm' | 'M' => (&value[..value.len() - 1], 1000.0),
+        _ => (value, 0.001),
+    };
+    digits.parse::<f64>().ok().map(|n| n * scale)
+}
+
+/// Bitrates are ffmpeg-style numbers with an optional k/M suffix: `9000k`, `6M`, `128000`.
+fn check_bitrate(key: &str, value: &str) -> Result<()> {
+    let digits = value
+        .strip_suffix(['k', 'K', 'm', 'M'])
+        .unwrap_or(value);
+    let valid = !digits.is_empty()
+        && digits.parse::<f64>().is_ok_and(|n| n > 0.0 && n.is_finite())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.');
+    if !valid {
+        anyhow::bail!("{key} = \"{value}\": expected a bitrate like 9000k or 6M");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptureSource {
+    Avfoundation(AvfoundationCapture),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvfoundationCapture {
+    pub video_device: String,
+    pub audio_device: Option<String>,
+    #[serde(default = "default_pixel_format")]
+    pub pixel_format: String,
+    #[serde(default = "default_capture_cursor")]
+    pub capture_cursor: bool,
+    #[serde(default)]
+    pub capture_clicks: bool,
+    #[serde(default)]
+    pub thread_queue_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Encoder {
+    // Configs have always spelled these like the ffmpeg codec names
+    #[serde(rename = "h264_videotoolbox", alias = "h264_video_toolbox")]
+    H264VideoToolbox {
+        quality: Option<String>,
+        #[serde(default = "default_allow_sw")]
+        allow_sw: bool,
+    },
+    #[serde(rename = "hevc_videotoolbox", alias = "hevc_video_toolbox")]
+    HevcVideoToolbox {
+        quality: Option<String>,
+        #[serde(default = "default_allow_sw")]
+        allow_sw: bool,
+    },
+    Libx264 {
+        preset: String,
+        #[serde(default)]
+        tune: Option<String>,
+    },
+}
+
+impl Encoder {
+    /// The ffmpeg `-c:v` name.
+    pub fn codec_name(&self) -> &'static str {
+        match self {
+            Encoder::H264VideoToolbox { .. } => "h264_videotoolbox",
+            Encoder::HevcVideoToolbox { .. } => "hevc_videotoolbox",
+            Encoder::Libx264 { .. } => "libx264",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transport {
+    #[serde(rename = "srt")]
+    Srt(SrtConfig),
+    #[serde(rename = "custom")]
+    Custom { url: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrtConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    #[serde(default = "default_srt_mode")]
+    pub mode: String,
+    #[serde(default = "default_srt_latency")]
+    pub latency_ms: u32,
+    #[serde(default = "default_packet_size")]
+    pub packet_size: u32,
+    pub passphrase: Option<String>,
+    pub pbkeylen: Option<u32>,
+    pub stream_id: Option<String>,
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteRunner {
+    #[serde(rename = "ffmpeg")]
+    Ffmpeg(RemoteFfmpeg),
+    #[serde(rename = "headless_obs")]
+    HeadlessObs(RemoteObs),
+    Custom {
+        command: String,
+    },
+}
+
+impl Default for RemoteRunner {
+    fn default() -> Self {
+        default_remote_runner()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteFfmpeg {
+    #[serde(default)]
+    pub ffmpeg_path: Option<PathBuf>,
+    #[serde(default = "default_remote_format")]
+    pub format: String,
+    pub output: String,
+    #[serde(default = "default_copy")]
+    pub copy_video: bool,
+    #[serde(default = "default_copy")]
+    pub copy_audio: bool,
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteObs {
+    pub binary: PathBuf,
+    pub profile: String,
+    pub scene_collection: String,
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub xvfb: bool,
+}
+
+fn default_profile_name() -> String {
+    "main".to_string()
+}
+
+fn default_tmux_session() -> String {
+    "streamd".to_string()
+}
+
+fn default_ingest_port() -> u16 {
+    6000
+}
+
+fn default_remote_packet_size() -> u32 {
+    1316
+}
+
+fn default_remote_runner() -> RemoteRunner {
+    RemoteRunner::Ffmpeg(RemoteFfmpeg {
+        ffmpeg_path: None,
+        format: "mpegts".to_string(),
+        output: "~/stream/current.ts".to_string(),
+        copy_video: true,
+        copy_audio: true,
+        extra_args: Vec::new(),
+    })
+}
+
+fn default_fps() -> u32 {
+    60
+}
+
+fn default_video_bitrate() -> String {
+    "9000k".to_string()
+}
+
+fn default_audio_bitrate() -> String {
+    "160k".to_string()
+}
+
+fn default_pixel_format() -> String {
+    "uyvy422".to_string()
+}
+
+fn default_capture_cursor() -> bool {
+    true
+}
+
+fn default_allow_sw() -> bool {
+    false
+}
+
+fn default_srt_mode() -> String {
+    "caller".to_string()
+}
+
+fn default_srt_latency() -> u32 {
+    20
+}
+
+fn default_packet_size() -> u32 {
+    1316
+}
+
+fn default_remote_format() -> String {
+    "mpegts".to_string()
+}
+
+fn default_copy() -> bool {
+    true
+}
+
+fn default_nice() -> i32 {
+    10 // Low priority by default to minimize system impact
+}
+
+fn default_probesize() -> u32 {
+    32 // Very small probe for instant start
+}
+
+fn default_analyzeduration() -> u32 {
+    0 // Skip analysis for known input
+}
+
+fn default_adaptive_min_speed() -> f64 {
+    0.95
+}
+
+fn default_adaptive_degraded_samples() -> usize {
+    5
+}
+
+fn default_adaptive_window_secs() -> u64 {
+    10
+}
+
+fn default_adaptive_stable_secs() -> u64 {
+    300
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config from before codecs, ladders and most tuning keys existed.
+    const OLD_CONFIG: &str = r#"# my stream box
+[profiles.main.remote]
+host = "stream-box.local"
+
+[profiles.main.local]
+ffmpeg_path = "/opt/homebrew/bin/ffmpeg"
+video_bitrate = "6000k" # keep it light
+
+[profiles.main.local.capture]
+type = "avfoundation"
+video_device = "1"
+
+[profiles.main.local.encoder]
+type = "h264_videotoolbox"
+"#;
+
+    #[test]
+    fn old_config_loads_with_defaults() {
+        let mut cfg: Config = toml::from_str(OLD_CONFIG).unwrap();
+        cfg.validate().unwrap();
+
+        let (name, profile) = cfg.profile(None).unwrap();
+        assert_eq!(name, "main");
+        assert_eq!(profile.local.fps, 60);
+        assert_eq!(profile.local.video_bitrate, "6000k");
+        assert_eq!(profile.local.audio_bitrate, "160k");
+        assert_eq!(profile.local.nice, 10);
+        assert!(profile.local.adaptive.is_none());
+        assert_eq!(profile.local.encoder.codec_name(), "h264_videotoolbox");
+        assert_eq!(profile.remote.as_ref().unwrap().ingest_port, 6000);
+        assert!(matches!(profile.target().unwrap(), StreamTarget::Remote(_)));
+    }
+
+    #[test]
+    fn output_profiles_need_a_valid_ingest_url() {
+        assert_eq!(
+            check_output_url("srt://ingest.example.com:9000?streamid=live").unwrap(),
+            OutputProtocol::Srt
+        );
+        assert_eq!(
+            check_output_url("rtmps://live.example.com:443/app/sk_live_123").unwrap(),
+            OutputProtocol::Rtmp
+        );
+        for bad in [
+            "ingest.example.com:9000",
+            "udp://239.0.0.1:1234",
+            "srt://ingest.example.com",
+            "srt://:9000",
+            "rtmp://live.example.com",
+            "rtmp://live.example.com:port/app/key",
+        ] {
+            assert!(check_output_url(bad).is_err(), "{bad}");
+        }
+
+        let direct = OLD_CONFIG.replace(
+            "[profiles.main.remote]\nhost = \"stream-box.local\"\n",
+            "[profiles.main]\noutput = \"rtmp://live.example.com/app/sk_live_123\"\n",
+        );
+        let mut cfg: Config = toml::from_str(&direct).unwrap();
+        cfg.validate().unwrap();
+        let (_, profile) = cfg.profile(None).unwrap();
+        assert!(matches!(
+            profile.target().unwrap(),
+            StreamTarget::Output(_, OutputProtocol::Rtmp)
+        ));
+        assert!(profile.summary().ends_with("-> rtmp://live.example.com/app/… (direct)"));
+
+        let both = format!("{direct}\n[profiles.main.remote]\nhost = \"box\"\n");
+        let mut cfg: Config = toml::from_str(&both).unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn invalid_values_name_the_profile_key() {
+        let local_key = "video_bitrate = \"6000k\" # keep it light";
+        let with_local =
+            |line: &str| OLD_CONFIG.replace(local_key, &format!("{local_key}\n{line}"));
+        let error = |raw: &str| {
+            let mut cfg: Config = toml::from_str(raw).unwrap();
+            format!("{:#}", cfg.validate().unwrap_err())
+        };
+
+        let err = error(&with_local("fps = 0"));
+        assert!(err.contains("profiles.main.local.fps = 0"), "{err}");
+        let err = error(&with_local("resolution = \"1921x1080\""));
+        assert!(err.contains("profiles.main.local.resolution = \"1921x1080\""), "{err}");
+        assert!(err.contains("even"), "{err}");
+        let err = error(&OLD_CONFIG.replace("\"6000k\"", "\"6 Mbps\""));
+        assert!(err.contains("profiles.main.local.video_bitrate = \"6 Mbps\""), "{err}");
+
+        let mut cfg: Config = toml::from_str(&with_local("resolution = \"2560:1440\"")).unwrap();
+        cfg.validate().unwrap();
+        assert_eq!(cfg.profiles["main"].local.resolution.as_deref(), Some("2560x1440"));
+        assert_eq!(bitrate_kbps("6M"), Some(6000.0));
+        assert_eq!(bitrate_kbps("128000"), Some(128.0));
+    }
+
+    #[test]
+    fn example_config_loads() {
+        let mut cfg: Config = toml::from_str(include_str!("../config.example.toml")).unwrap();
+        cfg.validate().unwrap();
+        assert!(cfg.profiles.contains_key(&cfg.default_profile));
+    }
+
+    #[test]
+    fn upgrade_fills_missing_keys_and_keeps_user_values() {
+        let (upgraded, added) = upgrade_config_str(OLD_CONFIG).unwrap();
+
+        assert!(added.contains(&"default_profile".to_string()));
+        assert!(added.contains(&"profiles.main.local.fps".to_string()));
+        assert!(added.contains(&"profiles.main.remote.runner".to_string()));
+        assert!(!added.iter().any(|key| key.ends_with("video_bitrate")));
+        assert!(!added.iter().any(|key| key.ends_with("filters")));
+        assert!(upgraded.contains("# my stream box"));
+        assert!(upgraded.contains("video_bitrate = \"6000k\" # keep it light"));
+
+        let cfg: Config = toml::from_str(&upgraded).unwrap();
+        let before: Config = toml::from_str(OLD_CONFIG).unwrap();
+        assert_eq!(
+            toml::to_string(&cfg).unwrap(),
+            toml::to_string(&before).unwrap()
+        );
+
+        let (again, added) = upgrade_config_str(&upgraded).unwrap();
+        assert!(added.is_empty());
+        assert_eq!(again, upgraded);
+    }
+}
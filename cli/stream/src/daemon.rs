@@ -0,0 +1,340 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use crate::adaptive::{Adaptation, BitrateController, ProgressSample, parse_progress};
+use crate::config::{Profile, StreamTarget};
+use crate::heartbeat::HeartbeatWriter;
+use crate::local;
+use crate::remote::{self, RemoteHandle};
+use crate::session::{self, SessionState};
+use crate::util::{self, notify_desktop, stream_failure_notice};
+
+pub struct DaemonOptions {
+    pub profile: String,
+    pub state_dir: PathBuf,
+    pub restart_delay: Duration,
+    /// 0 = unlimited.
+    pub max_restarts: u32,
+    pub skip_remote: bool,
+    pub notify: bool,
+    pub heartbeat: Option<PathBuf>,
+}
+
+/// How often the loop checks on ffmpeg and for a shutdown signal.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Keep `profile` streaming: (re)start the remote receiver and ffmpeg until
+/// SIGTERM/SIGINT, or until ffmpeg has died more than `max_restarts` times.
+pub fn run(profile: &Profile, opts: &DaemonOptions) -> Result<()> {
+    let target = profile.target()?;
+    let remote = match target {
+        StreamTarget::Remote(remote) if !opts.skip_remote => Some(remote),
+        _ => None,
+    };
+    let session_path = session::session_path(&opts.state_dir, &opts.profile);
+    session::ensure_not_running(&session_path, &opts.profile)?;
+
+    // Stop ffmpeg and clean up on the first signal; a second one exits immediately
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&shutdown))?;
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))?;
+    }
+
+    let log_dir = opts.state_dir.join("logs");
+    let log_name = session::file_safe(&opts.profile);
+    let mut handle: Option<RemoteHandle> = None;
+    let mut restarts = 0u32;
+    let mut monitor = Monitor {
+        controller: profile
+            .local
+            .adaptive
+            .as_ref()
+            .map(|adaptive| BitrateController::new(&profile.local, adaptive)),
+        heartbeat: opts.heartbeat.clone().map(|path| {
+            HeartbeatWriter::new(path, &opts.profile, &profile.local.video_bitrate)
+        }),
+    };
+
+    let result = loop {
+        // The start script is a no-op while the tmux session is up, so this
+        // only brings the receiver back if it died too
+        if let Some(remote) = remote {
+            match remote::build_start(remote)
+                .and_then(|(h, script)| remote::run_script(&h, &script.script).map(|()| h))
+            {
+                Ok(h) => handle = Some(h),
+                Err(err) => log(&format!("remote receiver: {err:#}")),
+            }
+        }
+
+        let local = match &mut monitor.controller {
+            Some(controller) => {
+                controller.restarted(Instant::now());
+                controller.apply(&profile.local)
+            }
+            None => profile.local.clone(),
+        };
+        let spec = local::build_command(&local, target, monitor.wants_progress())?;
+        let mut launch = local::spawn_local(&spec, &log_dir, &log_name)?;
+        log(&format!(
+            "ffmpeg started at {} (pid {}), log {}",
+            local.video_bitrate,
+            launch.pid,
+            launch.log_path.display()
+        ));
+        let mut state = SessionState::new(
+            &opts.profile,
+            target,
+            handle.clone(),
+            launch.pid,
+            launch.log_path.clone(),
+        );
+        state.daemon_pid = Some(std::process::id());
+        state.heartbeat = opts.heartbeat.clone();
+        session::write_session(&session_path, &state)?;
+        monitor.started(launch.pid, &local.video_bitrate, restarts);
+
+        let mut progress = if monitor.wants_progress() {
+            Some(ProgressTail::open(&launch.log_path)?)
+        } else {
+            None
+        };
+        let exit = wait_for_exit(&mut launch.child, &shutdown, progress.as_mut(), &mut monitor)?;
+        monitor.stopped();
+        let status = match exit {
+            Exit::Shutdown => {
+                log("shutting down");
+                break Ok(());
+            }
+            // A planned restart at the new rung; doesn't count against max_restarts
+            Exit::Adapted => continue,
+            Exit::Exited(status) => status,
+        };
+
+        restarts += 1;
+        let exit = describe_exit(status);
+        let gave_up = opts.max_restarts != 0 && restarts > opts.max_restarts;
+        log(&format!("ffmpeg {exit}"));
+        if opts.notify {
+            let (title, body) = stream_failure_notice(
+                &opts.profile,
+                &exit,
+                if gave_up { opts.max_restarts } else { restarts },
+                opts.max_restarts,
+                gave_up,
+            );
+            if let Err(err) = notify_desktop(&title, &body) {
+                log(&format!("notification failed: {err:#}"));
+            }
+        }
+        if gave_up {
+            break Err(anyhow::anyhow!(
+                "ffmpeg {exit}; gave up after {} restarts",
+                opts.max_restarts
+            ));
+        }
+
+        log(&format!(
+            "restarting in {}s ({restarts}/{})",
+            opts.restart_delay.as_secs(),
+            if opts.max_restarts == 0 {
+                "unlimited".to_string()
+            } else {
+                opts.max_restarts.to_string()
+            }
+        ));
+        if sleep_unless_shutdown(opts.restart_delay, &shutdown, &mut monitor) {
+            log("shutting down");
+            break Ok(());
+        }
+    };
+
+    if let Some(handle) = &handle {
+        if let Err(err) = remote::run_script(handle, &remote::build_stop(handle)) {
+            log(&format!("stop remote receiver: {err:#}"));
+        }
+    }
+    session::clear_session(&session_path)?;
+    // After giving up, leave the heartbeat (ffmpeg_pid null, going stale) for
+    // watchdogs to find
+    if result.is_ok() {
+        if let Some(heartbeat) = &monitor.heartbeat {
+            if let Err(err) = heartbeat.remove() {
+                log(&format!("heartbeat: {err:#}"));
+            }
+        }
+    }
+    result
+}
+
+/// What the daemon does with a running ffmpeg besides waiting on it.
+struct Monitor {
+    controller: Option<BitrateController>,
+    heartbeat: Option<HeartbeatWriter>,
+}
+
+impl Monitor {
+    /// Whether anything needs ffmpeg's progress lines (and so `-stats`).
+    fn wants_progress(&self) -> bool {
+        self.controller.is_some() || self.heartbeat.is_some()
+    }
+
+    fn started(&mut self, ffmpeg_pid: u32, bitrate: &str, restarts: u32) {
+        if let Some(heartbeat) = &mut self.heartbeat {
+            report_heartbeat(heartbeat.started(ffmpeg_pid, bitrate, restarts));
+        }
+    }
+
+    fn stopped(&mut self) {
+        if let Some(heartbeat) = &mut self.heartbeat {
+            report_heartbeat(heartbeat.stopped());
+        }
+    }
+
+    /// Returns the bitrate change to restart ffmpeg for, if any.
+    fn progress(&mut self, sample: &ProgressSample) -> Option<Adaptation> {
+        if let Some(heartbeat) = &mut self.heartbeat {
+            heartbeat.progress(sample);
+        }
+        let adaptation = self.controller.as_mut()?.observe(sample, Instant::now());
+        if let Some(adaptation) = &adaptation {
+            log(&adaptation.to_string());
+        }
+        adaptation
+    }
+
+    fn tick(&mut self) {
+        if let Some(heartbeat) = &mut self.heartbeat {
+            report_heartbeat(heartbeat.tick(Instant::now()));
+        }
+    }
+}
+
+/// A heartbeat that can't be written shouldn't take the stream down with it.
+fn report_heartbeat(result: Result<()>) {
+    if let Err(err) = result {
+        log(&format!("heartbeat: {err:#}"));
+    }
+}
+
+enum Exit {
+    /// ffmpeg died on its own.
+    Exited(ExitStatus),
+    /// Stopped by us for a bitrate change.
+    Adapted,
+    /// Stopped by us for a shutdown signal.
+    Shutdown,
+}
+
+/// Wait for ffmpeg to exit, feeding each progress line from `progress` to
+/// `monitor`. ffmpeg is stopped early on a shutdown signal or when the
+/// monitor asks for a bitrate change.
+fn wait_for_exit(
+    child: &mut Child,
+    shutdown: &AtomicBool,
+    mut progress: Option<&mut ProgressTail>,
+    monitor: &mut Monitor,
+) -> Result<Exit> {
+    loop {
+        if shutdown.load(Ordering::Relaxed) {
+            stop_child(child)?;
+            return Ok(Exit::Shutdown);
+        }
+        if let Some(status) = child.try_wait().context("wait for ffmpeg")? {
+            return Ok(Exit::Exited(status));
+        }
+        if let Some(tail) = progress.as_deref_mut() {
+            for sample in tail.read()? {
+                if monitor.progress(&sample).is_some() {
+                    stop_child(child)?;
+                    return Ok(Exit::Adapted);
+                }
+            }
+        }
+        monitor.tick();
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn stop_child(child: &mut Child) -> Result<()> {
+    let _ = util::send_signal(child.id(), libc::SIGTERM);
+    child.wait().context("wait for ffmpeg")?;
+    Ok(())
+}
+
+/// Follows an ffmpeg log as it grows and picks out the progress lines.
+struct ProgressTail {
+    file: File,
+    pending: String,
+}
+
+impl ProgressTail {
+    fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+        // A restart within the same second appends to the previous ffmpeg's log
+        file.seek(SeekFrom::End(0))
+            .with_context(|| format!("seek {}", path.display()))?;
+        Ok(Self {
+            file,
+            pending: String::new(),
+        })
+    }
+
+    /// Progress samples from whatever ffmpeg has written since the last call.
+    fn read(&mut self) -> Result<Vec<ProgressSample>> {
+        let mut buf = Vec::new();
+        self.file
+            .read_to_end(&mut buf)
+            .context("read ffmpeg log")?;
+        self.pending.push_str(&String::from_utf8_lossy(&buf));
+
+        // Progress updates end in \r, everything else in \n; keep the
+        // unterminated tail for next time
+        let Some(end) = self.pending.rfind(['\r', '\n']) else {
+            return Ok(Vec::new());
+        };
+        let samples = self.pending[..end]
+            .split(['\r', '\n'])
+            .filter_map(parse_progress)
+            .collect();
+        self.pending.drain(..=end);
+        Ok(samples)
+    }
+}
+
+/// Returns true if a shutdown signal arrived during the sleep.
+fn sleep_unless_shutdown(duration: Duration, shutdown: &AtomicBool, monitor: &mut Monitor) -> bool {
+    let mut slept = Duration::ZERO;
+    while slept < duration {
+        if shutdown.load(Ordering::Relaxed) {
+            return true;
+        }
+        monitor.tick();
+        thread::sleep(POLL_INTERVAL);
+        slept += POLL_INTERVAL;
+    }
+    shutdown.load(Ordering::Relaxed)
+}
+
+fn describe_exit(status: ExitStatus) -> String {
+    use std::os::unix::process::ExitStatusExt;
+    match (status.code(), status.signal()) {
+        (Some(code), _) => format!("exited with code {code}"),
+        (None, Some(signal)) => format!("killed by signal {signal}"),
+        (None, None) => format!("exited ({status})"),
+    }
+}
+
+fn log(message: &str) {
+    println!("[{}] {message}", Local::now().format("%H:%M:%S"));
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::util::resolve_program;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Video,
+    Audio,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureDevice {
+    pub kind: DeviceKind,
+    pub index: u32,
+    pub name: String,
+}
+
+/// Ask ffmpeg for the avfoundation inputs it can capture from.
+pub fn list_devices(ffmpeg_path: &Path) -> Result<Vec<CaptureDevice>> {
+    let program = resolve_program(ffmpeg_path)?;
+    // ffmpeg always exits non-zero here (no output given); the list is on stderr.
+    let output = Command::new(&program)
+        .args(["-hide_banner", "-f", "avfoundation", "-list_devices", "true", "-i", ""])
+        .output()
+        .with_context(|| format!("run {}", program.display()))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let devices = parse_device_list(&stderr);
+    if devices.is_empty() {
+        anyhow::bail!(
+            "no avfoundation devices found (ffmpeg output: {})",
+            stderr.trim()
+        );
+    }
+    Ok(devices)
+}
+
+/// Parse the `-list_devices` output, e.g.
+///
+/// ```text
+/// [AVFoundation indev @ 0x7f8] AVFoundation video devices:
+/// [AVFoundation indev @ 0x7f8] [0] FaceTime HD Camera
+/// [AVFoundation indev @ 0x7f8] [1] Capture screen 0
+/// [AVFoundation indev @ 0x7f8] AVFoundation audio devices:
+/// [AVFoundation indev @ 0x7f8] [0] MacBook Pro Microphone
+/// ```
+pub fn parse_device_list(output: &str) -> Vec<CaptureDevice> {
+    let mut devices = Vec::new();
+    let mut kind = None;
+
+    for line in output.lines() {
+        // Drop the "[AVFoundation indev @ 0x...]" prefix
+        let body = match line.find("] ") {
+            Some(idx) if line.starts_with('[') => line[idx + 2..].trim(),
+            _ => line.trim(),
+        };
+
+        if body.ends_with("video devices:") {
+            kind = Some(DeviceKind::Video);
+            continue;
+        }
+        if body.ends_with("audio devices:") {
+            kind = Some(DeviceKind::Audio);
+            continue;
+        }
+
+        let Some(kind) = kind else {
+            continue;
+        };
+        let Some(rest) = body.strip_prefix('[') else {
+            continue;
+        };
+        let Some((index, name)) = rest.split_once(']') else {
+            continue;
+        };
+        if let Ok(index) = index.parse::<u32>() {
+            devices.push(CaptureDevice {
+                kind,
+                index,
+                name: name.trim().to_string(),
+            });
+        }
+    }
+
+    devices
+}
+
+pub fn render_table(devices: &[CaptureDevice]) -> String {
+    let mut out = String::new();
+    for (kind, title) in [(DeviceKind::Video, "Video"), (DeviceKind::Audio, "Audio")] {
+        let group: Vec<_> = devices.iter().filter(|d| d.kind == kind).collect();
+        if group.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("{title} devices:\n"));
+        for device in group {
+            out.push_str(&format!("  {:>3}  {}\n", device.index, device.name));
+        }
+    }
+    out
+}
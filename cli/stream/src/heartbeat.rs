@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::adaptive::ProgressSample;
+
+/// How often `daemon --heartbeat` rewrites the file while streaming.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Contents of the `--heartbeat` file, for supervisors and watchdogs.
+///
+/// `updated_at` going stale means the daemon itself is stuck or gone;
+/// `last_progress_at` going stale while `updated_at` is fresh means ffmpeg
+/// is alive but no longer encoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub updated_at: DateTime<Utc>,
+    /// The daemon's pid.
+    pub pid: u32,
+    /// The running ffmpeg's pid; None while it's being restarted.
+    pub ffmpeg_pid: Option<u32>,
+    pub profile: String,
+    /// Bitrate ffmpeg was started with (the current adaptive rung), e.g. "6000k".
+    pub target_bitrate: String,
+    /// Output bitrate from ffmpeg's last progress line, in kbit/s.
+    pub bitrate_kbps: Option<f64>,
+    /// When ffmpeg last reported progress; None until the first report.
+    pub last_progress_at: Option<DateTime<Utc>>,
+    pub restarts: u32,
+}
+
+impl Heartbeat {
+    /// Why a watchdog should consider the stream down at `now`, if it should.
+    pub fn stale_reason(&self, now: DateTime<Utc>, max_age: Duration) -> Option<String> {
+        let age = |at: DateTime<Utc>| (now - at).to_std().unwrap_or_default();
+        if age(self.updated_at) > max_age {
+            return Some(format!(
+                "heartbeat not updated for {}s",
+                age(self.updated_at).as_secs()
+            ));
+        }
+        match (self.ffmpeg_pid, self.last_progress_at) {
+            (None, _) => Some("ffmpeg is not running".to_string()),
+            (Some(_), Some(at)) if age(at) > max_age => Some(format!(
+                "no ffmpeg progress for {}s",
+                age(at).as_secs()
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps the heartbeat file current from the daemon loop: `started` on every
+/// ffmpeg (re)start, `progress` per progress line, `tick` each iteration.
+pub struct HeartbeatWriter {
+    path: PathBuf,
+    state: Heartbeat,
+    last_write: Option<Instant>,
+}
+
+impl HeartbeatWriter {
+    pub fn new(path: PathBuf, profile: &str, target_bitrate: &str) -> Self {
+        Self {
+            path,
+            state: Heartbeat {
+                updated_at: Utc::now(),
+                pid: std::process::id(),
+                ffmpeg_pid: None,
+                profile: profile.to_string(),
+                target_bitrate: target_bitrate.to_string(),
+                bitrate_kbps: None,
+                last_progress_at: None,
+                restarts: 0,
+            },
+            last_write: None,
+        }
+    }
+
+    /// A new ffmpeg is running; written immediately.
+    pub fn started(&mut self, ffmpeg_pid: u32, target_bitrate: &str, restarts: u32) -> Result<()> {
+        self.state.ffmpeg_pid = Some(ffmpeg_pid);
+        self.state.target_bitrate = target_bitrate.to_string();
+        self.state.bitrate_kbps = None;
+        // Count the start as progress so a slow first frame isn't a stall
+        self.state.last_progress_at = Some(Utc::now());
+        self.state.restarts = restarts;
+        self.write(Instant::now())
+    }
+
+    /// ffmpeg exited; written immediately.
+    pub fn stopped(&mut self) -> Result<()> {
+        self.state.ffmpeg_pid = None;
+        self.write(Instant::now())
+    }
+
+    pub fn progress(&mut self, sample: &ProgressSample) {
+        self.state.last_progress_at = Some(Utc::now());
+        if sample.bitrate_kbps.is_some() {
+            self.state.bitrate_kbps = sample.bitrate_kbps;
+        }
+    }
+
+    /// Rewrite the file if `HEARTBEAT_INTERVAL` has passed since the last write.
+    pub fn tick(&mut self, now: Instant) -> Result<()> {
+        let due = self
+            .last_write
+            .is_none_or(|last| now.duration_since(last) >= HEARTBEAT_INTERVAL);
+        if !due {
+            return Ok(());
+        }
+        self.write(now)
+    }
+
+    /// Remove the file on a clean shutdown, so it doesn't read as a stall.
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .with_context(|| format!("remove {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, now: Instant) -> Result<()> {
+        self.state.updated_at = Utc::now();
+        write_heartbeat(&self.path, &self.state)?;
+        self.last_write = Some(now);
+        Ok(())
+    }
+}
+
+/// Write via a temp file and rename, so readers never see a partial file.
+pub fn write_heartbeat(path: &Path, heartbeat: &Heartbeat) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let raw = serde_json::to_string_pretty(heartbeat).context("serialize heartbeat to json")?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, raw).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(())
+}
+
+pub fn read_heartbeat(path: &Path) -> Result<Heartbeat> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writer_tracks_ffmpeg_and_watchdogs_see_stalls() {
+        let dir = std::env::temp_dir().join(format!("stream-heartbeat-{}", std::process::id()));
+        let path = dir.join("beat.json");
+        let mut writer = HeartbeatWriter::new(path.clone(), "main", "9000k");
+
+        writer.started(4250, "6000k", 1).unwrap();
+        writer.progress(&ProgressSample {
+            frame: 60,
+            drop: 0,
+            speed: Some(1.0),
+            bitrate_kbps: Some(5987.3),
+        });
+        // Progress is buffered until the next due write
+        assert_eq!(read_heartbeat(&path).unwrap().bitrate_kbps, None);
+        writer.tick(Instant::now() + HEARTBEAT_INTERVAL).unwrap();
+
+        let beat = read_heartbeat(&path).unwrap();
+        assert_eq!(beat.ffmpeg_pid, Some(4250));
+        assert_eq!(beat.target_bitrate, "6000k");
+        assert_eq!(beat.bitrate_kbps, Some(5987.3));
+        assert_eq!(beat.restarts, 1);
+
+        let max_age = Duration::from_secs(15);
+        assert_eq!(beat.stale_reason(beat.updated_at, max_age), None);
+        let later = beat.updated_at + chrono::Duration::seconds(20);
+        assert!(beat.stale_reason(later, max_age).unwrap().contains("not updated"));
+        let stalled = Heartbeat {
+            last_progress_at: Some(beat.updated_at - chrono::Duration::seconds(20)),
+            ..beat.clone()
+        };
+        let reason = stalled.stale_reason(beat.updated_at, max_age).unwrap();
+        assert!(reason.contains("no ffmpeg progress"), "{reason}");
+
+        writer.stopped().unwrap();
+        let beat = read_heartbeat(&path).unwrap();
+        let reason = beat.stale_reason(beat.updated_at, max_age);
+        assert_eq!(reason.as_deref(), Some("ffmpeg is not running"));
+
+        writer.remove().unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
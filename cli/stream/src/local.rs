@@ -1,12 +1,15 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use chrono::Local;
 
 use crate::config::{
-    AvfoundationCapture, CaptureSource, Encoder, LocalConfig, RemoteConfig, SrtConfig, Transport,
+    AvfoundationCapture, CaptureSource, Encoder, LocalConfig, RemoteConfig, SrtConfig,
+    StreamTarget, Transport, redact_output_url,
 };
 use crate::util::{join_shell_words, resolve_program};
 
@@ -21,14 +24,27 @@ pub struct CommandSpec {
 pub struct LocalLaunch {
     pub pid: u32,
     pub log_path: PathBuf,
+    /// Dropping it leaves ffmpeg running; the daemon keeps it to wait on.
+    pub child: Child,
 }
 
-pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<CommandSpec> {
+/// `stats` keeps ffmpeg's progress lines in the log (for the daemon's
+/// heartbeat); adaptive profiles always get them.
+pub fn build_command(
+    local: &LocalConfig,
+    target: StreamTarget<'_>,
+    stats: bool,
+) -> Result<CommandSpec> {
     let program = resolve_program(&local.ffmpeg_path)?;
     let mut args = Vec::new();
     args.push("-hide_banner".into());
     args.push("-loglevel".into());
     args.push("warning".into());
+    if stats || local.adaptive.is_some() {
+        // Keep progress lines in the log even at `warning` so the daemon
+        // can read `drop=`, `speed=` and `bitrate=`.
+        args.push("-stats".into());
+    }
 
     // Fast startup: minimal probing for known capture source
     args.push("-probesize".into());
@@ -41,8 +57,8 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
     let mut filter_chain = Vec::new();
     if let Some(scale) = &local.scale_filter {
         filter_chain.push(scale.clone());
-    } else if let Some(res) = &local.resolution {
-        filter_chain.push(format!("scale={res}"));
+    } else if let Some(size) = local.scale_size() {
+        filter_chain.push(format!("scale={size}"));
     }
     filter_chain.extend(local.filters.clone());
     if !filter_chain.is_empty() {
@@ -51,9 +67,9 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
     }
 
     args.push("-c:v".into());
+    args.push(local.encoder.codec_name().into());
     match &local.encoder {
         Encoder::H264VideoToolbox { quality, allow_sw } => {
-            args.push("h264_videotoolbox".into());
             if let Some(q) = quality {
                 args.push("-quality".into());
                 args.push(q.clone());
@@ -62,7 +78,6 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
             args.push(if *allow_sw { "1" } else { "0" }.into());
         }
         Encoder::HevcVideoToolbox { quality, allow_sw } => {
-            args.push("hevc_videotoolbox".into());
             if let Some(q) = quality {
                 args.push("-quality".into());
                 args.push(q.clone());
@@ -71,7 +86,6 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
             args.push(if *allow_sw { "1" } else { "0" }.into());
         }
         Encoder::Libx264 { preset, tune } => {
-            args.push("libx264".into());
             args.push("-preset".into());
             args.push(preset.clone());
             if let Some(tune) = tune {
@@ -105,22 +119,37 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
 
     args.extend(local.extra_args.clone());
 
-    args.push("-f".into());
-    args.push("mpegts".into());
-
-    let output_url = match &local.transport {
-        Some(Transport::Custom { url }) => url.clone(),
-        Some(Transport::Srt(config)) => config.build_url(remote),
-        None => SrtConfigDefaults::build_default(remote),
+    let (muxer, output_url, shown_url) = match target {
+        StreamTarget::Remote(remote) => {
+            let url = match &local.transport {
+                Some(Transport::Custom { url }) => url.clone(),
+                Some(Transport::Srt(config)) => config.build_url(remote),
+                None => SrtConfigDefaults::build_default(remote),
+            };
+            ("mpegts", url.clone(), url)
+        }
+        // The preview gets printed and pasted around; keep the stream key out of it
+        StreamTarget::Output(url, protocol) => {
+            (protocol.muxer(), url.to_string(), redact_output_url(url))
+        }
     };
-    args.push(output_url);
+    args.push("-f".into());
+    args.push(muxer.into());
 
     let nice_prefix = if local.nice != 0 {
         format!("nice -n {} ", local.nice)
     } else {
         String::new()
     };
-    let preview = format!("{}{} {}", nice_prefix, program.display(), join_shell_words(&args));
+    let mut shown_args = args.clone();
+    shown_args.push(shown_url);
+    let preview = format!(
+        "{}{} {}",
+        nice_prefix,
+        program.display(),
+        join_shell_words(&shown_args)
+    );
+    args.push(output_url);
 
     Ok(CommandSpec {
         program,
@@ -131,10 +160,140 @@ pub fn build_command(local: &LocalConfig, remote: &RemoteConfig) -> Result<Comma
     })
 }
 
-pub fn spawn_local(spec: &CommandSpec, log_dir: &Path) -> Result<LocalLaunch> {
+/// How long a snapshot may take before ffmpeg is killed. Covers device
+/// warm-up; a pending camera/screen permission prompt would otherwise hang.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// ffmpeg invocation that grabs one frame from the configured input, with
+/// the same capture args and scale/filter chain as the stream.
+pub fn build_snapshot_command(local: &LocalConfig, out: &Path) -> Result<CommandSpec> {
+    let program = resolve_program(&local.ffmpeg_path)?;
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-loglevel".into(), "error".into()];
+    args.push("-probesize".into());
+    args.push(local.probesize.to_string());
+    args.push("-analyzeduration".into());
+    args.push(local.analyzeduration.to_string());
+
+    args.extend(build_capture_args(&local.capture, local.fps));
+
+    let mut filter_chain = Vec::new();
+    if let Some(scale) = &local.scale_filter {
+        filter_chain.push(scale.clone());
+    } else if let Some(size) = local.scale_size() {
+        filter_chain.push(format!("scale={size}"));
+    }
+    filter_chain.extend(local.filters.clone());
+    if !filter_chain.is_empty() {
+        args.push("-vf".into());
+        args.push(filter_chain.join(","));
+    }
+
+    args.push("-an".into());
+    args.push("-frames:v".into());
+    args.push("1".into());
+    args.push("-update".into());
+    args.push("1".into());
+    args.push("-y".into());
+    args.push(out.display().to_string());
+
+    let preview = format!("{} {}", program.display(), join_shell_words(&args));
+    Ok(CommandSpec {
+        program,
+        args,
+        preview,
+        nice: 0,
+        realtime: false,
+    })
+}
+
+/// Capture a single frame to `out` (format from its extension, e.g. .png).
+/// The frame is written to a temp file next to `out` and renamed into place,
+/// so a failed or timed-out capture leaves nothing behind.
+pub fn capture_snapshot(local: &LocalConfig, out: &Path) -> Result<u64> {
+    let file_name = out
+        .file_name()
+        .with_context(|| format!("{} is not a file path", out.display()))?;
+    // Keep the real extension last so ffmpeg picks the right muxer
+    let tmp = out.with_file_name(format!(".partial-{}", file_name.to_string_lossy()));
+
+    let result = run_snapshot(local, &tmp).and_then(|()| {
+        let size = fs::metadata(&tmp)
+            .map(|m| m.len())
+            .with_context(|| format!("ffmpeg exited without writing {}", tmp.display()))?;
+        if size == 0 {
+            anyhow::bail!("ffmpeg wrote an empty frame");
+        }
+        fs::rename(&tmp, out).with_context(|| format!("move frame to {}", out.display()))?;
+        Ok(size)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Grab and discard one frame, to confirm the input actually produces video
+/// (for `stream check`). Returns the frame size in bytes.
+pub fn check_input_produces_video(local: &LocalConfig) -> Result<u64> {
+    let out = std::env::temp_dir().join(format!("stream-check-{}.png", std::process::id()));
+    let size = capture_snapshot(local, &out)?;
+    let _ = fs::remove_file(&out);
+    Ok(size)
+}
+
+fn run_snapshot(local: &LocalConfig, out: &Path) -> Result<()> {
+    let spec = build_snapshot_command(local, out)?;
+    let mut child = Command::new(&spec.program)
+        .args(&spec.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawn {}", spec.program.display()))?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().context("wait for ffmpeg")? {
+            break status;
+        }
+        if started.elapsed() > SNAPSHOT_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "no frame from {} after {}s (check Screen Recording/Camera permissions)",
+                capture_label(&local.capture),
+                SNAPSHOT_TIMEOUT.as_secs()
+            );
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            use std::io::Read;
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        anyhow::bail!(
+            "ffmpeg failed to capture from {} ({status}): {}",
+            capture_label(&local.capture),
+            stderr.trim()
+        );
+    }
+    Ok(())
+}
+
+fn capture_label(capture: &CaptureSource) -> String {
+    match capture {
+        CaptureSource::Avfoundation(spec) => format!("avfoundation device {}", spec.video_device),
+    }
+}
+
+/// Start ffmpeg detached, logging to `<log_dir>/stream-<name>-<timestamp>.log`.
+pub fn spawn_local(spec: &CommandSpec, log_dir: &Path, name: &str) -> Result<LocalLaunch> {
     fs::create_dir_all(log_dir).with_context(|| format!("create {}", log_dir.display()))?;
     let timestamp = Local::now().format("%Y%m%d-%H%M%S");
-    let log_path = log_dir.join(format!("stream-{timestamp}.log"));
+    let log_path = log_dir.join(format!("stream-{name}-{timestamp}.log"));
     let stdout = fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -171,6 +330,7 @@ pub fn spawn_local(spec: &CommandSpec, log_dir: &Path) -> Result<LocalLaunch> {
     Ok(LocalLaunch {
         pid: child.id(),
         log_path,
+        child,
     })
 }
 
@@ -181,16 +341,15 @@ fn build_capture_args(capture: &CaptureSource, fps: u32) -> Vec<String> {
 }
 
 fn avfoundation_args(capture: &AvfoundationCapture, fps: u32) -> Vec<String> {
-    let mut args = Vec::new();
-    args.push("-thread_queue_size".into());
-    args.push(
+    let mut args = vec![
+        "-thread_queue_size".to_string(),
         capture
             .thread_queue_size
             .map(|v| v.to_string())
             .unwrap_or_else(|| "512".into()),
-    );
-    args.push("-f".into());
-    args.push("avfoundation".into());
+        "-f".into(),
+        "avfoundation".into(),
+    ];
     if capture.capture_cursor {
         args.push("-capture_cursor".into());
         args.push("1".into());
@@ -0,0 +1,450 @@
+mod adaptive;
+mod config;
+mod daemon;
+mod devices;
+mod heartbeat;
+mod local;
+mod remote;
+mod session;
+mod util;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use clap::{Parser, Subcommand};
+
+use config::StreamTarget;
+use session::SessionState;
+
+#[derive(Parser)]
+#[command(
+    name = "stream",
+    version,
+    about = "Low-impact macOS screen streaming to a Linux receiver"
+)]
+struct Cli {
+    /// Config file (default: <config dir>/stream/config.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Manage the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Start the remote receiver and a detached local ffmpeg
+    Start {
+        #[arg(long)]
+        profile: Option<String>,
+        /// Print the ffmpeg command and remote script without running them
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't start the remote receiver (it's already running or managed elsewhere)
+        #[arg(long)]
+        skip_remote: bool,
+    },
+    /// Keep a profile streaming: restart ffmpeg and the remote receiver when they die
+    Daemon {
+        #[arg(long)]
+        profile: Option<String>,
+        /// Seconds to wait before restarting ffmpeg
+        #[arg(long, default_value = "5", value_name = "SECS")]
+        restart_delay: u64,
+        /// Give up after this many restarts (0 = unlimited)
+        #[arg(long, default_value = "10")]
+        max_restarts: u32,
+        /// Don't manage the remote receiver
+        #[arg(long)]
+        skip_remote: bool,
+        /// Post a macOS notification when ffmpeg dies (--notify=false to disable)
+        #[arg(
+            long,
+            default_value_t = true,
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set,
+            value_name = "BOOL"
+        )]
+        notify: bool,
+        /// Keep a JSON heartbeat at this path for external watchdogs
+        #[arg(long, value_name = "PATH")]
+        heartbeat: Option<PathBuf>,
+    },
+    /// Stop a running session and its remote receiver
+    Stop {
+        /// Session to stop (default: the only running one)
+        #[arg(long, conflicts_with = "all")]
+        profile: Option<String>,
+        /// Stop every session
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show running sessions
+    Status {
+        /// Only this profile's session
+        #[arg(long)]
+        profile: Option<String>,
+        /// Also ask the remote host whether its tmux session is up
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Validate the config and show the ffmpeg command a profile would run
+    Check {
+        #[arg(long)]
+        profile: Option<String>,
+        /// Also grab one frame to confirm the capture device produces video
+        #[arg(long)]
+        frame: bool,
+    },
+    /// Save one frame from a profile's capture input, scaled and filtered as streamed
+    Snapshot {
+        #[arg(long)]
+        profile: Option<String>,
+        /// Image to write; the extension picks the format (e.g. frame.png)
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+    },
+    /// List the avfoundation capture devices ffmpeg can see
+    Devices {
+        /// Use this profile's ffmpeg_path (default: the default profile, or ffmpeg on PATH)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Print the devices as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Write the example config
+    Init,
+    /// Print the config path
+    Path,
+    /// List profiles with their key settings (* marks the default)
+    List,
+    /// Check every profile and report all problems, not just the first
+    Validate,
+    /// Spell out every key that has a default, keeping your values and comments
+    Upgrade,
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    let config_path = match cli.config {
+        Some(path) => path,
+        None => default_config_path()?,
+    };
+
+    match cli.command {
+        Commands::Config { command } => match command {
+            ConfigCommands::Init => {
+                config::write_default_config(&config_path)?;
+                println!("Wrote {}", config_path.display());
+                Ok(())
+            }
+            ConfigCommands::Path => {
+                println!("{}", config_path.display());
+                Ok(())
+            }
+            ConfigCommands::List => {
+                let cfg = config::parse_from(&config_path)?;
+                for line in cfg.profile_lines() {
+                    println!("{line}");
+                }
+                Ok(())
+            }
+            ConfigCommands::Validate => handle_config_validate(&config_path),
+            ConfigCommands::Upgrade => {
+                let added = config::upgrade_config(&config_path)?;
+                if added.is_empty() {
+                    println!("{} is up to date", config_path.display());
+                } else {
+                    println!("Added {} keys to {}:", added.len(), config_path.display());
+                    for key in &added {
+                        println!("  {key}");
+                    }
+                    println!("Previous version saved as {}.bak", config_path.display());
+                }
+                Ok(())
+            }
+        },
+        Commands::Start {
+            profile,
+            dry_run,
+            skip_remote,
+        } => handle_start(&config_path, profile.as_deref(), dry_run, skip_remote),
+        Commands::Daemon {
+            profile,
+            restart_delay,
+            max_restarts,
+            skip_remote,
+            notify,
+            heartbeat,
+        } => {
+            let cfg = config::load_from(&config_path)?;
+            let (name, profile) = cfg.profile(profile.as_deref())?;
+            let opts = daemon::DaemonOptions {
+                profile: name,
+                state_dir: state_dir()?,
+                restart_delay: Duration::from_secs(restart_delay),
+                max_restarts,
+                skip_remote,
+                notify,
+                heartbeat,
+            };
+            daemon::run(profile, &opts)
+        }
+        Commands::Stop { profile, all } => handle_stop(profile.as_deref(), all),
+        Commands::Status { profile, remote } => handle_status(profile.as_deref(), remote),
+        Commands::Check { profile, frame } => {
+            handle_check(&config_path, profile.as_deref(), frame)
+        }
+        Commands::Snapshot { profile, out } => {
+            let cfg = config::load_from(&config_path)?;
+            let (_, profile) = cfg.profile(profile.as_deref())?;
+            let size = local::capture_snapshot(&profile.local, &out)?;
+            println!("Wrote {} ({size} bytes)", out.display());
+            Ok(())
+        }
+        Commands::Devices { profile, json } => {
+            handle_devices(&config_path, profile.as_deref(), json)
+        }
+    }
+}
+
+/// `~/Library/Application Support/stream` on macOS; holds the config,
+/// session files and logs.
+fn state_dir() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("could not determine the config directory")?;
+    Ok(dir.join("stream"))
+}
+
+fn default_config_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("config.toml"))
+}
+
+fn handle_start(
+    config_path: &Path,
+    profile: Option<&str>,
+    dry_run: bool,
+    skip_remote: bool,
+) -> Result<()> {
+    let cfg = config::load_from(config_path)?;
+    let (name, profile) = cfg.profile(profile)?;
+    let target = profile.target()?;
+    let spec = local::build_command(&profile.local, target, false)?;
+    let remote = match target {
+        StreamTarget::Remote(remote) if !skip_remote => Some(remote::build_start(remote)?),
+        _ => None,
+    };
+
+    if dry_run {
+        println!("{}", spec.preview);
+        if let Some((handle, script)) = &remote {
+            let host = util::render_remote_target(handle.user.as_deref(), &handle.host);
+            println!("\n# remote script for {host}\n{}", script.script);
+        }
+        return Ok(());
+    }
+
+    let dir = state_dir()?;
+    let path = session::session_path(&dir, &name);
+    session::ensure_not_running(&path, &name)?;
+
+    if let Some((handle, script)) = &remote {
+        remote::run_script(handle, &script.script).context("start remote receiver")?;
+    }
+    let launch = local::spawn_local(&spec, &dir.join("logs"), &session::file_safe(&name))?;
+
+    let handle = remote.map(|(handle, _)| handle);
+    let state = SessionState::new(&name, target, handle, launch.pid, launch.log_path);
+    session::write_session(&path, &state)?;
+
+    println!("Started {name} (pid {}) -> {}", state.local_pid, state.destination());
+    println!("Log: {}", state.log_path.display());
+    Ok(())
+}
+
+fn handle_stop(profile: Option<&str>, all: bool) -> Result<()> {
+    let dir = state_dir()?;
+    let sessions = if all {
+        session::list_sessions(&dir)?
+    } else {
+        session::find_session(&dir, profile)?.into_iter().collect()
+    };
+    if sessions.is_empty() {
+        match profile {
+            Some(profile) => println!("No session for profile {profile}"),
+            None => println!("No active stream session"),
+        }
+        return Ok(());
+    }
+
+    for (path, state) in sessions {
+        // Stop the daemon first so it doesn't restart ffmpeg
+        if let Some(pid) = state.daemon_pid.filter(|&pid| util::pid_alive(pid)) {
+            util::send_signal(pid, libc::SIGTERM)?;
+        }
+        if state.local_running() {
+            util::send_signal(state.local_pid, libc::SIGTERM)?;
+        }
+        // Direct-output sessions have no receiver to tear down
+        if let Some(handle) = &state.remote {
+            if let Err(err) = remote::run_script(handle, &remote::build_stop(handle)) {
+                eprintln!("warning: stop remote receiver for {}: {err:#}", state.profile);
+            }
+        }
+        session::clear_session(&path)?;
+        println!("Stopped {}", state.profile);
+    }
+    Ok(())
+}
+
+fn handle_status(profile: Option<&str>, check_remote: bool) -> Result<()> {
+    let dir = state_dir()?;
+    let sessions: Vec<_> = session::list_sessions(&dir)?
+        .into_iter()
+        .filter(|(_, s)| profile.is_none_or(|p| s.profile == p))
+        .collect();
+    if sessions.is_empty() {
+        println!("No active stream session");
+        return Ok(());
+    }
+
+    for (i, (_, state)) in sessions.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let local = if state.local_running() {
+            "running"
+        } else {
+            "exited"
+        };
+        println!("{}: ffmpeg pid {} {local}", state.profile, state.local_pid);
+        if let Some(pid) = state.daemon_pid {
+            println!("  daemon:      pid {pid}");
+        }
+        println!("  destination: {}", state.destination());
+        println!(
+            "  started:     {}",
+            state.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        );
+        println!("  log:         {}", state.log_path.display());
+        if let Some(path) = &state.heartbeat {
+            let health = match heartbeat::read_heartbeat(path) {
+                Ok(beat) => beat
+                    .stale_reason(Utc::now(), heartbeat::HEARTBEAT_INTERVAL * 3)
+                    .unwrap_or_else(|| "ok".to_string()),
+                Err(err) => format!("{err:#}"),
+            };
+            println!("  heartbeat:   {health} ({})", path.display());
+        }
+        if check_remote {
+            if let Some(handle) = &state.remote {
+                let remote = match remote::run_script(handle, &remote::build_status(handle)) {
+                    Ok(()) => "running".to_string(),
+                    Err(err) => format!("not running ({err:#})"),
+                };
+                println!("  remote tmux: {remote}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_check(config_path: &Path, profile: Option<&str>, frame: bool) -> Result<()> {
+    let cfg = config::load_from(config_path)?;
+    let (name, profile) = cfg.profile(profile)?;
+    println!("Config:  {}", config_path.display());
+    println!("Profile: {name}");
+    if let Some(description) = &profile.description {
+        println!("         {description}");
+    }
+    println!("Stream:  {}", profile.summary());
+
+    let target = profile.target()?;
+    if let StreamTarget::Output(url, protocol) = target {
+        // No receiver to start: stop and status won't touch ssh
+        println!(
+            "Output:  {} ({} muxer, no remote receiver)",
+            config::redact_output_url(url),
+            protocol.muxer()
+        );
+    }
+    let spec = local::build_command(&profile.local, target, false)?;
+    println!("ffmpeg:  {}", spec.program.display());
+    if spec.realtime {
+        // Nothing maps it to a scheduling class yet; don't let it look applied
+        println!("Nice:    {} (realtime = true is not applied)", spec.nice);
+    } else {
+        println!("Nice:    {}", spec.nice);
+    }
+    if frame {
+        let size = local::check_input_produces_video(&profile.local)?;
+        println!("Input:   captured a test frame ({size} bytes)");
+    }
+    println!();
+    println!("{}", spec.preview);
+    Ok(())
+}
+
+fn handle_config_validate(config_path: &Path) -> Result<()> {
+    let mut cfg = config::parse_from(config_path)?;
+    let report = cfg.validate_each();
+    let width = report.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut failed = 0;
+    for (name, issue) in &report {
+        match issue {
+            None => println!("ok     {name}"),
+            Some(issue) => {
+                failed += 1;
+                println!("error  {name:<width$}  {issue}");
+            }
+        }
+    }
+    if failed > 0 {
+        let plural = if failed == 1 { "" } else { "s" };
+        anyhow::bail!("{} has {failed} problem{plural}", config_path.display());
+    }
+    Ok(())
+}
+
+fn handle_devices(config_path: &Path, profile: Option<&str>, json: bool) -> Result<()> {
+    // Devices are what you look up before writing a config, so don't need one
+    let ffmpeg_path = if config_path.exists() {
+        let cfg = config::load_from(config_path)?;
+        let (_, profile) = cfg.profile(profile)?;
+        profile.local.ffmpeg_path.clone()
+    } else if let Some(profile) = profile {
+        anyhow::bail!(
+            "profile \"{profile}\" given but {} does not exist",
+            config_path.display()
+        );
+    } else {
+        PathBuf::from("ffmpeg")
+    };
+
+    let devices = devices::list_devices(&ffmpeg_path)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+    } else {
+        print!("{}", devices::render_table(&devices));
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{StreamTarget, redact_output_url};
+use crate::remote::RemoteHandle;
+use crate::util::{pid_alive, render_remote_target};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub profile: String,
+    pub started_at: DateTime<Utc>,
+    pub local_pid: u32,
+    pub log_path: PathBuf,
+    /// None for profiles that stream straight to an `output` URL.
+    pub remote: Option<RemoteHandle>,
+    /// Redacted ingest URL of a direct-output session, for `status`.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Set when `stream daemon` owns the session; `stop` signals it first.
+    #[serde(default)]
+    pub daemon_pid: Option<u32>,
+    /// The daemon's `--heartbeat` file, checked by `status`.
+    #[serde(default)]
+    pub heartbeat: Option<PathBuf>,
+}
+
+impl SessionState {
+    /// State for a just-started ffmpeg. `remote` is the receiver this
+    /// session started, if any; direct-output sessions record the ingest URL
+    /// with the stream key redacted instead, so stop/status never use ssh.
+    pub fn new(
+        profile: &str,
+        target: StreamTarget<'_>,
+        remote: Option<RemoteHandle>,
+        local_pid: u32,
+        log_path: PathBuf,
+    ) -> Self {
+        let (remote, output) = match target {
+            StreamTarget::Remote(_) => (remote, None),
+            StreamTarget::Output(url, _) => (None, Some(redact_output_url(url))),
+        };
+        Self {
+            profile: profile.to_string(),
+            started_at: Utc::now(),
+            local_pid,
+            log_path,
+            remote,
+            output,
+            daemon_pid: None,
+            heartbeat: None,
+        }
+    }
+
+    pub fn local_running(&self) -> bool {
+        pid_alive(self.local_pid)
+    }
+
+    /// Where this session streams: the remote receiver or the ingest URL.
+    pub fn destination(&self) -> String {
+        match (&self.remote, &self.output) {
+            (Some(remote), _) => format!(
+                "{} (tmux {})",
+                render_remote_target(remote.user.as_deref(), &remote.host),
+                remote.tmux_session
+            ),
+            (None, Some(output)) => output.clone(),
+            (None, None) => "local only".to_string(),
+        }
+    }
+}
+
+/// Legacy single-session file, still honoured when no per-profile file exists.
+const LEGACY_SESSION_FILE: &str = "session.json";
+
+/// Per-profile session file: `<dir>/session-<profile>.json`.
+pub fn session_path(dir: &Path, profile: &str) -> PathBuf {
+    dir.join(format!("session-{}.json", file_safe(profile)))
+}
+
+/// `profile` with anything but `[A-Za-z0-9_-]` replaced, for file names.
+pub fn file_safe(profile: &str) -> String {
+    profile
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Every recorded session in `dir`, sorted by profile name.
+pub fn list_sessions(dir: &Path) -> Result<Vec<(PathBuf, SessionState)>> {
+    let mut sessions = Vec::new();
+    if !dir.exists() {
+        return Ok(sessions);
+    }
+
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let is_session = name == LEGACY_SESSION_FILE
+            || (name.starts_with("session-") && name.ends_with(".json"));
+        if !is_session {
+            continue;
+        }
+        if let Some(state) = load_session(&path)? {
+            sessions.push((path, state));
+        }
+    }
+
+    sessions.sort_by(|a, b| a.1.profile.cmp(&b.1.profile));
+    Ok(sessions)
+}
+
+/// Session for `profile`, or, when no profile is given, the only session
+/// recorded. Errors if several sessions exist and none was named.
+pub fn find_session(
+    dir: &Path,
+    profile: Option<&str>,
+) -> Result<Option<(PathBuf, SessionState)>> {
+    let sessions = list_sessions(dir)?;
+
+    if let Some(profile) = profile {
+        return Ok(sessions.into_iter().find(|(_, s)| s.profile == profile));
+    }
+
+    match sessions.len() {
+        0 | 1 => Ok(sessions.into_iter().next()),
+        _ => {
+            let names: Vec<&str> = sessions.iter().map(|(_, s)| s.profile.as_str()).collect();
+            anyhow::bail!(
+                "{} sessions are active ({}); pass a profile or --all",
+                names.len(),
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Refuse to start `profile` again while its recorded ffmpeg is alive.
+pub fn ensure_not_running(path: &Path, profile: &str) -> Result<()> {
+    if let Some(existing) = load_session(path)? {
+        if existing.local_running() {
+            anyhow::bail!(
+                "profile {profile} is already streaming (pid {}); \
+                 run `stream stop --profile {profile}` first",
+                existing.local_pid
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn load_session(path: &Path) -> Result<Option<SessionState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let session: SessionState =
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    Ok(Some(session))
+}
+
+pub fn write_session(path: &Path, state: &SessionState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let raw = serde_json::to_string_pretty(state).context("serialize session state to json")?;
+    fs::write(path, raw).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+pub fn clear_session(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputProtocol;
+
+    #[test]
+    fn output_sessions_have_no_remote_and_a_redacted_url() {
+        let handle = RemoteHandle {
+            host: "box".to_string(),
+            user: Some("stream".to_string()),
+            port: None,
+            tmux_session: "streamd".to_string(),
+        };
+        let url = "rtmp://live.example.com/app/sk_live_123";
+        let target = StreamTarget::Output(url, OutputProtocol::Rtmp);
+        let state = SessionState::new("live", target, Some(handle), 42, "x.log".into());
+        assert!(state.remote.is_none());
+        assert_eq!(state.output.as_deref(), Some("rtmp://live.example.com/app/…"));
+        assert_eq!(state.destination(), "rtmp://live.example.com/app/…");
+
+        let dir = std::env::temp_dir().join(format!("stream-session-{}", std::process::id()));
+        let path = session_path(&dir, "live");
+        write_session(&path, &state).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk_live_123"), "{raw}");
+        let (_, loaded) = find_session(&dir, None).unwrap().unwrap();
+        assert!(loaded.remote.is_none());
+        assert_eq!(loaded.profile, "live");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
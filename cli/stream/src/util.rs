@@ -1,12 +1,13 @@
 use std::borrow::Cow;
 use std::env;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 use shell_escape::unix::escape;
 
 pub fn resolve_program(path: &Path) -> Result<PathBuf> {
-    if path.components().count() == 1 && path.to_string_lossy().contains('/') == false {
+    if path.components().count() == 1 && !path.to_string_lossy().contains('/') {
         let program = path
             .to_str()
             .context("program name contains invalid UTF-8")?;
@@ -59,6 +60,58 @@ pub fn join_shell_words(words: &[String]) -> String {
         .join(" ")
 }
 
+/// Post a macOS notification. Title and body go to osascript as arguments,
+/// so they need no AppleScript escaping.
+pub fn notify_desktop(title: &str, body: &str) -> Result<()> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            title,
+            body,
+        ])
+        .output()
+        .context("run osascript")?;
+    if !output.status.success() {
+        bail!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Title and body for a daemon notification about ffmpeg dying. `gave_up`
+/// is set once `max_restarts` is exhausted; 0 means unlimited.
+pub fn stream_failure_notice(
+    profile: &str,
+    exit: &str,
+    restarts: u32,
+    max_restarts: u32,
+    gave_up: bool,
+) -> (String, String) {
+    if gave_up {
+        (
+            format!("stream: {profile} stopped"),
+            format!("ffmpeg {exit}; gave up after {restarts} restarts"),
+        )
+    } else if max_restarts == 0 {
+        (
+            format!("stream: {profile} restarting"),
+            format!("ffmpeg {exit}; restart {restarts}"),
+        )
+    } else {
+        (
+            format!("stream: {profile} restarting"),
+            format!("ffmpeg {exit}; restart {restarts} of {max_restarts}"),
+        )
+    }
+}
+
 pub fn render_remote_target(user: Option<&str>, host: &str) -> String {
     if let Some(user) = user {
         format!("{user}@{host}")
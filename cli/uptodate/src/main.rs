@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
@@ -16,21 +16,51 @@ fn main() {
 async fn try_main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Parse GitHub username from URL or direct input
-    let username = parse_github_username(&cli.input)?;
+    if let Some(Commands::Ratelimit { json }) = cli.command {
+        return show_rate_limit(json).await;
+    }
 
     // Calculate since date
-    let since = if let Some(since_str) = &cli.since {
-        parse_duration(since_str)?
-    } else {
-        Utc::now() - Duration::days(30) // Default: last 30 days
+    let since = match cli.since.as_deref() {
+        Some(s) if s.trim().eq_ignore_ascii_case("auto") => Since::Auto,
+        Some(s) => Since::Window(parse_duration(s)?),
+        None => Since::Window(default_since()),
     };
 
-    eprintln!("Fetching activity for @{} since {}", username, since.format("%Y-%m-%d"));
+    let layout = SaveLayout::new(cli.out_dir.clone(), cli.name_template.clone())?;
+
+    if let Some(org) = &cli.org {
+        if cli.input.is_some() {
+            anyhow::bail!("--org cannot be combined with a username");
+        }
+        return run_org(&cli, org, since, &layout).await;
+    }
+
+    if let Some(from) = &cli.from {
+        return run_batch(&cli, from, since, &layout).await;
+    }
+
+    // Parse GitHub username from URL or direct input
+    let input = cli.input.as_deref().context("Missing GitHub username")?;
+    let username = parse_github_username(input)?;
+
+    eprintln!("Fetching activity for @{} since {}", username, since);
 
     // Fetch GitHub data
     let github_token = std::env::var("GITHUB_TOKEN").ok();
-    let contact = fetch_github_contact(&username, since, github_token.as_deref()).await?;
+    // --output is also where the previous snapshot lives
+    let snapshot = cli
+        .output
+        .clone()
+        .unwrap_or_else(|| layout.snapshot_path(&username));
+    let contact = fetch_contact_since(
+        &username,
+        since,
+        github_token.as_deref(),
+        &cli.activity_types,
+        &snapshot,
+    )
+    .await?;
 
     if cli.json {
         // Output JSON only
@@ -41,56 +71,120 @@ async fn try_main() -> Result<()> {
 
         // Sync to linsa if requested
         if cli.sync {
-            sync_to_linsa(&contact, &cli.api_url).await?;
+            sync_to_linsa(&contact, &cli.api_url, cli.force, &layout).await?;
         }
 
-        // Save to local file
-        if let Some(output) = &cli.output {
-            let json = serde_json::to_string_pretty(&contact)?;
-            std::fs::write(output, &json)?;
-            println!("\nSaved to {}", output.display());
-        } else {
-            // Default: save to ~/.db/uptodate/<username>.json
-            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-            let dir = PathBuf::from(&home).join(".db").join("uptodate");
-            std::fs::create_dir_all(&dir)?;
-            let path = dir.join(format!("{}.json", username));
-            let json = serde_json::to_string_pretty(&contact)?;
-            std::fs::write(&path, &json)?;
-            println!("\nSaved to {}", path.display());
-        }
+        // Save to local file: --output as given, else <out-dir>/<name-template>
+        let path = match &cli.output {
+            Some(output) => write_contact(output, &contact)?,
+            None => save_contact(&contact, &layout)?,
+        };
+        println!("\nSaved to {}", path.display());
     }
 
     Ok(())
 }
 
 #[derive(Parser)]
-#[command(name = "uptodate", version, about = "Fetch GitHub user activity and store as Contact")]
+#[command(
+    name = "uptodate",
+    version,
+    about = "Fetch GitHub user activity and store as Contact",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// GitHub URL or username (e.g., "steipete" or "https://github.com/steipete")
-    input: String,
+    #[arg(required_unless_present_any = ["org", "from"])]
+    input: Option<String>,
 
-    /// Time range to fetch (e.g., "7d", "30d", "3m")
+    /// Fetch every username/URL in this file (one per line, # for comments)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "org", "output"])]
+    from: Option<PathBuf>,
+
+    /// List an organization's public members instead of a single user
+    #[arg(long, value_name = "NAME")]
+    org: Option<String>,
+
+    /// With --org, list the org's repositories (by stars) instead of members
+    #[arg(long, requires = "org")]
+    repos: bool,
+
+    /// With --org, fetch a full Contact for each member
+    #[arg(long, requires = "org", conflicts_with = "repos")]
+    expand: bool,
+
+    /// With --org --expand, save each Contact to --out-dir
+    #[arg(long, requires = "expand")]
+    save: bool,
+
+    /// Maximum number of org members/repos to list
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+
+    /// Concurrent requests for --org --expand and --from
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+
+    /// Time range to fetch (e.g., "7d", "30d", "3m"), or "auto" to fetch only
+    /// what's new since the saved snapshot (see --out-dir) and merge it in
     #[arg(long)]
     since: Option<String>,
 
+    /// Which kinds of events count as activity
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = ActivityKind::all()
+    )]
+    activity_types: Vec<ActivityKind>,
+
     /// Output JSON only (no file save)
     #[arg(long)]
     json: bool,
 
-    /// Output file path (default: ~/.db/uptodate/<username>.json)
+    /// Output file path (default: <out-dir>/<name-template>); --since auto reads it back
     #[arg(long, short)]
     output: Option<PathBuf>,
 
-    /// Post to linsa API after fetching
+    /// Directory snapshots are saved to and, for --since auto, read from;
+    /// --sync keeps what it last posted in its synced/ (default: ~/.db/uptodate)
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Snapshot file name inside --out-dir, with {username}, {platform} and
+    /// {date} (the fetch day, YYYY-MM-DD); may contain subdirectories, e.g.
+    /// "{date}/{username}.json" (default: "{username}.json")
+    #[arg(long, value_name = "TEMPLATE")]
+    name_template: Option<String>,
+
+    /// Post to linsa API after fetching (skipped when nothing changed since the last sync)
     #[arg(long)]
     sync: bool,
 
+    /// With --sync, post even if nothing changed since the last sync
+    #[arg(long, requires = "sync")]
+    force: bool,
+
     /// Linsa API URL (default: http://localhost:3000)
     #[arg(long, default_value = "http://localhost:3000")]
     api_url: String,
 }
 
+#[derive(clap::Subcommand)]
+enum Commands {
+    /// Show remaining GitHub API quota (core and search) for GITHUB_TOKEN
+    Ratelimit {
+        /// Output JSON only
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 fn parse_github_username(input: &str) -> Result<String> {
     let input = input.trim();
 
@@ -130,6 +224,495 @@ fn parse_duration(s: &str) -> Result<DateTime<Utc>> {
     }
 }
 
+/// Window used when --since isn't given (and by `auto` without a snapshot).
+const DEFAULT_SINCE_DAYS: i64 = 30;
+
+fn default_since() -> DateTime<Utc> {
+    Utc::now() - Duration::days(DEFAULT_SINCE_DAYS)
+}
+
+/// Where to start fetching activity from.
+#[derive(Debug, Clone, Copy)]
+enum Since {
+    Window(DateTime<Utc>),
+    /// Each user's saved `last_fetched`, merging into the saved activity
+    Auto,
+}
+
+impl std::fmt::Display for Since {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Since::Window(since) => write!(f, "{}", format_day(since.date_naive())),
+            Since::Auto => write!(f, "their last fetch"),
+        }
+    }
+}
+
+fn uptodate_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(&home).join(".db").join("uptodate")
+}
+
+/// Days as they appear in file names and summaries.
+fn format_day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+const DEFAULT_NAME_TEMPLATE: &str = "{username}.json";
+const NAME_PLACEHOLDERS: &[&str] = &["{username}", "{platform}", "{date}"];
+
+/// Where snapshots live: `--out-dir` joined with the rendered `--name-template`.
+#[derive(Debug, Clone)]
+struct SaveLayout {
+    dir: PathBuf,
+    template: String,
+}
+
+impl SaveLayout {
+    fn new(dir: Option<PathBuf>, template: Option<String>) -> Result<Self> {
+        let template = template.unwrap_or_else(|| DEFAULT_NAME_TEMPLATE.to_string());
+        let rest = NAME_PLACEHOLDERS
+            .iter()
+            .fold(template.clone(), |rest, placeholder| rest.replace(placeholder, ""));
+        if rest.contains(['{', '}']) {
+            anyhow::bail!(
+                "Unknown placeholder in --name-template {:?} (use {})",
+                template,
+                NAME_PLACEHOLDERS.join(", ")
+            );
+        }
+        // Otherwise every user in a batch would overwrite the same file
+        if !template.contains("{username}") {
+            anyhow::bail!("--name-template must include {{username}}");
+        }
+        if std::path::Path::new(&template).is_absolute() {
+            anyhow::bail!("--name-template is relative to --out-dir; pass the directory there");
+        }
+        Ok(Self {
+            dir: dir.unwrap_or_else(uptodate_dir),
+            template,
+        })
+    }
+
+    fn path(&self, username: &str, platform: &str, date: NaiveDate) -> PathBuf {
+        let name = self
+            .template
+            .replace("{username}", username)
+            .replace("{platform}", platform)
+            .replace("{date}", &format_day(date));
+        self.dir.join(name)
+    }
+
+    /// Where a run today saves `username`, and so where --since auto looks
+    /// for the previous snapshot. With a {date} template that's only today's.
+    fn snapshot_path(&self, username: &str) -> PathBuf {
+        self.path(username, "github", Utc::now().date_naive())
+    }
+
+    fn contact_path(&self, contact: &Contact) -> PathBuf {
+        self.path(
+            &contact.username,
+            &contact.platform,
+            contact.last_fetched.date_naive(),
+        )
+    }
+}
+
+fn save_contact(contact: &Contact, layout: &SaveLayout) -> Result<PathBuf> {
+    write_contact(&layout.contact_path(contact), contact)
+}
+
+fn write_contact(path: &std::path::Path, contact: &Contact) -> Result<PathBuf> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(contact)?;
+    std::fs::write(path, &json).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path.to_path_buf())
+}
+
+/// The Contact saved by a previous run at `path`, if any.
+fn load_contact(path: &std::path::Path) -> Option<Contact> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Fetch a Contact. With `Since::Auto` only activity after the `snapshot`
+/// file's `last_fetched` is requested, and the snapshot's activity is
+/// kept alongside it; without a snapshot it falls back to the default window.
+async fn fetch_contact_since(
+    username: &str,
+    since: Since,
+    token: Option<&str>,
+    types: &[ActivityKind],
+    snapshot: &std::path::Path,
+) -> Result<Contact> {
+    let previous = match since {
+        Since::Window(_) => None,
+        Since::Auto => load_contact(snapshot),
+    };
+    let from = match (since, &previous) {
+        (Since::Window(since), _) => since,
+        (Since::Auto, Some(previous)) => previous.last_fetched,
+        (Since::Auto, None) => {
+            eprintln!(
+                "No snapshot for @{}, fetching the last {} days",
+                username, DEFAULT_SINCE_DAYS
+            );
+            default_since()
+        }
+    };
+
+    let mut contact = fetch_github_contact(username, from, token, types).await?;
+    if let Some(previous) = previous {
+        let stored = previous.recent_activity.len();
+        contact.recent_activity =
+            merge_activity(std::mem::take(&mut contact.recent_activity), previous.recent_activity);
+        // Score the merged history over the usual window, not just since the last fetch
+        contact.activity_score =
+            activity_score(&contact.recent_activity, default_since(), contact.last_fetched);
+        // Keep the last calendar if this fetch couldn't get one
+        if contact.contributions.is_empty() {
+            contact.contributions = previous.contributions;
+        }
+        eprintln!(
+            "{} new events since {}, {} total",
+            contact.recent_activity.len().saturating_sub(stored),
+            previous.last_fetched.format("%Y-%m-%d %H:%M"),
+            contact.recent_activity.len()
+        );
+    }
+    Ok(contact)
+}
+
+/// Stored activity plus newly fetched, newest first; an event already stored
+/// (same url and date) isn't added twice.
+fn merge_activity(
+    fetched: Vec<GitHubActivity>,
+    stored: Vec<GitHubActivity>,
+) -> Vec<GitHubActivity> {
+    let mut seen = std::collections::HashSet::new();
+    let mut merged: Vec<GitHubActivity> = stored
+        .into_iter()
+        .chain(fetched)
+        .filter(|a| seen.insert((a.url.clone(), a.date)))
+        .collect();
+    merged.sort_by_key(|a| std::cmp::Reverse(a.date));
+    merged
+}
+
+// === Org mode ===
+
+async fn run_org(cli: &Cli, org: &str, since: Since, layout: &SaveLayout) -> Result<()> {
+    let github_token = std::env::var("GITHUB_TOKEN").ok();
+    let (client, headers) = github_client(github_token.as_deref())?;
+    let per_page = cli.limit.clamp(1, 100);
+
+    if cli.repos {
+        eprintln!("Fetching repos for {}...", org);
+        // The API can't sort by stars, so rank the whole list here
+        let url = format!("https://api.github.com/orgs/{}/repos?type=public", org);
+        let repos: Vec<GitHubRepo> = fetch_all_pages(&client, &url, &headers, "org repos").await?;
+        let mut repos: Vec<RepoInfo> = repos.iter().map(repo_info).collect();
+        repos.sort_by_key(|r| std::cmp::Reverse(r.stars));
+        repos.truncate(cli.limit);
+
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&repos)?);
+        } else {
+            println!("\n{} ({} repos)", org, repos.len());
+            println!("{}", "=".repeat(40));
+            for repo in &repos {
+                let lang = repo.language.as_deref().unwrap_or("?");
+                let desc = repo.description.as_deref().unwrap_or("");
+                println!("  {} ({}) - {} stars  {}", repo.name, lang, repo.stars, desc);
+            }
+        }
+        return Ok(());
+    }
+
+    eprintln!("Fetching public members of {}...", org);
+    let url = format!(
+        "https://api.github.com/orgs/{}/public_members?per_page={}",
+        org, per_page
+    );
+    let mut members: Vec<GitHubMember> = fetch_json(&client, &url, &headers, "org members").await?;
+    members.truncate(cli.limit);
+
+    if !cli.expand {
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&members)?);
+        } else {
+            println!("\n{} ({} public members)", org, members.len());
+            println!("{}", "=".repeat(40));
+            for member in &members {
+                println!("  @{}  {}", member.login, member.html_url);
+            }
+        }
+        return Ok(());
+    }
+
+    // Expand each member into a Contact, a few at a time
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(cli.concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    for member in &members {
+        let login = member.login.clone();
+        let token = github_token.clone();
+        let types = cli.activity_types.clone();
+        let layout = layout.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let snapshot = layout.snapshot_path(&login);
+            let contact =
+                fetch_contact_since(&login, since, token.as_deref(), &types, &snapshot).await;
+            contact.with_context(|| format!("@{}", login))
+        });
+    }
+
+    let mut contacts = Vec::with_capacity(members.len());
+    while let Some(result) = tasks.join_next().await {
+        match result? {
+            Ok(contact) => contacts.push(contact),
+            Err(err) => eprintln!("Skipping {:#}", err),
+        }
+    }
+    contacts.sort_by_key(|c| std::cmp::Reverse(c.followers));
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&contacts)?);
+        return Ok(());
+    }
+
+    println!("\n{} ({} members)", org, contacts.len());
+    println!("{}", "=".repeat(40));
+    for contact in &contacts {
+        println!(
+            "  @{} ({}) - {} followers | {} repos | {} recent events | score {:.1}",
+            contact.username,
+            contact.name,
+            contact.followers,
+            contact.repos,
+            contact.recent_activity.len(),
+            contact.activity_score
+        );
+    }
+
+    if cli.save {
+        for contact in &contacts {
+            save_contact(contact, layout)?;
+        }
+        println!("\nSaved {} contacts to {}", contacts.len(), layout.dir.display());
+    }
+
+    Ok(())
+}
+
+// === Batch mode ===
+
+/// Usernames from a --from file, in order, without duplicates. Lines that
+/// aren't usable come back as errors so they show up in the summary.
+fn read_batch_file(path: &std::path::Path) -> Result<Vec<Result<String, String>>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_github_username(line) {
+            Ok(username) => {
+                if seen.insert(username.to_lowercase()) {
+                    entries.push(Ok(username));
+                }
+            }
+            Err(err) => entries.push(Err(format!("{}: {:#}", line, err))),
+        }
+    }
+    Ok(entries)
+}
+
+async fn run_batch(
+    cli: &Cli,
+    path: &std::path::Path,
+    since: Since,
+    layout: &SaveLayout,
+) -> Result<()> {
+    let entries = read_batch_file(path)?;
+    if entries.is_empty() {
+        anyhow::bail!("No usernames in {}", path.display());
+    }
+    eprintln!(
+        "Fetching activity for {} users since {}",
+        entries.len(),
+        since
+    );
+
+    let github_token = std::env::var("GITHUB_TOKEN").ok();
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(cli.concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let Ok(username) = entry else {
+            continue;
+        };
+        let username = username.clone();
+        let token = github_token.clone();
+        let types = cli.activity_types.clone();
+        let layout = layout.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let snapshot = layout.snapshot_path(&username);
+            let contact =
+                fetch_contact_since(&username, since, token.as_deref(), &types, &snapshot).await;
+            anyhow::Ok((idx, contact))
+        });
+    }
+
+    // Outcome per input line, kept in file order for the summary
+    let mut results: Vec<Result<Contact, String>> = entries
+        .iter()
+        .map(|entry| Err(entry.as_ref().err().cloned().unwrap_or_default()))
+        .collect();
+    while let Some(joined) = tasks.join_next().await {
+        let (idx, contact) = joined??;
+        results[idx] = contact.map_err(|err| format!("{:#}", err));
+    }
+
+    // Users whose sync was skipped because nothing changed
+    let mut unchanged = std::collections::HashSet::new();
+
+    if cli.json {
+        let mut contacts: Vec<&Contact> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        contacts.sort_by(|a, b| b.activity_score.total_cmp(&a.activity_score));
+        println!("{}", serde_json::to_string_pretty(&contacts)?);
+    } else {
+        for result in results.iter_mut() {
+            let Ok(contact) = result else {
+                continue;
+            };
+            let outcome = match save_contact(contact, layout) {
+                Ok(_) if cli.sync => sync_to_linsa(contact, &cli.api_url, cli.force, layout).await,
+                saved => saved.map(|_| true),
+            };
+            match outcome {
+                Ok(false) => {
+                    unchanged.insert(contact.username.clone());
+                }
+                Ok(true) => {}
+                Err(err) => *result = Err(format!("{:#}", err)),
+            }
+        }
+
+        // Most active first, then failures in file order
+        let mut ranked: Vec<&Contact> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        ranked.sort_by(|a, b| b.activity_score.total_cmp(&a.activity_score));
+
+        println!("\nBatch summary ({})", path.display());
+        println!("{}", "=".repeat(40));
+        for contact in ranked {
+            let sync_note = if !cli.sync {
+                ""
+            } else if unchanged.contains(&contact.username) {
+                ", unchanged"
+            } else {
+                ", synced"
+            };
+            println!(
+                "  ok    @{} - score {:.1}, {} recent events{}",
+                contact.username,
+                contact.activity_score,
+                contact.recent_activity.len(),
+                sync_note
+            );
+        }
+        for (entry, result) in entries.iter().zip(&results) {
+            match (entry, result) {
+                (Ok(_), Ok(_)) => {}
+                (Ok(username), Err(err)) => println!("  FAIL  @{} - {}", username, err),
+                (Err(err), _) => println!("  FAIL  {}", err),
+            }
+        }
+        let saved = results.iter().filter(|r| r.is_ok()).count();
+        if saved > 0 {
+            println!("\nSaved {} contacts to {}", saved, layout.dir.display());
+        }
+    }
+
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} users failed", failed, results.len());
+    }
+    Ok(())
+}
+
+// === Rate limit ===
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    resources: RateLimitResources,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RateLimitResources {
+    core: RateLimit,
+    search: RateLimit,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RateLimit {
+    limit: u64,
+    remaining: u64,
+    /// Unix timestamp when the window resets
+    reset: i64,
+}
+
+async fn show_rate_limit(json: bool) -> Result<()> {
+    let github_token = std::env::var("GITHUB_TOKEN").ok();
+    let (client, headers) = github_client(github_token.as_deref())?;
+
+    // Querying /rate_limit doesn't count against the quota
+    let response: RateLimitResponse = fetch_json(
+        &client,
+        "https://api.github.com/rate_limit",
+        &headers,
+        "rate limit",
+    )
+    .await?;
+    let resources = response.resources;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&resources)?);
+        return Ok(());
+    }
+
+    let auth = if github_token.is_some() {
+        "GITHUB_TOKEN"
+    } else {
+        "unauthenticated (set GITHUB_TOKEN for higher limits)"
+    };
+    println!("Auth: {}", auth);
+
+    for (name, limit) in [("core", &resources.core), ("search", &resources.search)] {
+        let reset = DateTime::from_timestamp(limit.reset, 0).unwrap_or_else(Utc::now);
+        let minutes = (reset - Utc::now()).num_minutes().max(0);
+        println!(
+            "  {:<7} {:>5}/{:<5} remaining, resets {} (in {}m)",
+            name,
+            limit.remaining,
+            limit.limit,
+            reset.with_timezone(&chrono::Local).format("%H:%M"),
+            minutes
+        );
+    }
+
+    Ok(())
+}
+
 // === GitHub API Types ===
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,7 +730,14 @@ pub struct Contact {
     pub followers: u32,
     pub following: u32,
     pub recent_activity: Vec<GitHubActivity>,
+    /// How active the user is right now; see `activity_score`
+    #[serde(default)]
+    pub activity_score: f64,
     pub top_repos: Vec<RepoInfo>,
+    /// Contributions per day over the last year, oldest first (only fetched
+    /// when GITHUB_TOKEN is set)
+    #[serde(default)]
+    pub contributions: Vec<(NaiveDate, u32)>,
     pub last_fetched: DateTime<Utc>,
 }
 
@@ -171,6 +761,9 @@ pub struct RepoInfo {
     pub forks: u32,
     pub language: Option<String>,
     pub updated_at: DateTime<Utc>,
+    /// Pinned on the user's profile (only known when GITHUB_TOKEN is set)
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 // GitHub API response types
@@ -202,6 +795,12 @@ struct GitHubRepo {
     fork: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct GitHubMember {
+    login: String,
+    html_url: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct GitHubEvent {
     #[serde(rename = "type")]
@@ -220,92 +819,76 @@ async fn fetch_github_contact(
     username: &str,
     since: DateTime<Utc>,
     token: Option<&str>,
+    types: &[ActivityKind],
 ) -> Result<Contact> {
-    let client = reqwest::Client::builder()
-        .user_agent("uptodate-cli/0.1")
-        .build()?;
-
-    // Build headers with optional auth
-    let mut headers = reqwest::header::HeaderMap::new();
-    if let Some(token) = token {
-        headers.insert(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", token).parse()?,
-        );
-    }
+    let (client, headers) = github_client(token)?;
 
-    // Fetch user profile
-    eprint!("Fetching profile...");
+    // Fetch profile, repos (sorted by updated) and recent events concurrently
+    eprint!("Fetching profile, repos and activity...");
     let user_url = format!("https://api.github.com/users/{}", username);
-    let user: GitHubUser = client
-        .get(&user_url)
-        .headers(headers.clone())
-        .send()
-        .await?
-        .error_for_status()
-        .context("Failed to fetch user profile")?
-        .json()
-        .await?;
-    eprintln!(" done");
-
-    // Fetch repos (sorted by updated)
-    eprint!("Fetching repos...");
     let repos_url = format!(
         "https://api.github.com/users/{}/repos?sort=updated&per_page=100",
         username
     );
-    let repos: Vec<GitHubRepo> = client
-        .get(&repos_url)
-        .headers(headers.clone())
-        .send()
-        .await?
-        .error_for_status()
-        .context("Failed to fetch repos")?
-        .json()
-        .await?;
-    eprintln!(" {} repos", repos.len());
+    let events_url = format!(
+        "https://api.github.com/users/{}/events?per_page=100",
+        username
+    );
+    // Pinned repos and the contribution calendar are GraphQL-only, which
+    // requires a token; skip quietly without one
+    let pinned = async {
+        let pinned = match token {
+            Some(_) => fetch_pinned_repos(&client, &headers, username)
+                .await
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        Ok::<_, anyhow::Error>(pinned)
+    };
+    let contributions = async {
+        let contributions = match token {
+            Some(_) => fetch_contribution_calendar(&client, &headers, username)
+                .await
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        Ok::<_, anyhow::Error>(contributions)
+    };
+    let (user, repos, events, pinned, contributions): (
+        GitHubUser,
+        Vec<GitHubRepo>,
+        Vec<GitHubEvent>,
+        _,
+        _,
+    ) = tokio::try_join!(
+        fetch_json(&client, &user_url, &headers, "user profile"),
+        fetch_json(&client, &repos_url, &headers, "repos"),
+        fetch_json(&client, &events_url, &headers, "events"),
+        pinned,
+        contributions,
+    )?;
+    eprintln!(" {} repos, {} events", repos.len(), events.len());
 
-    // Get top repos (non-forks, sorted by stars)
-    let mut top_repos: Vec<RepoInfo> = repos
+    // Top repos: pinned first, then non-forks by stars
+    let mut by_stars: Vec<RepoInfo> = repos
         .iter()
         .filter(|r| !r.fork)
-        .map(|r| RepoInfo {
-            name: r.name.clone(),
-            full_name: r.full_name.clone(),
-            description: r.description.clone(),
-            url: r.html_url.clone(),
-            stars: r.stargazers_count,
-            forks: r.forks_count,
-            language: r.language.clone(),
-            updated_at: r.updated_at,
-        })
+        .filter(|r| !pinned.iter().any(|p: &RepoInfo| p.full_name == r.full_name))
+        .map(repo_info)
         .collect();
-    top_repos.sort_by(|a, b| b.stars.cmp(&a.stars));
+    by_stars.sort_by_key(|r| std::cmp::Reverse(r.stars));
+    let mut top_repos = pinned;
+    top_repos.extend(by_stars);
     top_repos.truncate(10);
 
-    // Fetch recent events
-    eprint!("Fetching activity...");
-    let events_url = format!(
-        "https://api.github.com/users/{}/events?per_page=100",
-        username
-    );
-    let events: Vec<GitHubEvent> = client
-        .get(&events_url)
-        .headers(headers.clone())
-        .send()
-        .await?
-        .error_for_status()
-        .context("Failed to fetch events")?
-        .json()
-        .await?;
-    eprintln!(" {} events", events.len());
-
     // Convert events to activities
     let recent_activity: Vec<GitHubActivity> = events
         .into_iter()
         .filter(|e| e.created_at >= since)
-        .filter_map(|e| event_to_activity(e))
+        .filter_map(|e| event_to_activity(e, types))
         .collect();
+    let now = Utc::now();
+    let activity_score = activity_score(&recent_activity, since, now);
 
     Ok(Contact {
         name: user.name.unwrap_or_else(|| user.login.clone()),
@@ -321,12 +904,416 @@ async fn fetch_github_contact(
         followers: user.followers,
         following: user.following,
         recent_activity,
+        activity_score,
         top_repos,
-        last_fetched: Utc::now(),
+        contributions,
+        last_fetched: now,
     })
 }
 
-fn event_to_activity(event: GitHubEvent) -> Option<GitHubActivity> {
+// === Activity score ===
+//
+//   activity_score = sum over activities of weight(type) * 0.5^(age / half_life)
+//
+// where half_life = SCORE_HALF_LIFE * (now - since), so an event from today
+// counts fully, one a quarter of the window old (7.5 days with the default
+// 30-day --since) counts half, and one at the start of the window 1/16.
+// A push counts once however many commits it carries.
+
+/// Weight per activity type; types not listed count 0.
+const SCORE_WEIGHTS: &[(&str, f64)] = &[
+    ("commit", 1.0),
+    ("pr", 3.0),
+    ("review", 2.0),
+    ("issue", 2.0),
+    ("release", 3.0),
+    ("create", 1.0),
+    ("fork", 0.5),
+    ("star", 0.25),
+];
+
+/// Half-life as a fraction of the `since` window.
+const SCORE_HALF_LIFE: f64 = 0.25;
+
+fn activity_score(activity: &[GitHubActivity], since: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let window_days = ((now - since).num_seconds() as f64 / 86_400.0).max(1.0);
+    let half_life = window_days * SCORE_HALF_LIFE;
+
+    let score: f64 = activity
+        .iter()
+        .map(|a| {
+            let weight = SCORE_WEIGHTS
+                .iter()
+                .find(|(kind, _)| *kind == a.activity_type)
+                .map_or(0.0, |(_, w)| *w);
+            let age_days = ((now - a.date).num_seconds() as f64 / 86_400.0).max(0.0);
+            weight * 0.5_f64.powf(age_days / half_life)
+        })
+        .sum();
+    (score * 100.0).round() / 100.0
+}
+
+fn repo_info(r: &GitHubRepo) -> RepoInfo {
+    RepoInfo {
+        name: r.name.clone(),
+        full_name: r.full_name.clone(),
+        description: r.description.clone(),
+        url: r.html_url.clone(),
+        stars: r.stargazers_count,
+        forks: r.forks_count,
+        language: r.language.clone(),
+        updated_at: r.updated_at,
+        pinned: false,
+    }
+}
+
+const PINNED_REPOS_QUERY: &str = r#"query($login: String!) {
+  user(login: $login) {
+    pinnedItems(first: 6, types: REPOSITORY) {
+      nodes {
+        ... on Repository {
+          name
+          nameWithOwner
+          description
+          url
+          stargazerCount
+          forkCount
+          primaryLanguage { name }
+          updatedAt
+        }
+      }
+    }
+  }
+}"#;
+
+#[derive(Debug, Deserialize)]
+struct PinnedResponse {
+    data: Option<PinnedData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedData {
+    user: Option<PinnedUser>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinnedUser {
+    pinned_items: PinnedItems,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedItems {
+    nodes: Vec<PinnedRepo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinnedRepo {
+    name: String,
+    name_with_owner: String,
+    description: Option<String>,
+    url: String,
+    stargazer_count: u32,
+    fork_count: u32,
+    primary_language: Option<PinnedLanguage>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedLanguage {
+    name: String,
+}
+
+/// Repos pinned on a user's profile, in pinned order. GraphQL only (the
+/// REST API doesn't expose them) and GraphQL always needs a token.
+async fn fetch_pinned_repos(
+    client: &reqwest::Client,
+    headers: &reqwest::header::HeaderMap,
+    username: &str,
+) -> Result<Vec<RepoInfo>> {
+    let body = serde_json::json!({
+        "query": PINNED_REPOS_QUERY,
+        "variables": { "login": username },
+    });
+    let response: PinnedResponse = client
+        .post("https://api.github.com/graphql")
+        .headers(headers.clone())
+        .json(&body)
+        .send()
+        .await
+        .context("Failed to fetch pinned repos")?
+        .error_for_status()
+        .context("Failed to fetch pinned repos")?
+        .json()
+        .await
+        .context("Failed to parse pinned repos")?;
+
+    let nodes = response
+        .data
+        .and_then(|d| d.user)
+        .map(|u| u.pinned_items.nodes)
+        .unwrap_or_default();
+    Ok(nodes
+        .into_iter()
+        .map(|r| RepoInfo {
+            name: r.name,
+            full_name: r.name_with_owner,
+            description: r.description,
+            url: r.url,
+            stars: r.stargazer_count,
+            forks: r.fork_count,
+            language: r.primary_language.map(|l| l.name),
+            updated_at: r.updated_at,
+            pinned: true,
+        })
+        .collect())
+}
+
+const CONTRIBUTIONS_QUERY: &str = r#"query($login: String!) {
+  user(login: $login) {
+    contributionsCollection {
+      contributionCalendar {
+        weeks {
+          contributionDays {
+            date
+            contributionCount
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+#[derive(Debug, Deserialize)]
+struct ContributionsResponse {
+    data: Option<ContributionsData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContributionsData {
+    user: Option<ContributionsUser>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsUser {
+    contributions_collection: ContributionsCollection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+    contribution_calendar: ContributionCalendar,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContributionCalendar {
+    weeks: Vec<ContributionWeek>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionWeek {
+    contribution_days: Vec<ContributionDay>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionDay {
+    date: NaiveDate,
+    contribution_count: u32,
+}
+
+/// The profile's contribution calendar (the last year, per day), as shown
+/// on GitHub. GraphQL only, so it needs a token like `fetch_pinned_repos`.
+async fn fetch_contribution_calendar(
+    client: &reqwest::Client,
+    headers: &reqwest::header::HeaderMap,
+    username: &str,
+) -> Result<Vec<(NaiveDate, u32)>> {
+    let body = serde_json::json!({
+        "query": CONTRIBUTIONS_QUERY,
+        "variables": { "login": username },
+    });
+    let response: ContributionsResponse = client
+        .post("https://api.github.com/graphql")
+        .headers(headers.clone())
+        .json(&body)
+        .send()
+        .await
+        .context("Failed to fetch contributions")?
+        .error_for_status()
+        .context("Failed to fetch contributions")?
+        .json()
+        .await
+        .context("Failed to parse contributions")?;
+
+    let weeks = response
+        .data
+        .and_then(|d| d.user)
+        .map(|u| u.contributions_collection.contribution_calendar.weeks)
+        .unwrap_or_default();
+    Ok(weeks
+        .into_iter()
+        .flat_map(|w| w.contribution_days)
+        .map(|d| (d.date, d.contribution_count))
+        .collect())
+}
+
+fn github_client(token: Option<&str>) -> Result<(reqwest::Client, reqwest::header::HeaderMap)> {
+    let client = reqwest::Client::builder()
+        .user_agent("uptodate-cli/0.1")
+        .build()?;
+
+    // Build headers with optional auth
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(token) = token {
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            format!("Bearer {}", token).parse()?,
+        );
+    }
+
+    Ok((client, headers))
+}
+
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &reqwest::header::HeaderMap,
+    what: &str,
+) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        let response = client
+            .get(url)
+            .headers(headers.clone())
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", what))?;
+
+        if attempt < MAX_RATE_LIMIT_RETRIES {
+            if let Some(wait) = rate_limit_backoff(&response, attempt) {
+                attempt += 1;
+                eprintln!(
+                    "Rate limited fetching {}, retrying in {}s...",
+                    what,
+                    wait.as_secs()
+                );
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+        }
+
+        return response
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch {}", what))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {}", what));
+    }
+}
+
+/// Every page of a list endpoint, 100 items at a time. `url` already has a
+/// query string.
+async fn fetch_all_pages<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &reqwest::header::HeaderMap,
+    what: &str,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    for page in 1.. {
+        let url = format!("{}&per_page=100&page={}", url, page);
+        let batch: Vec<T> = fetch_json(client, &url, headers, what).await?;
+        let last = batch.len() < 100;
+        items.extend(batch);
+        if last {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// How long to wait before retrying a rate-limited response, or None if the
+/// response wasn't rate limited. Uses `Retry-After` / `X-RateLimit-Reset`
+/// when GitHub sends them, otherwise exponential backoff. Capped at a minute.
+fn rate_limit_backoff(response: &reqwest::Response, attempt: u32) -> Option<std::time::Duration> {
+    let status = response.status();
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok())
+    };
+
+    let exhausted = header("x-ratelimit-remaining") == Some(0);
+    let limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || (status == reqwest::StatusCode::FORBIDDEN
+            && (exhausted || header("retry-after").is_some()));
+    if !limited {
+        return None;
+    }
+
+    let secs = header("retry-after")
+        .or_else(|| header("x-ratelimit-reset").map(|reset| reset - Utc::now().timestamp()))
+        .unwrap_or(1 << (attempt + 1))
+        .clamp(1, 60);
+    Some(std::time::Duration::from_secs(secs as u64))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ActivityKind {
+    #[value(alias = "commit")]
+    Push,
+    Pr,
+    Issue,
+    Review,
+    Release,
+    Star,
+    Fork,
+    Create,
+}
+
+impl ActivityKind {
+    fn all() -> Vec<ActivityKind> {
+        vec![
+            ActivityKind::Push,
+            ActivityKind::Pr,
+            ActivityKind::Issue,
+            ActivityKind::Review,
+            ActivityKind::Release,
+            ActivityKind::Star,
+            ActivityKind::Fork,
+            ActivityKind::Create,
+        ]
+    }
+
+    fn from_event_type(event_type: &str) -> Option<ActivityKind> {
+        Some(match event_type {
+            "PushEvent" => ActivityKind::Push,
+            "PullRequestEvent" => ActivityKind::Pr,
+            "IssuesEvent" => ActivityKind::Issue,
+            "PullRequestReviewEvent" => ActivityKind::Review,
+            "ReleaseEvent" => ActivityKind::Release,
+            "WatchEvent" => ActivityKind::Star,
+            "ForkEvent" => ActivityKind::Fork,
+            "CreateEvent" => ActivityKind::Create,
+            _ => return None,
+        })
+    }
+}
+
+fn event_to_activity(event: GitHubEvent, types: &[ActivityKind]) -> Option<GitHubActivity> {
+    let kind = ActivityKind::from_event_type(&event.event_type)?;
+    if !types.contains(&kind) {
+        return None;
+    }
+
     let (activity_type, title, url) = match event.event_type.as_str() {
         "PushEvent" => {
             let commits = event.payload.get("commits")?.as_array()?;
@@ -354,6 +1341,34 @@ fn event_to_activity(event: GitHubEvent) -> Option<GitHubActivity> {
             let url = issue.get("html_url")?.as_str()?.to_string();
             ("issue".to_string(), title, url)
         }
+        "PullRequestReviewEvent" => {
+            let pr = event.payload.get("pull_request")?;
+            let state = event
+                .payload
+                .get("review")
+                .and_then(|r| r.get("state"))
+                .and_then(|s| s.as_str())
+                .unwrap_or("reviewed");
+            let title = format!("{}: {}", state.replace('_', " "), pr.get("title")?.as_str()?);
+            let url = event
+                .payload
+                .get("review")
+                .and_then(|r| r.get("html_url"))
+                .or_else(|| pr.get("html_url"))?
+                .as_str()?
+                .to_string();
+            ("review".to_string(), title, url)
+        }
+        "ReleaseEvent" => {
+            let release = event.payload.get("release")?;
+            let name = release
+                .get("name")
+                .and_then(|n| n.as_str())
+                .filter(|n| !n.is_empty())
+                .or_else(|| release.get("tag_name").and_then(|t| t.as_str()))?;
+            let url = release.get("html_url")?.as_str()?.to_string();
+            ("release".to_string(), format!("Released {}", name), url)
+        }
         "WatchEvent" => {
             ("star".to_string(), format!("Starred {}", event.repo.name), format!("https://github.com/{}", event.repo.name))
         }
@@ -380,17 +1395,77 @@ fn event_to_activity(event: GitHubEvent) -> Option<GitHubActivity> {
     })
 }
 
-async fn sync_to_linsa(contact: &Contact, api_url: &str) -> Result<()> {
+/// Last body successfully posted to linsa for a user, under --out-dir. Kept
+/// apart from the saved Contact, which is rewritten on every fetch whether or
+/// not it synced.
+fn synced_path(layout: &SaveLayout, username: &str) -> PathBuf {
+    layout.dir.join("synced").join(format!("{}.json", username))
+}
+
+fn load_synced(layout: &SaveLayout, username: &str) -> Option<serde_json::Value> {
+    let json = std::fs::read_to_string(synced_path(layout, username)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn save_synced(layout: &SaveLayout, username: &str, body: &serde_json::Value) -> Result<()> {
+    let path = synced_path(layout, username);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(body)?)?;
+    Ok(())
+}
+
+/// Post the contact to linsa. Returns false without posting when it matches
+/// what was last synced, unless `force` is set.
+async fn sync_to_linsa(
+    contact: &Contact,
+    api_url: &str,
+    force: bool,
+    layout: &SaveLayout,
+) -> Result<bool> {
+    let body = sync_body(contact);
+    if !force && load_synced(layout, &contact.username).as_ref() == Some(&body) {
+        eprintln!("No changes to sync for @{}", contact.username);
+        return Ok(false);
+    }
+
     // Get API key from environment
     let api_key = std::env::var("LINSA_API_KEY")
         .context("LINSA_API_KEY environment variable not set. Get one from linsa.io/settings")?;
 
     let client = reqwest::Client::new();
     let url = format!("{}/api/contacts", api_url.trim_end_matches('/'));
+    let mut request = body.clone();
+    request["api_key"] = api_key.into();
 
-    // Build request body matching the API schema
-    let body = serde_json::json!({
-        "api_key": api_key,
+    eprint!("Syncing to linsa...");
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .context("Failed to connect to linsa API")?;
+
+    if response.status().is_success() {
+        let result: serde_json::Value = response.json().await?;
+        let action = result.get("action").and_then(|a| a.as_str()).unwrap_or("synced");
+        eprintln!(" {} @{}", action, contact.username);
+        if let Err(err) = save_synced(layout, &contact.username, &body) {
+            eprintln!("warning: failed to record sync for @{}: {:#}", contact.username, err);
+        }
+        Ok(true)
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        anyhow::bail!("Linsa API error ({}): {}", status, error_text)
+    }
+}
+
+/// Request body matching the linsa API schema, minus the API key. Only
+/// fields linsa stores go in, so a refetch with no news compares equal.
+fn sync_body(contact: &Contact) -> serde_json::Value {
+    serde_json::json!({
         "name": contact.name,
         "username": contact.username,
         "platform": contact.platform,
@@ -422,27 +1497,102 @@ async fn sync_to_linsa(contact: &Contact, api_url: &str) -> Result<()> {
                 "forks": r.forks,
                 "language": r.language,
                 "updated_at": r.updated_at.to_rfc3339(),
+                "pinned": r.pinned,
             })
         }).collect::<Vec<_>>(),
-    });
+    })
+}
 
-    eprint!("Syncing to linsa...");
-    let response = client
-        .post(&url)
-        .json(&body)
-        .send()
-        .await
-        .context("Failed to connect to linsa API")?;
+// === Contributions heatmap ===
+//
+// One column per week (Sunday on top, as on GitHub), one cell per day,
+// shaded by that day's count relative to the busiest day shown.
 
-    if response.status().is_success() {
-        let result: serde_json::Value = response.json().await?;
-        let action = result.get("action").and_then(|a| a.as_str()).unwrap_or("synced");
-        eprintln!(" {} @{}", action, contact.username);
-        Ok(())
+const HEATMAP_SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Approximate contributions per day from the event list, for when there's
+/// no calendar (no token). Days without events between the first event and
+/// `until` count 0.
+fn daily_counts_from_activity(
+    activity: &[GitHubActivity],
+    until: DateTime<Utc>,
+) -> Vec<(NaiveDate, u32)> {
+    let mut counts = std::collections::BTreeMap::new();
+    for a in activity {
+        *counts.entry(a.date.date_naive()).or_insert(0) += 1;
+    }
+    let Some(&first) = counts.keys().next() else {
+        return Vec::new();
+    };
+    first
+        .iter_days()
+        .take_while(|d| *d <= until.date_naive())
+        .map(|d| (d, counts.get(&d).copied().unwrap_or(0)))
+        .collect()
+}
+
+fn heatmap_shade(count: u32, max: u32) -> char {
+    if count == 0 || max == 0 {
+        return HEATMAP_SHADES[0];
+    }
+    let steps = (HEATMAP_SHADES.len() - 1) as u32;
+    let level = (count * steps).div_ceil(max).clamp(1, steps);
+    HEATMAP_SHADES[level as usize]
+}
+
+/// Seven lines (Sun..Sat) of the heatmap for `days`, oldest week on the left.
+fn render_heatmap(days: &[(NaiveDate, u32)]) -> Vec<String> {
+    let (Some(&(first, _)), Some(&(last, _))) = (days.first(), days.last()) else {
+        return Vec::new();
+    };
+    let counts: std::collections::HashMap<NaiveDate, u32> = days.iter().copied().collect();
+    let max = counts.values().copied().max().unwrap_or(0);
+    let start = first - Duration::days(first.weekday().num_days_from_sunday() as i64);
+    let weeks = (last - start).num_days() / 7 + 1;
+
+    ["", "Mon", "", "Wed", "", "Fri", ""]
+        .iter()
+        .enumerate()
+        .map(|(row, label)| {
+            let cells: String = (0..weeks)
+                .map(|week| {
+                    let date = start + Duration::days(week * 7 + row as i64);
+                    if date < first || date > last {
+                        ' '
+                    } else {
+                        heatmap_shade(counts.get(&date).copied().unwrap_or(0), max)
+                    }
+                })
+                .collect();
+            format!("  {:<3} {}", label, cells)
+        })
+        .collect()
+}
+
+fn print_contributions(contact: &Contact) {
+    let (days, source) = if contact.contributions.is_empty() {
+        let days = daily_counts_from_activity(&contact.recent_activity, contact.last_fetched);
+        (days, "approximate, from events")
     } else {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        anyhow::bail!("Linsa API error ({}): {}", status, error_text)
+        (contact.contributions.clone(), "last year")
+    };
+    if days.is_empty() {
+        return;
+    }
+
+    let total: u32 = days.iter().map(|(_, n)| n).sum();
+    let active = days.iter().filter(|(_, n)| *n > 0).count();
+    println!(
+        "\nContributions ({}): {} over {} active days",
+        source, total, active
+    );
+    if let Some((date, count)) = days.iter().max_by_key(|(date, n)| (*n, *date)) {
+        if *count > 0 {
+            println!("  busiest day {} ({})", format_day(*date), count);
+        }
+    }
+    for line in render_heatmap(&days) {
+        println!("{}", line.trim_end());
     }
 }
 
@@ -454,8 +1604,8 @@ fn print_contact_summary(contact: &Contact) {
         println!("{}", bio);
     }
 
-    println!("\nStats: {} repos | {} followers | {} following",
-        contact.repos, contact.followers, contact.following);
+    println!("\nStats: {} repos | {} followers | {} following | activity score {:.1}",
+        contact.repos, contact.followers, contact.following, contact.activity_score);
 
     if let Some(company) = &contact.company {
         println!("Company: {}", company);
@@ -468,10 +1618,13 @@ fn print_contact_summary(contact: &Contact) {
         println!("\nTop Repos:");
         for repo in contact.top_repos.iter().take(5) {
             let lang = repo.language.as_deref().unwrap_or("?");
-            println!("  {} ({}) - {} stars", repo.name, lang, repo.stars);
+            let pinned = if repo.pinned { " [pinned]" } else { "" };
+            println!("  {} ({}) - {} stars{}", repo.name, lang, repo.stars, pinned);
         }
     }
 
+    print_contributions(contact);
+
     if !contact.recent_activity.is_empty() {
         println!("\nRecent Activity ({} events):", contact.recent_activity.len());
         for activity in contact.recent_activity.iter().take(10) {
@@ -480,3 +1633,95 @@ fn print_contact_summary(contact: &Contact) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(activity_type: &str, url: &str, date: DateTime<Utc>) -> GitHubActivity {
+        GitHubActivity {
+            activity_type: activity_type.to_string(),
+            repo: "o/r".to_string(),
+            title: String::new(),
+            url: url.to_string(),
+            date,
+        }
+    }
+
+    #[test]
+    fn name_templates_are_validated() {
+        let error = |template: &str| {
+            SaveLayout::new(None, Some(template.to_string()))
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error("{username}-{day}.json").starts_with("Unknown placeholder"));
+        assert_eq!(error("{date}.json"), "--name-template must include {username}");
+        assert!(error("/tmp/{username}.json").contains("relative to --out-dir"));
+
+        let layout = SaveLayout::new(
+            Some(PathBuf::from("/db")),
+            Some("{platform}/{username}-{date}.json".to_string()),
+        )
+        .unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        assert_eq!(
+            layout.path("alice", "github", day),
+            PathBuf::from("/db/github/alice-2024-06-03.json")
+        );
+    }
+
+    #[test]
+    fn merged_activity_drops_events_seen_at_the_same_url_and_date() {
+        let day = |d| Utc.with_ymd_and_hms(2024, 6, d, 12, 0, 0).unwrap();
+        let stored = vec![event("pr", "u1", day(1))];
+        let fetched = vec![
+            event("pr", "u1", day(1)),
+            event("pr", "u1", day(2)),
+            event("commit", "u2", day(1)),
+        ];
+        let merged: Vec<_> = merge_activity(fetched, stored)
+            .into_iter()
+            .map(|a| (a.url, a.date))
+            .collect();
+        assert_eq!(
+            merged,
+            [
+                ("u1".to_string(), day(2)),
+                ("u1".to_string(), day(1)),
+                ("u2".to_string(), day(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn score_halves_every_quarter_window() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 0, 0, 0).unwrap();
+        let since = now - Duration::days(30);
+        let score = |age_hours, kind| {
+            activity_score(&[event(kind, "u", now - Duration::hours(age_hours))], since, now)
+        };
+        assert_eq!(score(0, "pr"), 3.0);
+        assert_eq!(score(7 * 24 + 12, "pr"), 1.5);
+        // 3/16, rounded to cents
+        assert_eq!(score(30 * 24, "pr"), 0.19);
+        assert_eq!(score(0, "unknown"), 0.0);
+    }
+
+    #[test]
+    fn heatmap_aligns_weeks_to_sunday_and_shades_by_max() {
+        // Wednesday 2024-06-05 through Monday 2024-06-10
+        let first = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+        let days: Vec<_> = [4, 0, 1, 0, 0, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(i, n)| (first + Duration::days(i as i64), n))
+            .collect();
+        let lines = render_heatmap(&days);
+        let cells: Vec<&str> = lines.iter().map(|line| &line[6..]).collect();
+        assert_eq!(cells, [" ·", " ▒", "  ", "█ ", "· ", "░ ", "· "]);
+        assert!(lines[1].starts_with("  Mon "));
+        assert!(render_heatmap(&[]).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 //! linsa: shared functionality crate.
 
+pub mod secrets;
+
 /// Placeholder helper to keep the crate compiling; replace with real functionality.
 pub fn add(left: i32, right: i32) -> i32 {
     left + right
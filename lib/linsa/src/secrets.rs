@@ -0,0 +1,20 @@
+//! Credential patterns shared by ctx (scrubbing) and flow (secret scans).
+
+/// Known secret shapes as (name, regex). When a regex has a `secret` group,
+/// only that part is the secret, so `.env` keys stay readable. Matches may
+/// span lines (private keys), so run these over whole files.
+pub const SECRET_PATTERNS: &[(&str, &str)] = &[
+    ("aws access key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("openai/anthropic key", r"\bsk-(?:proj-|ant-)?[A-Za-z0-9_-]{20,}"),
+    ("github token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    ("jwt", r"\beyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]+"),
+    (
+        "private key",
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+    ),
+    (
+        "env secret",
+        r#"(?m)^[ \t]*(?:export[ \t]+)?[A-Z0-9_]*(?:SECRET|TOKEN|PASSWORD|PASSWD|API_KEY|APIKEY|PRIVATE_KEY|ACCESS_KEY)[A-Z0-9_]*=(?P<secret>"[^"\n]+"|'[^'\n]+'|[^\s"'$#][^\s#]*)"#,
+    ),
+];